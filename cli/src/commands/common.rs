@@ -47,6 +47,13 @@ pub fn get_common_commands<'a, 'b>() -> App<'a, 'b> {
                         .required(true)
                         .takes_value(true)
                         .help("Company fund auth pubkey."),
+                )
+                .arg(
+                    Arg::with_name("position_nft_uri")
+                        .value_name("URI")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Base URI of the staking position NFT metadata."),
                 ),
         )
 }
@@ -1,4 +1,5 @@
 use super::CMDS_DISTRIBUTION;
+use crate::projection::parse_target_time;
 use clap::{App, AppSettings, Arg, SubCommand};
 use solana_clap_utils::input_validators::is_valid_pubkey;
 
@@ -6,8 +7,21 @@ pub const CMD_INITIALIZE: &str = "initialize";
 pub const CMD_DISTRIBUTE: &str = "distribute";
 pub const CMD_UPDATE_ADMIN: &str = "update-admin";
 pub const CMD_SET_UPDATE_AUTHORITY: &str = "set-update-authority";
-pub const CMD_UPDATE_SHARES: &str = "update-shares";
+pub const CMD_UPDATE_RECIPIENTS_TIMELOCK: &str = "update-recipients-timelock";
+pub const CMD_EXECUTE_RECIPIENTS_TIMELOCK: &str = "execute-recipients-timelock";
+pub const CMD_PROPOSE_RECIPIENTS: &str = "propose-recipients";
+pub const CMD_CANCEL_RECIPIENTS: &str = "cancel-recipients";
+pub const CMD_EXECUTE_RECIPIENTS: &str = "execute-recipients";
+pub const CMD_SHOW_PENDING_RECIPIENTS: &str = "show-pending-recipients";
+pub const CMD_UPDATE_MIN_DISTRIBUTION_INTERVAL: &str = "update-min-distribution-interval";
+pub const CMD_UPDATE_MIN_DISTRIBUTION_AMOUNT: &str = "update-min-distribution-amount";
+pub const CMD_UPDATE_KEEPER_REWARD_SHARE: &str = "update-keeper-reward-share";
+pub const CMD_UPDATE_BURN_SHARE: &str = "update-burn-share";
 pub const CMD_SHOW_FUNDS_INFO: &str = "show-funds-info";
+pub const CMD_HISTORY: &str = "history";
+pub const CMD_CREATE_VESTING_ESCROW: &str = "create-vesting-escrow";
+pub const CMD_CLAIM_VESTED: &str = "claim-vested";
+pub const CMD_SHOW_VESTING_ESCROW: &str = "show-vesting-escrow";
 
 pub fn get_distribution_commands<'a, 'b>() -> App<'a, 'b> {
     SubCommand::with_name(CMDS_DISTRIBUTION)
@@ -33,69 +47,168 @@ pub fn get_distribution_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The GGWP Token (mint) pubkey."),
                 )
                 .arg(
-                    Arg::with_name("play_to_earn_fund")
+                    Arg::with_name("recipients")
+                        .value_name("FUND:SHARE_BPS[:VESTING_ESCROW]")
+                        .required(true)
+                        .multiple(true)
+                        .takes_value(true)
+                        .help("The recipient fund wallets with shares, e.g. <FUND>:4500 <ESCROW_WALLET>:5500:<VESTING_ESCROW>."),
+                )
+                .arg(
+                    Arg::with_name("dust_recipient")
+                        .long("dust-recipient")
                         .value_name("PUBKEY")
                         .validator(is_valid_pubkey)
-                        .required(true)
                         .takes_value(true)
-                        .help("The play to earn fund wallet pubkey."),
+                        .help("The recipient fund wallet getting rounding dust. The last recipient by default."),
                 )
                 .arg(
-                    Arg::with_name("play_to_earn_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("recipients_timelock")
+                        .long("recipients-timelock")
+                        .value_name("SECONDS")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The delay between proposing and executing the recipients change."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_DISTRIBUTE)
+                .about("Anyone can start the distribution of GGWP tokens.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The play to earn fund share in percent."),
+                        .help("The distribution info account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_ADMIN)
+                .about("Admin can set the new admin.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("staking_fund")
+                    Arg::with_name("admin")
                         .value_name("PUBKEY")
                         .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The staking fund wallet pubkey."),
+                        .help("The new admin address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_SET_UPDATE_AUTHORITY)
+                .about("Admin can set the new update authority.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("staking_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("update_auth")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new update authority address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_RECIPIENTS_TIMELOCK)
+                .about("Admin can set the new delay between proposing and executing the recipients change. Reduction is queued behind the current delay.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The staking fund share in percent."),
+                        .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("company_fund")
+                    Arg::with_name("recipients_timelock")
+                        .value_name("i64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new timelock in seconds."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_EXECUTE_RECIPIENTS_TIMELOCK)
+                .about("Apply the pending recipients timelock reduction after the current timelock.")
+                .arg(
+                    Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
                         .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The company fund wallet pubkey."),
+                        .help("The distribution info account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_PROPOSE_RECIPIENTS)
+                .about("Update authority can queue the new recipient funds and shares.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("company_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("recipients")
+                        .value_name("FUND:SHARE_BPS[:VESTING_ESCROW]")
                         .required(true)
+                        .multiple(true)
                         .takes_value(true)
-                        .help("The company fund share in percent."),
+                        .help("The recipient fund wallets with shares, e.g. <FUND>:4500 <ESCROW_WALLET>:5500:<VESTING_ESCROW>."),
                 )
                 .arg(
-                    Arg::with_name("team_fund")
+                    Arg::with_name("dust_recipient")
+                        .long("dust-recipient")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The recipient fund wallet getting rounding dust. The last recipient by default."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CANCEL_RECIPIENTS)
+                .about("Admin can cancel the pending recipients change.")
+                .arg(
+                    Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
                         .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The team fund wallet pubkey."),
-                )
+                        .help("The distribution info account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_EXECUTE_RECIPIENTS)
+                .about("Apply the pending recipients change after the timelock.")
                 .arg(
-                    Arg::with_name("team_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The team fund share in percent."),
+                        .help("The distribution info account address."),
                 ),
         )
         .subcommand(
-            SubCommand::with_name(CMD_DISTRIBUTE)
-                .about("Anyone can start the distribution of GGWP tokens.")
+            SubCommand::with_name(CMD_SHOW_PENDING_RECIPIENTS)
+                .about("Show the pending recipients change.")
                 .arg(
                     Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
@@ -106,8 +219,8 @@ pub fn get_distribution_commands<'a, 'b>() -> App<'a, 'b> {
                 ),
         )
         .subcommand(
-            SubCommand::with_name(CMD_UPDATE_ADMIN)
-                .about("Admin can set the new admin.")
+            SubCommand::with_name(CMD_UPDATE_MIN_DISTRIBUTION_INTERVAL)
+                .about("Update authority can set the new min interval between distributions.")
                 .arg(
                     Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
@@ -117,17 +230,35 @@ pub fn get_distribution_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("admin")
+                    Arg::with_name("min_distribution_interval")
+                        .value_name("i64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new min interval in seconds."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_MIN_DISTRIBUTION_AMOUNT)
+                .about("Update authority can set the new min accumulative fund amount to distribute.")
+                .arg(
+                    Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
                         .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The new admin address."),
+                        .help("The distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .value_name("f64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new min GGWP amount."),
                 ),
         )
         .subcommand(
-            SubCommand::with_name(CMD_SET_UPDATE_AUTHORITY)
-                .about("Admin can set the new update authority.")
+            SubCommand::with_name(CMD_UPDATE_KEEPER_REWARD_SHARE)
+                .about("Update authority can set the new keeper reward share.")
                 .arg(
                     Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
@@ -137,17 +268,46 @@ pub fn get_distribution_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("update_auth")
+                    Arg::with_name("keeper_reward_share")
+                        .value_name("u16")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new keeper reward share in basis points."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_BURN_SHARE)
+                .about("Update authority can set the new burn share.")
+                .arg(
+                    Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
                         .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The new update authority address."),
+                        .help("The distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("burn_share")
+                        .value_name("u16")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new burn share in basis points."),
                 ),
         )
         .subcommand(
-            SubCommand::with_name(CMD_UPDATE_SHARES)
-                .about("Update authority can set the new fund shares.")
+            SubCommand::with_name(CMD_SHOW_FUNDS_INFO)
+                .about("Show information about funds.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The distribution info account address."),
+                ),
+        )        .subcommand(
+            SubCommand::with_name(CMD_HISTORY)
+                .about("Show the last distributions.")
                 .arg(
                     Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
@@ -157,37 +317,57 @@ pub fn get_distribution_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("play_to_earn_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("csv")
+                        .long("csv")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .help("Export the history into the CSV file."),
+                ),
+        )        .subcommand(
+            SubCommand::with_name(CMD_CREATE_VESTING_ESCROW)
+                .about("Admin can create the vesting escrow of the fund wallet. Set the escrow wallet as the recipient to lock the fund share.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The play to earn fund share in percent."),
+                        .help("The distribution info account address."),
                 )
                 .arg(
-                    Arg::with_name("staking_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("beneficiary_wallet")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
                         .required(true)
                         .takes_value(true)
-                        .help("The staking fund share in percent."),
+                        .help("The beneficiary fund wallet pubkey."),
                 )
                 .arg(
-                    Arg::with_name("company_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("cliff_period")
+                        .value_name("i64")
                         .required(true)
                         .takes_value(true)
-                        .help("The company fund share in percent."),
+                        .help("The cliff period in seconds."),
                 )
                 .arg(
-                    Arg::with_name("team_fund_share")
-                        .value_name("u8")
+                    Arg::with_name("vesting_period")
+                        .value_name("i64")
                         .required(true)
                         .takes_value(true)
-                        .help("The team fund share in percent."),
+                        .help("The vesting period in seconds."),
+                )
+                .arg(
+                    Arg::with_name("start")
+                        .long("start")
+                        .value_name("DATE")
+                        .validator(|value| parse_target_time(&value).map(|_| ()))
+                        .takes_value(true)
+                        .help("The vesting start date as YYYY-MM-DD or unix timestamp. Now by default."),
                 ),
         )
         .subcommand(
-            SubCommand::with_name(CMD_SHOW_FUNDS_INFO)
-                .about("Show information about funds.")
+            SubCommand::with_name(CMD_CLAIM_VESTED)
+                .about("Owner of the beneficiary fund wallet can claim vested GGWP.")
                 .arg(
                     Arg::with_name("distribution_info")
                         .value_name("PUBKEY")
@@ -195,6 +375,34 @@ pub fn get_distribution_commands<'a, 'b>() -> App<'a, 'b> {
                         .required(true)
                         .takes_value(true)
                         .help("The distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("beneficiary_wallet")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The beneficiary fund wallet pubkey."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_SHOW_VESTING_ESCROW)
+                .about("Show information about the vesting escrow.")
+                .arg(
+                    Arg::with_name("distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("beneficiary_wallet")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The beneficiary fund wallet pubkey."),
                 ),
         )
 }
@@ -1,4 +1,5 @@
 use super::CMDS_FREEZING;
+use crate::projection::parse_target_time;
 use clap::{App, AppSettings, Arg, SubCommand};
 use solana_clap_utils::input_validators::is_valid_pubkey;
 
@@ -10,11 +11,22 @@ pub const CMD_UPDATE_UNFREEZE_ROYALTY: &str = "update-unfreeze-royalty";
 pub const CMD_UPDATE_REWARD_TABLE: &str = "update-reward-table";
 pub const CMD_UPDATE_REWARD_PERIOD: &str = "update-reward-period";
 pub const CMD_UPDATE_UNFREEZE_LOCK_PERIOD: &str = "update-unfreeze-lock-period";
+pub const CMD_UPDATE_REFERRAL_SHARE: &str = "update-referral-share";
+pub const CMD_MIGRATE_INFO: &str = "migrate-info";
+pub const CMD_MIGRATE_USER_INFO: &str = "migrate-user-info";
+pub const CMD_REGISTER_REFERRER: &str = "register-referrer";
+pub const CMD_CLAIM_REFERRAL_REWARDS: &str = "claim-referral-rewards";
 pub const CMD_FREEZE: &str = "freeze";
 pub const CMD_WITHDRAW_GPASS: &str = "withdraw-gpass";
 pub const CMD_UNFREEZE: &str = "unfreeze";
+pub const CMD_UPDATE_AUTO_CLAIM: &str = "update-auto-claim";
+pub const CMD_DEPOSIT_TIP_BUDGET: &str = "deposit-tip-budget";
+pub const CMD_WITHDRAW_GPASS_FOR: &str = "withdraw-gpass-for";
+pub const CMD_KEEPER: &str = "keeper";
 pub const CMD_SHOW_INFO: &str = "show-info";
 pub const CMD_SHOW_USER_INFO: &str = "show-user-info";
+pub const CMD_SHOW_REFERRER_INFO: &str = "show-referrer-info";
+pub const CMD_ESTIMATE: &str = "estimate";
 
 pub fn get_freezing_commands<'a, 'b>() -> App<'a, 'b> {
     SubCommand::with_name(CMDS_FREEZING)
@@ -248,6 +260,81 @@ pub fn get_freezing_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The new reward period value in second."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_REFERRAL_SHARE)
+                .about("Update authority can set the new referral share of royalty in percent.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("referral_share")
+                        .value_name("u8")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new referral share value."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_MIGRATE_INFO)
+                .about("Admin can reallocate the freezing info created before the referral share.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_MIGRATE_USER_INFO)
+                .about("Anyone can reallocate the user info created before the auto claim.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("user")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The user system account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_REGISTER_REFERRER)
+                .about("Register the referral code (payer pubkey) for getting referral rewards.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CLAIM_REFERRAL_REWARDS)
+                .about("Referrer can claim the referral rewards to his GGWP wallet.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                ),
+        )
         .subcommand(
             SubCommand::with_name(CMD_FREEZE)
                 .about("User can freeze the amount of GGWP for getting rewards in GPASS.")
@@ -265,6 +352,14 @@ pub fn get_freezing_commands<'a, 'b>() -> App<'a, 'b> {
                         .required(true)
                         .takes_value(true)
                         .help("The amount to freeze."),
+                )
+                .arg(
+                    Arg::with_name("referrer")
+                        .long("referrer")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The referral code (referrer pubkey), used on the first freeze."),
                 ),
         )
         .subcommand(
@@ -291,6 +386,92 @@ pub fn get_freezing_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The freezing info account address."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_AUTO_CLAIM)
+                .about("User can allow anyone to withdraw his earned GPASS.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("auto_claim")
+                        .value_name("bool")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Enable or disable auto claim."),
+                )
+                .arg(
+                    Arg::with_name("keeper_tip")
+                        .long("tip")
+                        .value_name("ui_amount (f64)")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("The GGWP tip paid to keeper for every claim."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_DEPOSIT_TIP_BUDGET)
+                .about("User can deposit GGWP for paying keeper tips (returned on unfreeze).")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .value_name("ui_amount (f64)")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The amount to deposit."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_WITHDRAW_GPASS_FOR)
+                .about("Anyone can withdraw earned GPASS for user with enabled auto claim.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("user")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The user system account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_KEEPER)
+                .about("Run the keeper loop withdrawing GPASS for all users with auto claim.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("u64")
+                        .takes_value(true)
+                        .default_value("60")
+                        .help("The interval between scans in seconds."),
+                ),
+        )
         .subcommand(
             SubCommand::with_name(CMD_SHOW_INFO)
                 .about("Show freezing info.")
@@ -323,4 +504,64 @@ pub fn get_freezing_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The user system account address."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name(CMD_SHOW_REFERRER_INFO)
+                .about("Show the referrer info account with referral earnings.")
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("referrer")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The referrer system account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_ESTIMATE)
+                .about(
+                    "Estimate the GPASS earned by user or hypothetical freeze on the target date.",
+                )
+                .arg(
+                    Arg::with_name("freezing_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The freezing info account address."),
+                )
+                .arg(
+                    Arg::with_name("date")
+                        .long("date")
+                        .value_name("DATE")
+                        .validator(|value| parse_target_time(&value).map(|_| ()))
+                        .required(true)
+                        .takes_value(true)
+                        .help("The target date as YYYY-MM-DD or unix timestamp."),
+                )
+                .arg(
+                    Arg::with_name("user")
+                        .long("user")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required_unless("amount")
+                        .conflicts_with("amount")
+                        .help("The user system account address."),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("f64")
+                        .takes_value(true)
+                        .help("The hypothetical GGWP amount to freeze now."),
+                ),
+        )
 }
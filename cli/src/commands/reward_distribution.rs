@@ -7,6 +7,15 @@ pub const CMD_UPDATE_ADMIN: &str = "update-admin";
 pub const CMD_SET_UPDATE_AUTHORITY: &str = "set-update-authority";
 pub const CMD_UPDATE_TRANSFER_AUTH_LIST: &str = "update-transfer-auth-list";
 pub const CMD_SHOW_INFO: &str = "show-info";
+pub const CMD_SET_TRANSFER_ALLOWANCE: &str = "set-transfer-allowance";
+pub const CMD_SHOW_TRANSFER_ALLOWANCE: &str = "show-transfer-allowance";
+pub const CMD_BATCH_TRANSFER: &str = "batch-transfer";
+pub const CMD_CREATE_CLAIM_CAMPAIGN: &str = "create-claim-campaign";
+pub const CMD_CLAIM_FROM_CAMPAIGN: &str = "claim-from-campaign";
+pub const CMD_SHOW_CLAIM_CAMPAIGN: &str = "show-claim-campaign";
+pub const CMD_UPDATE_VESTING_DURATION: &str = "update-vesting-duration";
+pub const CMD_CLAIM: &str = "claim";
+pub const CMD_SHOW_VESTING_SCHEDULE: &str = "show-vesting-schedule";
 
 pub fn get_reward_distribution_commands<'a, 'b>() -> App<'a, 'b> {
     SubCommand::with_name(CMDS_REWARD_DISTRIBUTION)
@@ -122,4 +131,177 @@ pub fn get_reward_distribution_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The reward distribution info account address."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name(CMD_SET_TRANSFER_ALLOWANCE)
+                .about("Admin can create or update the allowance of the transfer authority.")
+                .arg(
+                    Arg::with_name("reward_distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The reward distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("authority")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The transfer authority pubkey."),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .value_name("f64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The GGWP amount allowed per window."),
+                )
+                .arg(
+                    Arg::with_name("window")
+                        .value_name("i64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The window in seconds."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_SHOW_TRANSFER_ALLOWANCE)
+                .about("Show the allowance of the transfer authority.")
+                .arg(
+                    Arg::with_name("reward_distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The reward distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("authority")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The transfer authority pubkey."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_BATCH_TRANSFER)
+                .about("Transfer authority can send GGWP to many recipients.")
+                .arg(
+                    Arg::with_name("reward_distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The reward distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .value_name("FILE")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The CSV file with recipient token account,amount rows. Amount in GGWP."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CREATE_CLAIM_CAMPAIGN)
+                .about("Transfer authority can create the campaign of rewards claimed by users.")
+                .arg(
+                    Arg::with_name("reward_distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The reward distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .value_name("FILE")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The CSV file with user,amount rows. Amount in GGWP."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CLAIM_FROM_CAMPAIGN)
+                .about("Claim the campaign reward of the payer.")
+                .arg(
+                    Arg::with_name("claim_campaign")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The claim campaign account address."),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .value_name("FILE")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The CSV file the campaign was created with."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_SHOW_CLAIM_CAMPAIGN)
+                .about("Show information about the claim campaign.")
+                .arg(
+                    Arg::with_name("claim_campaign")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The claim campaign account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_VESTING_DURATION)
+                .about("Update authority can set the vesting duration of vested transfers.")
+                .arg(
+                    Arg::with_name("reward_distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The reward distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("vesting_duration")
+                        .value_name("SECONDS")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The vesting duration in seconds. Zero disables vesting."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CLAIM)
+                .about("Claim the vested rewards of the payer.")
+                .arg(
+                    Arg::with_name("reward_distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The reward distribution info account address."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_SHOW_VESTING_SCHEDULE)
+                .about("Show the vesting schedule of the user or of all users.")
+                .arg(
+                    Arg::with_name("reward_distribution_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The reward distribution info account address."),
+                )
+                .arg(
+                    Arg::with_name("user")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The user pubkey. All users when omitted."),
+                ),
+        )
 }
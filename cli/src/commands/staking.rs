@@ -1,4 +1,5 @@
 use super::CMDS_STAKING;
+use crate::projection::parse_target_time;
 use clap::{App, AppSettings, Arg, SubCommand};
 use solana_clap_utils::input_validators::is_valid_pubkey;
 
@@ -10,10 +11,23 @@ pub const CMD_UPDATE_MIN_STAKE_AMOUNT: &str = "update-min-stake-amount";
 pub const CMD_UPDATE_HOLD_PERIOD_DAYS: &str = "update-hold-period-days";
 pub const CMD_UPDATE_HOLD_ROYALTY: &str = "update-hold-royalty";
 pub const CMD_UPDATE_ROYALTY: &str = "update-royalty";
+pub const CMD_UPDATE_POSITION_NFT_URI: &str = "update-position-nft-uri";
+pub const CMD_UPDATE_LOCKUP_OPTIONS: &str = "update-lockup-options";
+pub const CMD_ADVANCE_EPOCH: &str = "advance-epoch";
 pub const CMD_STAKE: &str = "stake";
+pub const CMD_STAKE_FOR: &str = "stake-for";
 pub const CMD_WITHDRAW: &str = "withdraw";
+pub const CMD_CLAIM_REWARDS: &str = "claim-rewards";
+pub const CMD_SET_AUTO_COMPOUND: &str = "set-auto-compound";
+pub const CMD_COMPOUND: &str = "compound";
+pub const CMD_CLAIM_IOU: &str = "claim-iou";
 pub const CMD_SHOW_STAKING_INFO: &str = "show-staking-info";
 pub const CMD_SHOW_USER_INFO: &str = "show-user-info";
+pub const CMD_SHOW_EPOCH_HISTORY: &str = "show-epoch-history";
+pub const CMD_POSITIONS: &str = "positions";
+pub const CMD_UPDATE_APR_SCHEDULE: &str = "update-apr-schedule";
+pub const CMD_SHOW_APR_SCHEDULE: &str = "show-apr-schedule";
+pub const CMD_ESTIMATE: &str = "estimate";
 
 pub fn get_staking_commands<'a, 'b>() -> App<'a, 'b> {
     SubCommand::with_name(CMDS_STAKING)
@@ -109,6 +123,23 @@ pub fn get_staking_commands<'a, 'b>() -> App<'a, 'b> {
                         .required(true)
                         .takes_value(true)
                         .help("The end APR percent value."),
+                )
+                .arg(
+                    Arg::with_name("pool_mode")
+                        .long("pool-mode")
+                        .value_name("MODE")
+                        .possible_values(&["fixed-apr", "reward-per-share"])
+                        .default_value("fixed-apr")
+                        .takes_value(true)
+                        .help("The rewards mode: fixed APR or staking fund deposits spread pro rata."),
+                )
+                .arg(
+                    Arg::with_name("position_nft_uri")
+                        .long("position-nft-uri")
+                        .value_name("URI")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The position NFT metadata base URI, <URI>/<POSITION>.json describes the position."),
                 ),
         )
         .subcommand(
@@ -246,6 +277,76 @@ pub fn get_staking_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The new royalty in percent."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_POSITION_NFT_URI)
+                .about("Update authority can set the new position NFT metadata base URI.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("position_nft_uri")
+                        .value_name("URI")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The new position NFT metadata base URI."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_LOCKUP_OPTIONS)
+                .about("Update authority can set the lockup options table.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("lockup_options")
+                        .value_name("DAYS:BOOST_BPS")
+                        .required(true)
+                        .multiple(true)
+                        .takes_value(true)
+                        .help("The lockup options, e.g. 0:10000 90:12500 365:20000."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_UPDATE_APR_SCHEDULE)
+                .about("Update authority can schedule APR of the future epochs from a file.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .value_name("PATH")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The file with `epoch,apr_bps` lines of contiguous epochs."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_ADVANCE_EPOCH)
+                .about("Move the staking to the current epoch and record the applied APR.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                ),
+        )
         .subcommand(
             SubCommand::with_name(CMD_STAKE)
                 .about("User can stake the amount of GGWP.")
@@ -263,11 +364,182 @@ pub fn get_staking_commands<'a, 'b>() -> App<'a, 'b> {
                         .required(true)
                         .takes_value(true)
                         .help("The GGWP amount to stake."),
+                )
+                .arg(
+                    Arg::with_name("lockup")
+                        .long("lockup")
+                        .value_name("u8")
+                        .default_value("0")
+                        .takes_value(true)
+                        .help("The lockup option index."),
+                )
+                .arg(
+                    Arg::with_name("auto_compound")
+                        .long("auto-compound")
+                        .takes_value(false)
+                        .help("Compound the position rewards at each epoch."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_STAKE_FOR)
+                .about("Funder can stake the amount of GGWP into the new position of beneficiary.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("beneficiary")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The owner of the new position."),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .value_name("f64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The GGWP amount to stake."),
+                )
+                .arg(
+                    Arg::with_name("lockup")
+                        .long("lockup")
+                        .value_name("u8")
+                        .default_value("0")
+                        .takes_value(true)
+                        .help("The lockup option index."),
+                )
+                .arg(
+                    Arg::with_name("auto_compound")
+                        .long("auto-compound")
+                        .takes_value(false)
+                        .help("Compound the position rewards at each epoch."),
                 ),
         )
         .subcommand(
             SubCommand::with_name(CMD_WITHDRAW)
-                .about("User can withdraw the full amount of GGWP with rewards.")
+                .about("Position NFT holder can withdraw the full amount of GGWP of the position with rewards.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("position")
+                        .value_name("u64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The position index."),
+                )
+                .arg(
+                    Arg::with_name("staker")
+                        .long("staker")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The user who created the position. Payer by default."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CLAIM_REWARDS)
+                .about("Position NFT holder can claim rewards of the position without withdraw.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("position")
+                        .value_name("u64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The position index."),
+                )
+                .arg(
+                    Arg::with_name("staker")
+                        .long("staker")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The user who created the position. Payer by default."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_SET_AUTO_COMPOUND)
+                .about("Position NFT holder can switch compounding of the position rewards.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("position")
+                        .value_name("u64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The position index."),
+                )
+                .arg(
+                    Arg::with_name("auto_compound")
+                        .value_name("bool")
+                        .required(true)
+                        .takes_value(true)
+                        .possible_values(&["true", "false"])
+                        .help("Compound the position rewards at each epoch."),
+                )
+                .arg(
+                    Arg::with_name("staker")
+                        .long("staker")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The user who created the position. Payer by default."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_COMPOUND)
+                .about("Anyone can compound rewards of the completed epochs into the auto-compounding position.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("position")
+                        .value_name("u64")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The position index."),
+                )
+                .arg(
+                    Arg::with_name("staker")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The user who created the position."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_CLAIM_IOU)
+                .about("User can claim the unpaid rewards IOU.")
                 .arg(
                     Arg::with_name("staking_info")
                         .value_name("PUBKEY")
@@ -301,4 +573,103 @@ pub fn get_staking_commands<'a, 'b>() -> App<'a, 'b> {
                         .help("The staking info pubkey."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name(CMD_SHOW_EPOCH_HISTORY)
+                .about("Show the applied APR of the completed epochs.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_SHOW_APR_SCHEDULE)
+                .about("Show the scheduled APR of the epochs in basis points.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_POSITIONS)
+                .about("Show the user positions with projected rewards.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("user")
+                        .long("user")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("The user pubkey. Payer by default."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_ESTIMATE)
+                .about("Estimate the rewards of user positions or hypothetical stake on the target date.")
+                .arg(
+                    Arg::with_name("staking_info")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .takes_value(true)
+                        .help("The staking info pubkey."),
+                )
+                .arg(
+                    Arg::with_name("date")
+                        .long("date")
+                        .value_name("DATE")
+                        .validator(|value| parse_target_time(&value).map(|_| ()))
+                        .required(true)
+                        .takes_value(true)
+                        .help("The target date as YYYY-MM-DD or unix timestamp."),
+                )
+                .arg(
+                    Arg::with_name("user")
+                        .long("user")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required_unless("amount")
+                        .conflicts_with("amount")
+                        .help("The user pubkey to estimate the positions of."),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("f64")
+                        .takes_value(true)
+                        .help("The hypothetical GGWP amount to stake."),
+                )
+                .arg(
+                    Arg::with_name("stake_date")
+                        .long("stake-date")
+                        .value_name("DATE")
+                        .validator(|value| parse_target_time(&value).map(|_| ()))
+                        .takes_value(true)
+                        .requires("amount")
+                        .help("The hypothetical stake date as YYYY-MM-DD or unix timestamp. Now by default."),
+                )
+                .arg(
+                    Arg::with_name("lockup")
+                        .long("lockup")
+                        .value_name("u8")
+                        .default_value("0")
+                        .takes_value(true)
+                        .help("The hypothetical stake lockup option index."),
+                ),
+        )
 }
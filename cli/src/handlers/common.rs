@@ -2,6 +2,7 @@ use super::utils::get_or_create_token_account;
 use crate::commands;
 use crate::handlers::utils::get_token_mint_data;
 use anchor_client::anchor_lang::system_program;
+use anchor_client::solana_sdk::instruction::AccountMeta;
 use anchor_client::solana_sdk::program_option::COption;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::signer::Signer;
@@ -9,14 +10,15 @@ use anchor_client::{solana_sdk::pubkey::Pubkey, Client, Program};
 use anchor_client::{ClientError, Cluster};
 use clap::value_t_or_exit;
 use clap::{ArgMatches, Error};
-use distribution::state::DistributionInfo;
+use distribution::state::{DistributionInfo, Recipient, VestingEscrow};
 use fighting::state::{FightingSettings, GPASS_BURN_AUTH_SEED};
 use freezing::state::{FreezingInfo, RewardTableRow, GPASS_MINT_AUTH_SEED};
 use gpass::state::GpassInfo;
 use spl_token::amount_to_ui_amount;
 use spl_token::state::Mint;
-use staking::state::{StakingInfo, STAKING_FUND_AUTH_SEED};
+use staking::state::{PoolMode, StakingInfo, STAKING_FUND_AUTH_SEED};
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn handle(
     cmd_matches: &ArgMatches,
@@ -48,6 +50,7 @@ pub fn handle(
             let ggwp_token = value_t_or_exit!(arg_matches, "ggwp_token", Pubkey);
             let team_fund_auth = value_t_or_exit!(arg_matches, "team_fund_auth", Pubkey);
             let company_fund_auth = value_t_or_exit!(arg_matches, "company_fund_auth", Pubkey);
+            let position_nft_uri = value_t_or_exit!(arg_matches, "position_nft_uri", String);
 
             cmd_init_all(
                 gpass_program,
@@ -61,6 +64,7 @@ pub fn handle(
                 ggwp_token,
                 team_fund_auth,
                 company_fund_auth,
+                position_nft_uri,
             )
             .expect("Init all command error");
 
@@ -86,6 +90,7 @@ pub fn cmd_init_all(
     ggwp_token: Pubkey,
     team_fund_auth: Pubkey,
     company_fund_auth: Pubkey,
+    position_nft_uri: String,
 ) -> Result<(), ClientError> {
     // TODO: deploy command
     // Initial checks
@@ -184,6 +189,24 @@ pub fn cmd_init_all(
     );
     println!("Staking treasury auth: {}", staking_treasury_auth);
 
+    let (staking_epoch_history, _) = Pubkey::find_program_address(
+        &[
+            staking::state::EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.pubkey().as_ref(),
+        ],
+        &staking_program.id(),
+    );
+    println!("Staking epoch history: {}", staking_epoch_history);
+
+    let (staking_apr_schedule, _) = Pubkey::find_program_address(
+        &[
+            staking::state::APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.pubkey().as_ref(),
+        ],
+        &staking_program.id(),
+    );
+    println!("Staking APR schedule: {}", staking_apr_schedule);
+
     let (accumulative_fund_auth, _) = Pubkey::find_program_address(
         &[
             distribution::state::ACCUMULATIVE_FUND_AUTH_SEED.as_bytes(),
@@ -193,6 +216,15 @@ pub fn cmd_init_all(
     );
     println!("Accumulative fund auth: {}", accumulative_fund_auth);
 
+    let (distribution_history, _) = Pubkey::find_program_address(
+        &[
+            distribution::state::DISTRIBUTION_HISTORY_SEED.as_bytes(),
+            distribution_info.pubkey().as_ref(),
+        ],
+        &distribution_program.id(),
+    );
+    println!("Distribution history: {}", distribution_history);
+
     let (play_to_earn_fund_auth, _) = Pubkey::find_program_address(
         &[
             reward_distribution::state::PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
@@ -248,35 +280,133 @@ pub fn cmd_init_all(
         company_fund_auth, company_fund
     );
     println!("Team fund (owner: {}): {}", team_fund_auth, team_fund);
+
+    // Team and company shares are locked in vesting escrows
+    let get_vesting_escrow_pks = |beneficiary_wallet: Pubkey| {
+        let (vesting_escrow, _) = Pubkey::find_program_address(
+            &[
+                distribution::state::VESTING_ESCROW_SEED.as_bytes(),
+                distribution_info.pubkey().as_ref(),
+                beneficiary_wallet.as_ref(),
+            ],
+            &distribution_program.id(),
+        );
+        let (escrow_auth, _) = Pubkey::find_program_address(
+            &[
+                distribution::state::VESTING_ESCROW_AUTH_SEED.as_bytes(),
+                vesting_escrow.as_ref(),
+            ],
+            &distribution_program.id(),
+        );
+        (vesting_escrow, escrow_auth)
+    };
+    let (team_vesting_escrow, team_escrow_auth) = get_vesting_escrow_pks(team_fund);
+    let (company_vesting_escrow, company_escrow_auth) = get_vesting_escrow_pks(company_fund);
+    let team_escrow_wallet =
+        get_or_create_token_account(&gpass_program, ggwp_token, team_escrow_auth)?;
+    let company_escrow_wallet =
+        get_or_create_token_account(&gpass_program, ggwp_token, company_escrow_auth)?;
+    println!(
+        "Company escrow wallet (owner: {}): {}",
+        company_escrow_auth, company_escrow_wallet
+    );
+    println!(
+        "Team escrow wallet (owner: {}): {}",
+        team_escrow_auth, team_escrow_wallet
+    );
     println!();
 
     // Init distribution with funds info
+    let distribution_recipients = vec![
+        Recipient {
+            fund: play_to_earn_fund,
+            share: params.distribution.play_to_earn_fund_share,
+            vesting_escrow: Pubkey::default(),
+        },
+        Recipient {
+            fund: staking_fund,
+            share: params.distribution.staking_fund_share,
+            vesting_escrow: Pubkey::default(),
+        },
+        Recipient {
+            fund: company_escrow_wallet,
+            share: params.distribution.company_fund_share,
+            vesting_escrow: company_vesting_escrow,
+        },
+        Recipient {
+            fund: team_escrow_wallet,
+            share: params.distribution.team_fund_share,
+            vesting_escrow: team_vesting_escrow,
+        },
+    ];
     distribution_program
         .request()
         .accounts(distribution::accounts::Initialize {
             admin: admin_pk,
             distribution_info: distribution_info.pubkey(),
+            distribution_history: distribution_history,
             ggwp_token: ggwp_token,
             accumulative_fund: accumulative_fund,
             accumulative_fund_auth: accumulative_fund_auth,
-            play_to_earn_fund: play_to_earn_fund,
-            staking_fund: staking_fund,
-            company_fund: company_fund,
-            team_fund: team_fund,
             system_program: system_program::ID,
         })
+        .accounts(
+            distribution_recipients
+                .iter()
+                .map(|recipient| AccountMeta::new(recipient.fund, false))
+                .collect::<Vec<AccountMeta>>(),
+        )
         .args(distribution::instruction::Initialize {
             update_auth: update_auth,
-            play_to_earn_fund_share: params.distribution.play_to_earn_fund_share,
-            staking_fund_share: params.distribution.staking_fund_share,
-            company_fund_share: params.distribution.company_fund_share,
-            team_fund_share: params.distribution.team_fund_share,
+            recipients: distribution_recipients,
+            // Team fund gets rounding dust
+            dust_recipient: team_escrow_wallet,
+            recipients_timelock: params.distribution.recipients_timelock,
         })
         .signer(&distribution_info)
         .send()?;
     let distribution_info_data: DistributionInfo =
         distribution_program.account(distribution_info.pubkey())?;
     println!("Distribution Initalized: {:?}", distribution_info_data);
+
+    let vesting_start_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time error")
+        .as_secs() as i64;
+    for (vesting_escrow, escrow_auth, escrow_wallet, beneficiary_wallet) in [
+        (
+            team_vesting_escrow,
+            team_escrow_auth,
+            team_escrow_wallet,
+            team_fund,
+        ),
+        (
+            company_vesting_escrow,
+            company_escrow_auth,
+            company_escrow_wallet,
+            company_fund,
+        ),
+    ] {
+        distribution_program
+            .request()
+            .accounts(distribution::accounts::CreateVestingEscrow {
+                admin: admin_pk,
+                distribution_info: distribution_info.pubkey(),
+                vesting_escrow: vesting_escrow,
+                escrow_auth: escrow_auth,
+                escrow_wallet: escrow_wallet,
+                beneficiary_wallet: beneficiary_wallet,
+                system_program: system_program::ID,
+            })
+            .args(distribution::instruction::CreateVestingEscrow {
+                start_time: vesting_start_time,
+                cliff_period: params.distribution.vesting_cliff_period,
+                vesting_period: params.distribution.vesting_period,
+            })
+            .send()?;
+        let vesting_escrow_data: VestingEscrow = distribution_program.account(vesting_escrow)?;
+        println!("Vesting escrow: {:?}", vesting_escrow_data);
+    }
     println!();
 
     reward_distribution_program
@@ -301,6 +431,30 @@ pub fn cmd_init_all(
         "Reward distribution Initalized: {:?}",
         reward_distribution_info_data
     );
+
+    let fighting_transfer_allowance = super::reward_distribution::get_transfer_allowance_pk(
+        &reward_distribution_program,
+        reward_distribution_info.pubkey(),
+        fighting_reward_transfer_auth,
+    );
+    reward_distribution_program
+        .request()
+        .accounts(reward_distribution::accounts::CreateTransferAllowance {
+            admin: admin_pk,
+            reward_distribution_info: reward_distribution_info.pubkey(),
+            authority: fighting_reward_transfer_auth,
+            transfer_allowance: fighting_transfer_allowance,
+            system_program: system_program::ID,
+        })
+        .args(reward_distribution::instruction::CreateTransferAllowance {
+            limit: params.reward_distribution.fighting_allowance_limit,
+            window: params.reward_distribution.fighting_allowance_window,
+        })
+        .send()?;
+    println!(
+        "Fighting transfer allowance: {}",
+        fighting_transfer_allowance
+    );
     println!();
 
     // Init GPASS with lists of minters and burners
@@ -381,6 +535,8 @@ pub fn cmd_init_all(
         .accounts(staking::accounts::Initialize {
             admin: admin_pk,
             staking_info: staking_info.pubkey(),
+            epoch_history: staking_epoch_history,
+            apr_schedule: staking_apr_schedule,
             ggwp_token: ggwp_token,
             accumulative_fund: accumulative_fund,
             treasury: staking_treasury,
@@ -399,6 +555,8 @@ pub fn cmd_init_all(
             apr_start: params.staking.apr_start,
             apr_step: params.staking.apr_step,
             apr_end: params.staking.apr_end,
+            pool_mode: params.staking.pool_mode,
+            position_nft_uri: position_nft_uri,
         })
         .signer(&staking_info)
         .send()?;
@@ -451,14 +609,21 @@ pub struct ProgramsParams {
     pub freezing: FreezingParams,
     pub staking: StakingParams,
     pub fighting: FightingParams,
+    pub reward_distribution: RewardDistributionParams,
 }
 
 #[derive(Debug)]
 pub struct DistributionParams {
-    pub play_to_earn_fund_share: u8,
-    pub staking_fund_share: u8,
-    pub company_fund_share: u8,
-    pub team_fund_share: u8,
+    // Shares in basis points
+    pub play_to_earn_fund_share: u16,
+    pub staking_fund_share: u16,
+    pub company_fund_share: u16,
+    pub team_fund_share: u16,
+    // Team and company shares vesting in seconds
+    pub vesting_cliff_period: i64,
+    pub vesting_period: i64,
+    // Delay of the recipients change in seconds
+    pub recipients_timelock: i64,
 }
 
 #[derive(Debug)]
@@ -484,6 +649,14 @@ pub struct StakingParams {
     pub apr_start: u8,
     pub apr_step: u8,
     pub apr_end: u8,
+    pub pool_mode: PoolMode,
+}
+
+#[derive(Debug)]
+pub struct RewardDistributionParams {
+    // Fighting rewards budget per window in seconds
+    pub fighting_allowance_limit: u64,
+    pub fighting_allowance_window: i64,
 }
 
 #[derive(Debug)]
@@ -500,10 +673,13 @@ impl ProgramsParams {
         match cluster {
             Cluster::Devnet => ProgramsParams {
                 distribution: DistributionParams {
-                    play_to_earn_fund_share: 45,
-                    staking_fund_share: 40,
-                    company_fund_share: 5,
-                    team_fund_share: 10,
+                    play_to_earn_fund_share: 4500,
+                    staking_fund_share: 4000,
+                    company_fund_share: 500,
+                    team_fund_share: 1000,
+                    vesting_cliff_period: 1 * 24 * 60 * 60,
+                    vesting_period: 7 * 24 * 60 * 60,
+                    recipients_timelock: 1 * 60 * 60,
                 },
                 gpass: GPASSParams {
                     burn_period: 1 * 24 * 60 * 60,
@@ -523,6 +699,7 @@ impl ProgramsParams {
                     apr_start: 45,
                     apr_step: 1,
                     apr_end: 5,
+                    pool_mode: PoolMode::FixedApr,
                 },
                 fighting: FightingParams {
                     validator: Pubkey::from_str("Bf2MP46M6y6nWqEw13Gd4vWG8qK4DqB1yrd1qoftjUuv")
@@ -532,13 +709,20 @@ impl ProgramsParams {
                     reward_coefficient: 20000,
                     gpass_daily_reward_coefficient: 10,
                 },
+                reward_distribution: RewardDistributionParams {
+                    fighting_allowance_limit: 100_000_000_000_000,
+                    fighting_allowance_window: 24 * 60 * 60,
+                },
             },
             Cluster::Testnet => ProgramsParams {
                 distribution: DistributionParams {
-                    play_to_earn_fund_share: 45,
-                    staking_fund_share: 40,
-                    company_fund_share: 5,
-                    team_fund_share: 10,
+                    play_to_earn_fund_share: 4500,
+                    staking_fund_share: 4000,
+                    company_fund_share: 500,
+                    team_fund_share: 1000,
+                    vesting_cliff_period: 7 * 24 * 60 * 60,
+                    vesting_period: 30 * 24 * 60 * 60,
+                    recipients_timelock: 1 * 24 * 60 * 60,
                 },
                 gpass: GPASSParams {
                     burn_period: 1 * 24 * 60 * 60,
@@ -558,6 +742,7 @@ impl ProgramsParams {
                     apr_start: 45,
                     apr_step: 1,
                     apr_end: 5,
+                    pool_mode: PoolMode::FixedApr,
                 },
                 fighting: FightingParams {
                     validator: Pubkey::from_str("Bf2MP46M6y6nWqEw13Gd4vWG8qK4DqB1yrd1qoftjUuv")
@@ -567,13 +752,20 @@ impl ProgramsParams {
                     reward_coefficient: 20000,
                     gpass_daily_reward_coefficient: 10,
                 },
+                reward_distribution: RewardDistributionParams {
+                    fighting_allowance_limit: 100_000_000_000_000,
+                    fighting_allowance_window: 24 * 60 * 60,
+                },
             },
             Cluster::Mainnet => ProgramsParams {
                 distribution: DistributionParams {
-                    play_to_earn_fund_share: 45,
-                    staking_fund_share: 40,
-                    company_fund_share: 5,
-                    team_fund_share: 10,
+                    play_to_earn_fund_share: 4500,
+                    staking_fund_share: 4000,
+                    company_fund_share: 500,
+                    team_fund_share: 1000,
+                    vesting_cliff_period: 365 * 24 * 60 * 60,
+                    vesting_period: 3 * 365 * 24 * 60 * 60,
+                    recipients_timelock: 3 * 24 * 60 * 60,
                 },
                 gpass: GPASSParams {
                     burn_period: 30 * 24 * 60 * 60,
@@ -593,6 +785,7 @@ impl ProgramsParams {
                     apr_start: 45,
                     apr_step: 1,
                     apr_end: 5,
+                    pool_mode: PoolMode::FixedApr,
                 },
                 fighting: FightingParams {
                     validator: Pubkey::from_str("Bf2MP46M6y6nWqEw13Gd4vWG8qK4DqB1yrd1qoftjUuv")
@@ -602,6 +795,10 @@ impl ProgramsParams {
                     reward_coefficient: 20000,
                     gpass_daily_reward_coefficient: 10,
                 },
+                reward_distribution: RewardDistributionParams {
+                    fighting_allowance_limit: 50_000_000_000_000,
+                    fighting_allowance_window: 24 * 60 * 60,
+                },
             },
             _ => panic!("Bad cluster"),
         }
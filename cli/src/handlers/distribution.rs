@@ -1,14 +1,23 @@
 use super::utils::get_or_create_token_account;
 use crate::commands;
 use crate::handlers::utils::get_token_account_data;
+use crate::projection::parse_target_time;
 use anchor_client::anchor_lang::system_program;
+use anchor_client::solana_sdk::instruction::AccountMeta;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::ClientError;
 use anchor_client::{solana_sdk::pubkey::Pubkey, Client, Program};
 use clap::value_t_or_exit;
 use clap::{ArgMatches, Error};
-use distribution::state::{DistributionInfo, ACCUMULATIVE_FUND_AUTH_SEED};
+use distribution::state::{
+    DistributionHistory, DistributionInfo, DistributionRecord, PendingRecipients, Recipient,
+    VestingEscrow, ACCUMULATIVE_FUND_AUTH_SEED, DISTRIBUTION_HISTORY_SEED, PENDING_RECIPIENTS_SEED,
+    VESTING_ESCROW_AUTH_SEED, VESTING_ESCROW_SEED,
+};
+use spl_token::ui_amount_to_amount;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn handle(
     cmd_matches: &ArgMatches,
@@ -22,28 +31,17 @@ pub fn handle(
             println!("Commad initialize");
             let update_auth = value_t_or_exit!(arg_matches, "update_auth", Pubkey);
             let ggwp_token = value_t_or_exit!(arg_matches, "ggwp_token", Pubkey);
-            let play_to_earn_fund = value_t_or_exit!(arg_matches, "play_to_earn_fund", Pubkey);
-            let play_to_earn_fund_share =
-                value_t_or_exit!(arg_matches, "play_to_earn_fund_share", u8);
-            let staking_fund = value_t_or_exit!(arg_matches, "staking_fund", Pubkey);
-            let staking_fund_share = value_t_or_exit!(arg_matches, "staking_fund_share", u8);
-            let company_fund = value_t_or_exit!(arg_matches, "company_fund", Pubkey);
-            let company_fund_share = value_t_or_exit!(arg_matches, "company_fund_share", u8);
-            let team_fund = value_t_or_exit!(arg_matches, "team_fund", Pubkey);
-            let team_fund_share = value_t_or_exit!(arg_matches, "team_fund_share", u8);
+            let (recipients, dust_recipient) =
+                parse_recipients(arg_matches).expect("Parse recipients error");
+            let recipients_timelock = value_t_or_exit!(arg_matches, "recipients_timelock", i64);
 
             cmd_initialize(
                 &program,
                 update_auth,
                 ggwp_token,
-                play_to_earn_fund,
-                play_to_earn_fund_share,
-                staking_fund,
-                staking_fund_share,
-                company_fund,
-                company_fund_share,
-                team_fund,
-                team_fund_share,
+                recipients,
+                dust_recipient,
+                recipients_timelock,
             )
             .expect("Initialize error");
 
@@ -51,6 +49,14 @@ pub fn handle(
             Ok(())
         }
 
+        (commands::distribution::CMD_DISTRIBUTE, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            cmd_distribute(&program, distribution_info).expect("Distribute error");
+
+            println!("Successful");
+            Ok(())
+        }
+
         (commands::distribution::CMD_UPDATE_ADMIN, Some(arg_matches)) => {
             let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
             let admin = value_t_or_exit!(arg_matches, "admin", Pubkey);
@@ -70,23 +76,124 @@ pub fn handle(
             Ok(())
         }
 
-        (commands::distribution::CMD_UPDATE_SHARES, Some(arg_matches)) => {
+        (commands::distribution::CMD_UPDATE_RECIPIENTS_TIMELOCK, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let recipients_timelock = value_t_or_exit!(arg_matches, "recipients_timelock", i64);
+            cmd_update_recipients_timelock(&program, distribution_info, recipients_timelock)
+                .expect("Update recipients timelock error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_EXECUTE_RECIPIENTS_TIMELOCK, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            cmd_execute_recipients_timelock(&program, distribution_info)
+                .expect("Execute recipients timelock error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_PROPOSE_RECIPIENTS, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let (recipients, dust_recipient) =
+                parse_recipients(arg_matches).expect("Parse recipients error");
+
+            cmd_propose_recipients(&program, distribution_info, recipients, dust_recipient)
+                .expect("Propose recipients error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_CANCEL_RECIPIENTS, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            cmd_cancel_recipients(&program, distribution_info).expect("Cancel recipients error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_EXECUTE_RECIPIENTS, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            cmd_execute_recipients(&program, distribution_info).expect("Execute recipients error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_SHOW_PENDING_RECIPIENTS, Some(arg_matches)) => {
             let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
-            let play_to_earn_fund_share =
-                value_t_or_exit!(arg_matches, "play_to_earn_fund_share", u8);
-            let staking_fund_share = value_t_or_exit!(arg_matches, "staking_fund_share", u8);
-            let company_fund_share = value_t_or_exit!(arg_matches, "company_fund_share", u8);
-            let team_fund_share = value_t_or_exit!(arg_matches, "team_fund_share", u8);
+            let data: PendingRecipients = program
+                .account(get_pending_recipients_pk(&program, distribution_info))
+                .expect("No pending recipients change");
+
+            println!("Proposer: {}", data.proposer);
+            println!("Proposed at: {}", data.proposed_at);
+            println!("Executable at: {}", data.executable_at);
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time error")
+                .as_secs() as i64;
+            if now < data.executable_at {
+                println!("Time left: {} sec", data.executable_at - now);
+            } else {
+                println!("Ready to execute");
+            }
+            for recipient in data.recipients {
+                println!("Fund: {}", recipient.fund);
+                println!("Share: {} bps", recipient.share);
+                if recipient.is_vesting() {
+                    println!("Vesting escrow: {}", recipient.vesting_escrow);
+                }
+            }
+            println!("Dust recipient: {}", data.dust_recipient);
+
+            Ok(())
+        }
 
-            cmd_update_shares(
+        (commands::distribution::CMD_UPDATE_MIN_DISTRIBUTION_INTERVAL, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let min_distribution_interval =
+                value_t_or_exit!(arg_matches, "min_distribution_interval", i64);
+            cmd_update_min_distribution_interval(
                 &program,
                 distribution_info,
-                play_to_earn_fund_share,
-                staking_fund_share,
-                company_fund_share,
-                team_fund_share,
+                min_distribution_interval,
             )
-            .expect("Update fund shares error");
+            .expect("Update min distribution interval error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_UPDATE_MIN_DISTRIBUTION_AMOUNT, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let amount = ui_amount_to_amount(amount, 9);
+            cmd_update_min_distribution_amount(&program, distribution_info, amount)
+                .expect("Update min distribution amount error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_UPDATE_KEEPER_REWARD_SHARE, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let keeper_reward_share = value_t_or_exit!(arg_matches, "keeper_reward_share", u16);
+            cmd_update_keeper_reward_share(&program, distribution_info, keeper_reward_share)
+                .expect("Update keeper reward share error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_UPDATE_BURN_SHARE, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let burn_share = value_t_or_exit!(arg_matches, "burn_share", u16);
+            cmd_update_burn_share(&program, distribution_info, burn_share)
+                .expect("Update burn share error");
 
             println!("Successful");
             Ok(())
@@ -105,33 +212,147 @@ pub fn handle(
                 .amount;
             println!("Amount: {}", amount);
 
-            println!("Play to earn fund: {}", data.play_to_earn_fund);
-            let amount = get_token_account_data(&program, data.play_to_earn_fund)
-                .expect("Fund fetch error")
-                .amount;
-            println!("Amount: {}", amount);
-            println!("Share: {}", data.play_to_earn_fund_share);
+            for recipient in data.recipients {
+                println!("Fund: {}", recipient.fund);
+                let amount = get_token_account_data(&program, recipient.fund)
+                    .expect("Fund fetch error")
+                    .amount;
+                println!("Amount: {}", amount);
+                println!("Share: {} bps", recipient.share);
+                if recipient.is_vesting() {
+                    println!("Vesting escrow: {}", recipient.vesting_escrow);
+                }
+            }
+            println!("Dust recipient: {}", data.dust_recipient);
+            println!(
+                "Min distribution interval: {} sec",
+                data.min_distribution_interval
+            );
+            println!("Min distribution amount: {}", data.min_distribution_amount);
+            println!("Keeper reward share: {} bps", data.keeper_reward_share);
+            println!("Burn share: {} bps", data.burn_share);
+            println!("Total burned: {}", data.total_burned);
+            println!("Recipients timelock: {} sec", data.recipients_timelock);
+            if data.pending_recipients_timelock != 0 {
+                println!(
+                    "Pending recipients timelock: {} sec, executable at {}",
+                    data.pending_recipients_timelock, data.pending_recipients_timelock_at
+                );
+            }
 
-            println!("Staking fund: {}", data.staking_fund);
-            let amount = get_token_account_data(&program, data.staking_fund)
-                .expect("Fund fetch error")
-                .amount;
-            println!("Amount: {}", amount);
-            println!("Share: {}", data.staking_fund_share);
+            Ok(())
+        }
 
-            println!("Company fund: {}", data.company_fund);
-            let amount = get_token_account_data(&program, data.company_fund)
-                .expect("Fund fetch error")
-                .amount;
-            println!("Amount: {}", amount);
-            println!("Share: {}", data.company_fund_share);
+        (commands::distribution::CMD_HISTORY, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let data: DistributionHistory = program
+                .account(get_distribution_history_pk(&program, distribution_info))
+                .expect("Account fetch error");
 
-            println!("Team fund: {}", data.team_fund);
-            let amount = get_token_account_data(&program, data.team_fund)
-                .expect("Fund fetch error")
-                .amount;
-            println!("Amount: {}", amount);
-            println!("Share: {}", data.team_fund_share);
+            if let Some(csv_file) = arg_matches.value_of("csv") {
+                std::fs::write(csv_file, get_history_csv(&data)).expect("CSV write error");
+                println!("Exported {} records into {}", data.records.len(), csv_file);
+                return Ok(());
+            }
+
+            println!("Total distributions: {}", data.total_distributions);
+            for (number, record) in get_history_numbered_records(&data) {
+                println!();
+                println!("Distribution: {}", number);
+                println!("Time: {}", record.time);
+                println!("Caller: {}", record.caller);
+                println!("Amount: {}", record.amount);
+                println!("Keeper reward: {}", record.keeper_reward);
+                println!("Burned: {}", record.burned);
+                for fund_amount in &record.fund_amounts {
+                    println!("Fund {}: {}", fund_amount.fund, fund_amount.amount);
+                }
+            }
+
+            Ok(())
+        }
+
+        (commands::distribution::CMD_CREATE_VESTING_ESCROW, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let beneficiary_wallet = value_t_or_exit!(arg_matches, "beneficiary_wallet", Pubkey);
+            let cliff_period = value_t_or_exit!(arg_matches, "cliff_period", i64);
+            let vesting_period = value_t_or_exit!(arg_matches, "vesting_period", i64);
+            let start_time = match arg_matches.value_of("start") {
+                Some(value) => parse_target_time(value).expect("Start date error"),
+                None => SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("Time error")
+                    .as_secs() as i64,
+            };
+
+            cmd_create_vesting_escrow(
+                &program,
+                distribution_info,
+                beneficiary_wallet,
+                start_time,
+                cliff_period,
+                vesting_period,
+            )
+            .expect("Create vesting escrow error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_CLAIM_VESTED, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let beneficiary_wallet = value_t_or_exit!(arg_matches, "beneficiary_wallet", Pubkey);
+            cmd_claim_vested(&program, distribution_info, beneficiary_wallet)
+                .expect("Claim vested error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::distribution::CMD_SHOW_VESTING_ESCROW, Some(arg_matches)) => {
+            let distribution_info = value_t_or_exit!(arg_matches, "distribution_info", Pubkey);
+            let beneficiary_wallet = value_t_or_exit!(arg_matches, "beneficiary_wallet", Pubkey);
+            let (vesting_escrow, _) =
+                get_vesting_escrow_pks(&program, distribution_info, beneficiary_wallet);
+            let data: VestingEscrow = program
+                .account(vesting_escrow)
+                .expect("Account fetch error");
+            let current_time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time error")
+                .as_secs() as i64;
+            let total_received = data.released_amount
+                + data
+                    .tranches
+                    .iter()
+                    .map(|tranche| tranche.amount)
+                    .sum::<u64>();
+            let vested_amount = distribution::utils::calc_escrow_vested_amount(&data, current_time)
+                .expect("Vested amount calc error");
+
+            println!("Vesting escrow: {}", vesting_escrow);
+            println!("Beneficiary wallet: {}", data.beneficiary_wallet);
+            println!("Escrow wallet: {}", data.escrow_wallet);
+            println!("Start time: {}", data.start_time);
+            println!("Cliff end time: {}", data.start_time + data.cliff_period);
+            println!("Cliff period: {} sec", data.cliff_period);
+            println!("Vesting period: {} sec", data.vesting_period);
+            println!("Total received: {}", total_received);
+            println!("Released: {}", data.released_amount);
+            for tranche in data.tranches.iter() {
+                println!(
+                    "Tranche: {} vesting from {} to {}",
+                    tranche.amount,
+                    tranche.start_time,
+                    tranche.start_time + data.vesting_period
+                );
+            }
+            println!("Vested: {}", vested_amount);
+            println!("Claimed: {}", data.total_claimed);
+            println!(
+                "Claimable: {}",
+                vested_amount.saturating_sub(data.total_claimed)
+            );
 
             Ok(())
         }
@@ -143,18 +364,140 @@ pub fn handle(
     }
 }
 
+/// Parses `fund:share_bps[:vesting_escrow]` recipients and the dust recipient.
+fn parse_recipients(arg_matches: &ArgMatches) -> Result<(Vec<Recipient>, Pubkey), String> {
+    let recipients = arg_matches
+        .values_of("recipients")
+        .unwrap()
+        .map(parse_recipient)
+        .collect::<Result<Vec<Recipient>, String>>()?;
+    let dust_recipient = match arg_matches.value_of("dust_recipient") {
+        Some(dust_recipient) => Pubkey::from_str(dust_recipient)
+            .map_err(|_| format!("{}: invalid dust recipient", dust_recipient))?,
+        None => recipients.last().ok_or("Empty recipients")?.fund,
+    };
+
+    Ok((recipients, dust_recipient))
+}
+
+/// Parses `fund:share_bps[:vesting_escrow]` recipient.
+fn parse_recipient(value: &str) -> Result<Recipient, String> {
+    let mut parts = value.split(':');
+    let (fund, share) = parts
+        .next()
+        .zip(parts.next())
+        .ok_or(format!("{}: expected fund:share_bps", value))?;
+    let vesting_escrow = match parts.next() {
+        Some(vesting_escrow) => Pubkey::from_str(vesting_escrow)
+            .map_err(|_| format!("{}: invalid vesting escrow", value))?,
+        None => Pubkey::default(),
+    };
+    if parts.next().is_some() {
+        return Err(format!(
+            "{}: expected fund:share_bps[:vesting_escrow]",
+            value
+        ));
+    }
+
+    Ok(Recipient {
+        fund: Pubkey::from_str(fund).map_err(|_| format!("{}: invalid fund", value))?,
+        share: share
+            .parse()
+            .map_err(|_| format!("{}: invalid share", value))?,
+        vesting_escrow,
+    })
+}
+
+fn get_distribution_history_pk(program: &Program, distribution_info: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            DISTRIBUTION_HISTORY_SEED.as_bytes(),
+            distribution_info.as_ref(),
+        ],
+        &program.id(),
+    )
+    .0
+}
+
+fn get_pending_recipients_pk(program: &Program, distribution_info: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PENDING_RECIPIENTS_SEED.as_bytes(),
+            distribution_info.as_ref(),
+        ],
+        &program.id(),
+    )
+    .0
+}
+
+/// History records from the oldest to the newest with the distribution numbers.
+fn get_history_numbered_records(history: &DistributionHistory) -> Vec<(u64, &DistributionRecord)> {
+    let first_number = history.total_distributions - history.records.len() as u64 + 1;
+    distribution::utils::get_ordered_records(history)
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| (first_number + i as u64, record))
+        .collect()
+}
+
+/// One row per fund of the distribution for reconciliation.
+fn get_history_csv(history: &DistributionHistory) -> String {
+    let mut csv =
+        String::from("distribution,time,caller,total_amount,keeper_reward,burned,fund,amount\n");
+    for (number, record) in get_history_numbered_records(history) {
+        for fund_amount in &record.fund_amounts {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                number,
+                record.time,
+                record.caller,
+                record.amount,
+                record.keeper_reward,
+                record.burned,
+                fund_amount.fund,
+                fund_amount.amount
+            ));
+        }
+    }
+    csv
+}
+
+/// Vesting escrow and its auth of the beneficiary wallet.
+fn get_vesting_escrow_pks(
+    program: &Program,
+    distribution_info: Pubkey,
+    beneficiary_wallet: Pubkey,
+) -> (Pubkey, Pubkey) {
+    let (vesting_escrow, _) = Pubkey::find_program_address(
+        &[
+            VESTING_ESCROW_SEED.as_bytes(),
+            distribution_info.as_ref(),
+            beneficiary_wallet.as_ref(),
+        ],
+        &program.id(),
+    );
+    let (escrow_auth, _) = Pubkey::find_program_address(
+        &[VESTING_ESCROW_AUTH_SEED.as_bytes(), vesting_escrow.as_ref()],
+        &program.id(),
+    );
+    (vesting_escrow, escrow_auth)
+}
+
+/// Recipient fund accounts in the list order.
+fn get_recipient_accounts(recipients: &[Recipient]) -> Vec<AccountMeta> {
+    recipients
+        .iter()
+        .map(|recipient| AccountMeta::new(recipient.fund, false))
+        .collect()
+}
+
 fn cmd_initialize(
     program: &Program,
     update_auth: Pubkey,
     ggwp_token: Pubkey,
-    play_to_earn_fund: Pubkey,
-    play_to_earn_fund_share: u8,
-    staking_fund: Pubkey,
-    staking_fund_share: u8,
-    company_fund: Pubkey,
-    company_fund_share: u8,
-    team_fund: Pubkey,
-    team_fund_share: u8,
+    recipients: Vec<Recipient>,
+    dust_recipient: Pubkey,
+    recipients_timelock: i64,
 ) -> Result<(), ClientError> {
     let distribution_info = Keypair::new();
     println!(
@@ -172,27 +515,25 @@ fn cmd_initialize(
 
     let accumulative_fund =
         get_or_create_token_account(program, ggwp_token, accumulative_fund_auth)?;
+    let distribution_history = get_distribution_history_pk(program, distribution_info.pubkey());
 
     program
         .request()
         .accounts(distribution::accounts::Initialize {
             admin: program.payer(),
             distribution_info: distribution_info.pubkey(),
+            distribution_history: distribution_history,
             ggwp_token: ggwp_token,
             accumulative_fund: accumulative_fund,
             accumulative_fund_auth: accumulative_fund_auth,
-            play_to_earn_fund: play_to_earn_fund,
-            staking_fund: staking_fund,
-            company_fund: company_fund,
-            team_fund: team_fund,
             system_program: system_program::ID,
         })
+        .accounts(get_recipient_accounts(&recipients))
         .args(distribution::instruction::Initialize {
             update_auth: update_auth,
-            play_to_earn_fund_share: play_to_earn_fund_share,
-            staking_fund_share: staking_fund_share,
-            company_fund_share: company_fund_share,
-            team_fund_share: team_fund_share,
+            recipients: recipients,
+            dust_recipient: dust_recipient,
+            recipients_timelock: recipients_timelock,
         })
         .signer(&distribution_info)
         .send()?;
@@ -200,6 +541,45 @@ fn cmd_initialize(
     Ok(())
 }
 
+fn cmd_distribute(program: &Program, distribution_info: Pubkey) -> Result<(), ClientError> {
+    let distribution_info_data: DistributionInfo = program.account(distribution_info)?;
+    let (accumulative_fund_auth, _) = Pubkey::find_program_address(
+        &[
+            ACCUMULATIVE_FUND_AUTH_SEED.as_bytes(),
+            distribution_info.as_ref(),
+        ],
+        &program.id(),
+    );
+    let caller_ggwp_wallet =
+        get_or_create_token_account(program, distribution_info_data.ggwp_token, program.payer())?;
+
+    program
+        .request()
+        .accounts(distribution::accounts::Distribute {
+            caller: program.payer(),
+            distribution_info: distribution_info,
+            distribution_history: get_distribution_history_pk(program, distribution_info),
+            accumulative_fund_auth: accumulative_fund_auth,
+            ggwp_token: distribution_info_data.ggwp_token,
+            accumulative_fund: distribution_info_data.accumulative_fund,
+            caller_ggwp_wallet: caller_ggwp_wallet,
+            token_program: spl_token::id(),
+        })
+        .accounts(get_recipient_accounts(&distribution_info_data.recipients))
+        .accounts(
+            distribution_info_data
+                .recipients
+                .iter()
+                .filter(|recipient| recipient.is_vesting())
+                .map(|recipient| AccountMeta::new(recipient.vesting_escrow, false))
+                .collect::<Vec<AccountMeta>>(),
+        )
+        .args(distribution::instruction::Distribute {})
+        .send()?;
+
+    Ok(())
+}
+
 fn cmd_update_admin(
     program: &Program,
     distribution_info: Pubkey,
@@ -236,13 +616,101 @@ fn cmd_set_update_authority(
     Ok(())
 }
 
-fn cmd_update_shares(
+fn cmd_update_recipients_timelock(
+    program: &Program,
+    distribution_info: Pubkey,
+    recipients_timelock: i64,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(distribution::accounts::UpdateParam {
+            authority: program.payer(),
+            distribution_info: distribution_info,
+        })
+        .args(distribution::instruction::UpdateRecipientsTimelock {
+            recipients_timelock: recipients_timelock,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_execute_recipients_timelock(
+    program: &Program,
+    distribution_info: Pubkey,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(distribution::accounts::ExecuteRecipientsTimelock {
+            distribution_info: distribution_info,
+        })
+        .args(distribution::instruction::ExecuteRecipientsTimelock {})
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_propose_recipients(
+    program: &Program,
+    distribution_info: Pubkey,
+    recipients: Vec<Recipient>,
+    dust_recipient: Pubkey,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(distribution::accounts::ProposeRecipients {
+            authority: program.payer(),
+            distribution_info: distribution_info,
+            pending_recipients: get_pending_recipients_pk(program, distribution_info),
+            system_program: system_program::ID,
+        })
+        .accounts(get_recipient_accounts(&recipients))
+        .args(distribution::instruction::ProposeRecipients {
+            recipients: recipients,
+            dust_recipient: dust_recipient,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_cancel_recipients(program: &Program, distribution_info: Pubkey) -> Result<(), ClientError> {
+    let pending_recipients = get_pending_recipients_pk(program, distribution_info);
+    let pending_recipients_data: PendingRecipients = program.account(pending_recipients)?;
+    program
+        .request()
+        .accounts(distribution::accounts::CancelRecipients {
+            admin: program.payer(),
+            distribution_info: distribution_info,
+            pending_recipients: pending_recipients,
+            proposer: pending_recipients_data.proposer,
+        })
+        .args(distribution::instruction::CancelRecipients {})
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_execute_recipients(program: &Program, distribution_info: Pubkey) -> Result<(), ClientError> {
+    let pending_recipients = get_pending_recipients_pk(program, distribution_info);
+    let pending_recipients_data: PendingRecipients = program.account(pending_recipients)?;
+    program
+        .request()
+        .accounts(distribution::accounts::ExecuteRecipients {
+            distribution_info: distribution_info,
+            pending_recipients: pending_recipients,
+            proposer: pending_recipients_data.proposer,
+        })
+        .args(distribution::instruction::ExecuteRecipients {})
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_update_min_distribution_interval(
     program: &Program,
     distribution_info: Pubkey,
-    play_to_earn_fund_share: u8,
-    staking_fund_share: u8,
-    company_fund_share: u8,
-    team_fund_share: u8,
+    min_distribution_interval: i64,
 ) -> Result<(), ClientError> {
     program
         .request()
@@ -250,12 +718,128 @@ fn cmd_update_shares(
             authority: program.payer(),
             distribution_info: distribution_info,
         })
-        .args(distribution::instruction::UpdateShares {
-            play_to_earn_fund_share: play_to_earn_fund_share,
-            staking_fund_share: staking_fund_share,
-            company_fund_share: company_fund_share,
-            team_fund_share: team_fund_share,
+        .args(distribution::instruction::UpdateMinDistributionInterval {
+            min_distribution_interval: min_distribution_interval,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_update_min_distribution_amount(
+    program: &Program,
+    distribution_info: Pubkey,
+    min_distribution_amount: u64,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(distribution::accounts::UpdateParam {
+            authority: program.payer(),
+            distribution_info: distribution_info,
+        })
+        .args(distribution::instruction::UpdateMinDistributionAmount {
+            min_distribution_amount: min_distribution_amount,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_update_keeper_reward_share(
+    program: &Program,
+    distribution_info: Pubkey,
+    keeper_reward_share: u16,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(distribution::accounts::UpdateParam {
+            authority: program.payer(),
+            distribution_info: distribution_info,
+        })
+        .args(distribution::instruction::UpdateKeeperRewardShare {
+            keeper_reward_share: keeper_reward_share,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_update_burn_share(
+    program: &Program,
+    distribution_info: Pubkey,
+    burn_share: u16,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(distribution::accounts::UpdateParam {
+            authority: program.payer(),
+            distribution_info: distribution_info,
+        })
+        .args(distribution::instruction::UpdateBurnShare {
+            burn_share: burn_share,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_create_vesting_escrow(
+    program: &Program,
+    distribution_info: Pubkey,
+    beneficiary_wallet: Pubkey,
+    start_time: i64,
+    cliff_period: i64,
+    vesting_period: i64,
+) -> Result<(), ClientError> {
+    let distribution_info_data: DistributionInfo = program.account(distribution_info)?;
+    let (vesting_escrow, escrow_auth) =
+        get_vesting_escrow_pks(program, distribution_info, beneficiary_wallet);
+    let escrow_wallet =
+        get_or_create_token_account(program, distribution_info_data.ggwp_token, escrow_auth)?;
+    println!("Vesting escrow: {}", vesting_escrow);
+    println!("Escrow wallet (recipient fund): {}", escrow_wallet);
+
+    program
+        .request()
+        .accounts(distribution::accounts::CreateVestingEscrow {
+            admin: program.payer(),
+            distribution_info: distribution_info,
+            vesting_escrow: vesting_escrow,
+            escrow_auth: escrow_auth,
+            escrow_wallet: escrow_wallet,
+            beneficiary_wallet: beneficiary_wallet,
+            system_program: system_program::ID,
+        })
+        .args(distribution::instruction::CreateVestingEscrow {
+            start_time: start_time,
+            cliff_period: cliff_period,
+            vesting_period: vesting_period,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_claim_vested(
+    program: &Program,
+    distribution_info: Pubkey,
+    beneficiary_wallet: Pubkey,
+) -> Result<(), ClientError> {
+    let (vesting_escrow, escrow_auth) =
+        get_vesting_escrow_pks(program, distribution_info, beneficiary_wallet);
+    let vesting_escrow_data: VestingEscrow = program.account(vesting_escrow)?;
+
+    program
+        .request()
+        .accounts(distribution::accounts::ClaimVested {
+            authority: program.payer(),
+            vesting_escrow: vesting_escrow,
+            escrow_auth: escrow_auth,
+            escrow_wallet: vesting_escrow_data.escrow_wallet,
+            beneficiary_wallet: beneficiary_wallet,
+            token_program: spl_token::id(),
         })
+        .args(distribution::instruction::ClaimVested {})
         .send()?;
 
     Ok(())
@@ -1,16 +1,21 @@
 use super::utils::get_or_create_token_account;
 use crate::commands;
+use crate::projection::{self, parse_target_time};
 use anchor_client::anchor_lang::system_program;
+use anchor_client::solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use anchor_client::solana_sdk::instruction::AccountMeta;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::ClientError;
 use anchor_client::{solana_sdk::pubkey::Pubkey, Client, Program};
-use clap::{value_t_or_exit, values_t_or_exit};
+use clap::{value_t, value_t_or_exit, values_t_or_exit};
 use clap::{ArgMatches, Error};
 use freezing::state::{
-    FreezingInfo, RewardTableRow, UserInfo, GPASS_MINT_AUTH_SEED, TREASURY_AUTH_SEED,
+    FreezingInfo, ReferrerInfo, RewardTableRow, UserInfo, GPASS_MINT_AUTH_SEED, REFERRER_INFO_SEED,
+    TREASURY_AUTH_SEED,
 };
-use spl_token::ui_amount_to_amount;
+use spl_token::{amount_to_ui_amount, ui_amount_to_amount};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub fn handle(
     cmd_matches: &ArgMatches,
@@ -142,11 +147,57 @@ pub fn handle(
             Ok(())
         }
 
+        (commands::freezing::CMD_UPDATE_REFERRAL_SHARE, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let referral_share = value_t_or_exit!(arg_matches, "referral_share", u8);
+            cmd_update_referral_share(&program, freezing_info, referral_share)
+                .expect("Update referral share error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::freezing::CMD_MIGRATE_INFO, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            cmd_migrate_info(&program, freezing_info).expect("Migrate freezing info error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::freezing::CMD_MIGRATE_USER_INFO, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let user = value_t_or_exit!(arg_matches, "user", Pubkey);
+            cmd_migrate_user_info(&program, freezing_info, user).expect("Migrate user info error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::freezing::CMD_REGISTER_REFERRER, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            cmd_register_referrer(&program, freezing_info).expect("Register referrer error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::freezing::CMD_CLAIM_REFERRAL_REWARDS, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            cmd_claim_referral_rewards(&program, freezing_info)
+                .expect("Claim referral rewards error");
+
+            println!("Successful");
+            Ok(())
+        }
+
         (commands::freezing::CMD_FREEZE, Some(arg_matches)) => {
             let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
             let amount = value_t_or_exit!(arg_matches, "amount", f64);
             let amount = ui_amount_to_amount(amount, 9);
-            cmd_freeze(&program, gpass_program_id, freezing_info, amount).expect("Freeze error");
+            let referrer = value_t!(arg_matches, "referrer", Pubkey).ok();
+            cmd_freeze(&program, gpass_program_id, freezing_info, amount, referrer)
+                .expect("Freeze error");
 
             println!("Successful");
             Ok(())
@@ -169,6 +220,47 @@ pub fn handle(
             Ok(())
         }
 
+        (commands::freezing::CMD_UPDATE_AUTO_CLAIM, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let auto_claim = value_t_or_exit!(arg_matches, "auto_claim", bool);
+            let keeper_tip = value_t_or_exit!(arg_matches, "keeper_tip", f64);
+            let keeper_tip = ui_amount_to_amount(keeper_tip, 9);
+            cmd_update_auto_claim(&program, freezing_info, auto_claim, keeper_tip)
+                .expect("Update auto claim error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::freezing::CMD_DEPOSIT_TIP_BUDGET, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let amount = ui_amount_to_amount(amount, 9);
+            cmd_deposit_tip_budget(&program, freezing_info, amount)
+                .expect("Deposit tip budget error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::freezing::CMD_WITHDRAW_GPASS_FOR, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let user = value_t_or_exit!(arg_matches, "user", Pubkey);
+            cmd_withdraw_gpass_for(&program, gpass_program_id, freezing_info, user)
+                .expect("Withdraw gpass for user error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::freezing::CMD_KEEPER, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let interval = value_t_or_exit!(arg_matches, "interval", u64);
+            cmd_keeper(&program, gpass_program_id, freezing_info, interval).expect("Keeper error");
+
+            Ok(())
+        }
+
         (commands::freezing::CMD_SHOW_INFO, Some(arg_matches)) => {
             let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
             let freezing_info_data: FreezingInfo =
@@ -202,6 +294,48 @@ pub fn handle(
             Ok(())
         }
 
+        (commands::freezing::CMD_SHOW_REFERRER_INFO, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let referrer = value_t_or_exit!(arg_matches, "referrer", Pubkey);
+            let (referrer_info, _bump) = Pubkey::find_program_address(
+                &[
+                    REFERRER_INFO_SEED.as_bytes(),
+                    freezing_info.as_ref(),
+                    referrer.as_ref(),
+                ],
+                &program.id(),
+            );
+            println!("Referrer info address: {:?}", referrer_info);
+
+            match program.account::<ReferrerInfo>(referrer_info) {
+                Ok(d) => {
+                    println!("Referrals: {}", d.referrals);
+                    println!("Total earned: {}", amount_to_ui_amount(d.total_earned, 9));
+                    println!("Unclaimed: {}", amount_to_ui_amount(d.unclaimed_amount, 9));
+                    println!("Referrer info data: {:?}", d);
+                }
+                Err(e) => {
+                    println!("{}", e);
+                }
+            }
+
+            Ok(())
+        }
+
+        (commands::freezing::CMD_ESTIMATE, Some(arg_matches)) => {
+            let freezing_info = value_t_or_exit!(arg_matches, "freezing_info", Pubkey);
+            let target_time = parse_target_time(arg_matches.value_of("date").unwrap())
+                .expect("Target date error");
+            let user = value_t!(arg_matches, "user", Pubkey).ok();
+            let amount = value_t!(arg_matches, "amount", f64)
+                .ok()
+                .map(|amount| ui_amount_to_amount(amount, 9));
+            cmd_estimate(&program, freezing_info, user, amount, target_time)
+                .expect("Estimate error");
+
+            Ok(())
+        }
+
         _ => {
             println!("{}", cmd_matches.usage());
             Ok(())
@@ -401,11 +535,135 @@ fn cmd_update_reward_table(
     Ok(())
 }
 
+fn cmd_update_referral_share(
+    program: &Program,
+    freezing_info: Pubkey,
+    referral_share: u8,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(freezing::accounts::UpdateParam {
+            authority: program.payer(),
+            freezing_info: freezing_info,
+        })
+        .args(freezing::instruction::UpdateReferralShare {
+            referral_share: referral_share,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_migrate_info(program: &Program, freezing_info: Pubkey) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(freezing::accounts::MigrateFreezingInfo {
+            admin: program.payer(),
+            freezing_info: freezing_info,
+            system_program: system_program::ID,
+        })
+        .args(freezing::instruction::MigrateFreezingInfo {})
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_migrate_user_info(
+    program: &Program,
+    freezing_info: Pubkey,
+    user: Pubkey,
+) -> Result<(), ClientError> {
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            freezing::state::USER_INFO_SEED.as_bytes(),
+            freezing_info.as_ref(),
+            user.as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(freezing::accounts::MigrateUserInfo {
+            payer: program.payer(),
+            user: user,
+            user_info: user_info,
+            freezing_info: freezing_info,
+            system_program: system_program::ID,
+        })
+        .args(freezing::instruction::MigrateUserInfo {})
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_register_referrer(program: &Program, freezing_info: Pubkey) -> Result<(), ClientError> {
+    let (referrer_info, _) = Pubkey::find_program_address(
+        &[
+            REFERRER_INFO_SEED.as_bytes(),
+            freezing_info.as_ref(),
+            program.payer().as_ref(),
+        ],
+        &program.id(),
+    );
+    println!("Referral code: {}", program.payer());
+
+    program
+        .request()
+        .accounts(freezing::accounts::RegisterReferrer {
+            referrer: program.payer(),
+            referrer_info: referrer_info,
+            freezing_info: freezing_info,
+            system_program: system_program::ID,
+        })
+        .args(freezing::instruction::RegisterReferrer {})
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_claim_referral_rewards(program: &Program, freezing_info: Pubkey) -> Result<(), ClientError> {
+    let freezing_info_data: FreezingInfo = program.account(freezing_info)?;
+    let (referrer_info, _) = Pubkey::find_program_address(
+        &[
+            REFERRER_INFO_SEED.as_bytes(),
+            freezing_info.as_ref(),
+            program.payer().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    let (treasury_auth, _) = Pubkey::find_program_address(
+        &[TREASURY_AUTH_SEED.as_bytes(), freezing_info.as_ref()],
+        &program.id(),
+    );
+
+    let referrer_ggwp_wallet =
+        get_or_create_token_account(program, freezing_info_data.ggwp_token, program.payer())?;
+
+    program
+        .request()
+        .accounts(freezing::accounts::ClaimReferralRewards {
+            referrer: program.payer(),
+            referrer_info: referrer_info,
+            freezing_info: freezing_info,
+            referrer_ggwp_wallet: referrer_ggwp_wallet,
+            treasury: freezing_info_data.treasury,
+            treasury_auth: treasury_auth,
+            token_program: spl_token::id(),
+        })
+        .args(freezing::instruction::ClaimReferralRewards {})
+        .send()?;
+
+    Ok(())
+}
+
 fn cmd_freeze(
     freezing_program: &Program,
     gpass_program_id: Pubkey,
     freezing_info: Pubkey,
     amount: u64,
+    referrer: Option<Pubkey>,
 ) -> Result<(), ClientError> {
     let freezing_info_data: FreezingInfo = freezing_program.account(freezing_info)?;
     let (user_info, _) = Pubkey::find_program_address(
@@ -441,6 +699,24 @@ fn cmd_freeze(
         freezing_program.payer(),
     )?;
 
+    // Saved referrer has priority over the new one
+    let referrer = match freezing_program.account::<UserInfo>(user_info) {
+        Ok(user_info_data) if user_info_data.referrer.is_some() => user_info_data.referrer,
+        _ => referrer,
+    };
+    let mut referrer_accounts = vec![];
+    if let Some(referrer) = referrer {
+        let (referrer_info, _) = Pubkey::find_program_address(
+            &[
+                REFERRER_INFO_SEED.as_bytes(),
+                freezing_info.as_ref(),
+                referrer.as_ref(),
+            ],
+            &freezing_program.id(),
+        );
+        referrer_accounts.push(AccountMeta::new(referrer_info, false));
+    }
+
     freezing_program
         .request()
         .accounts(freezing::accounts::Freeze {
@@ -457,7 +733,11 @@ fn cmd_freeze(
             system_program: system_program::ID,
             token_program: spl_token::id(),
         })
-        .args(freezing::instruction::Freeze { amount: amount })
+        .accounts(referrer_accounts)
+        .args(freezing::instruction::Freeze {
+            amount: amount,
+            referrer: referrer,
+        })
         .send()?;
 
     Ok(())
@@ -578,3 +858,300 @@ fn cmd_unfreeze(
 
     Ok(())
 }
+
+fn cmd_update_auto_claim(
+    program: &Program,
+    freezing_info: Pubkey,
+    auto_claim: bool,
+    keeper_tip: u64,
+) -> Result<(), ClientError> {
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            freezing::state::USER_INFO_SEED.as_bytes(),
+            freezing_info.as_ref(),
+            program.payer().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(freezing::accounts::UpdateUserParam {
+            user: program.payer(),
+            user_info: user_info,
+            freezing_info: freezing_info,
+        })
+        .args(freezing::instruction::UpdateAutoClaim {
+            auto_claim: auto_claim,
+            keeper_tip: keeper_tip,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_deposit_tip_budget(
+    program: &Program,
+    freezing_info: Pubkey,
+    amount: u64,
+) -> Result<(), ClientError> {
+    let freezing_info_data: FreezingInfo = program.account(freezing_info)?;
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            freezing::state::USER_INFO_SEED.as_bytes(),
+            freezing_info.as_ref(),
+            program.payer().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    let user_ggwp_wallet =
+        get_or_create_token_account(program, freezing_info_data.ggwp_token, program.payer())?;
+
+    program
+        .request()
+        .accounts(freezing::accounts::DepositTipBudget {
+            user: program.payer(),
+            user_info: user_info,
+            freezing_info: freezing_info,
+            user_ggwp_wallet: user_ggwp_wallet,
+            treasury: freezing_info_data.treasury,
+            token_program: spl_token::id(),
+        })
+        .args(freezing::instruction::DepositTipBudget { amount: amount })
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_withdraw_gpass_for(
+    freezing_program: &Program,
+    gpass_program_id: Pubkey,
+    freezing_info: Pubkey,
+    user: Pubkey,
+) -> Result<(), ClientError> {
+    let freezing_info_data: FreezingInfo = freezing_program.account(freezing_info)?;
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            freezing::state::USER_INFO_SEED.as_bytes(),
+            freezing_info.as_ref(),
+            user.as_ref(),
+        ],
+        &freezing_program.id(),
+    );
+
+    let (gpass_mint_auth, _) = Pubkey::find_program_address(
+        &[
+            GPASS_MINT_AUTH_SEED.as_bytes(),
+            freezing_info.as_ref(),
+            freezing_info_data.gpass_info.as_ref(),
+        ],
+        &freezing_program.id(),
+    );
+
+    let (user_gpass_wallet, _) = Pubkey::find_program_address(
+        &[
+            gpass::state::USER_WALLET_SEED.as_bytes(),
+            freezing_info_data.gpass_info.as_ref(),
+            user.as_ref(),
+        ],
+        &gpass_program_id,
+    );
+
+    let (treasury_auth, _) = Pubkey::find_program_address(
+        &[TREASURY_AUTH_SEED.as_bytes(), freezing_info.as_ref()],
+        &freezing_program.id(),
+    );
+
+    let keeper_ggwp_wallet = get_or_create_token_account(
+        &freezing_program,
+        freezing_info_data.ggwp_token,
+        freezing_program.payer(),
+    )?;
+
+    freezing_program
+        .request()
+        .accounts(freezing::accounts::WithdrawFor {
+            keeper: freezing_program.payer(),
+            user: user,
+            user_info: user_info,
+            freezing_info: freezing_info,
+            gpass_info: freezing_info_data.gpass_info,
+            user_gpass_wallet: user_gpass_wallet,
+            gpass_mint_auth: gpass_mint_auth,
+            keeper_ggwp_wallet: keeper_ggwp_wallet,
+            treasury: freezing_info_data.treasury,
+            treasury_auth: treasury_auth,
+            token_program: spl_token::id(),
+            gpass_program: gpass_program_id,
+        })
+        .args(freezing::instruction::WithdrawGpassFor {})
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_keeper(
+    freezing_program: &Program,
+    gpass_program_id: Pubkey,
+    freezing_info: Pubkey,
+    interval: u64,
+) -> Result<(), ClientError> {
+    loop {
+        // Keeper keeps running through RPC errors
+        let freezing_info_data: FreezingInfo = match freezing_program.account(freezing_info) {
+            Ok(freezing_info_data) => freezing_info_data,
+            Err(e) => {
+                println!("Freezing info fetch error: {}", e);
+                std::thread::sleep(Duration::from_secs(interval));
+                continue;
+            }
+        };
+        let current_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time error")
+            .as_secs() as i64;
+
+        let users_info: Vec<(Pubkey, UserInfo)> =
+            match freezing_program.accounts(vec![RpcFilterType::Memcmp(Memcmp {
+                offset: UserInfo::AUTO_CLAIM_OFFSET,
+                bytes: MemcmpEncodedBytes::Bytes(vec![1]),
+                encoding: None,
+            })]) {
+                Ok(users_info) => users_info,
+                Err(e) => {
+                    println!("User infos fetch error: {}", e);
+                    std::thread::sleep(Duration::from_secs(interval));
+                    continue;
+                }
+            };
+        println!("Scanned auto claim user infos: {}", users_info.len());
+        for (user_info, user_info_data) in users_info {
+            if user_info_data.freezed_amount == 0 {
+                continue;
+            }
+
+            // Skip positions of other freezing infos
+            let (expected_user_info, _) = Pubkey::find_program_address(
+                &[
+                    freezing::state::USER_INFO_SEED.as_bytes(),
+                    freezing_info.as_ref(),
+                    user_info_data.user.as_ref(),
+                ],
+                &freezing_program.id(),
+            );
+            if expected_user_info != user_info {
+                continue;
+            }
+
+            let spent_time = current_time - user_info_data.last_getting_gpass;
+            if spent_time < freezing_info_data.reward_period {
+                continue;
+            }
+
+            match cmd_withdraw_gpass_for(
+                freezing_program,
+                gpass_program_id,
+                freezing_info,
+                user_info_data.user,
+            ) {
+                Ok(()) => println!("Withdrawn GPASS for user: {}", user_info_data.user),
+                Err(e) => println!(
+                    "Withdraw GPASS for user {} error: {}",
+                    user_info_data.user, e
+                ),
+            }
+        }
+
+        std::thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+fn cmd_estimate(
+    program: &Program,
+    freezing_info: Pubkey,
+    user: Option<Pubkey>,
+    amount: Option<u64>,
+    target_time: i64,
+) -> Result<(), ClientError> {
+    let freezing_info_data: FreezingInfo = program.account(freezing_info)?;
+
+    let (freezed_amount, freezed_time, last_getting_gpass) = if let Some(user) = user {
+        let (user_info, _) = Pubkey::find_program_address(
+            &[
+                freezing::state::USER_INFO_SEED.as_bytes(),
+                freezing_info.as_ref(),
+                user.as_ref(),
+            ],
+            &program.id(),
+        );
+        let user_info_data: UserInfo = program.account(user_info)?;
+        (
+            user_info_data.freezed_amount,
+            user_info_data.freezed_time,
+            user_info_data.last_getting_gpass,
+        )
+    } else {
+        let amount = amount.unwrap_or(0);
+        let royalty_amount = reward_calc::calc_royalty_amount(freezing_info_data.royalty, amount);
+        let freezed_amount = amount - royalty_amount;
+        let current_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs() as i64;
+        println!("Freeze royalty: {}", amount_to_ui_amount(royalty_amount, 9));
+        println!(
+            "GPASS earned immediately: {}",
+            reward_calc::calc_gpass_per_period(
+                freezing_info_data
+                    .reward_table
+                    .iter()
+                    .map(|row| (row.ggwp_amount, row.gpass_amount)),
+                freezed_amount,
+            )
+        );
+        (freezed_amount, current_time, current_time)
+    };
+
+    println!(
+        "Freezed amount: {}, freezed time {}, last getting GPASS {}",
+        amount_to_ui_amount(freezed_amount, 9),
+        freezed_time,
+        last_getting_gpass,
+    );
+    let rows = projection::project_freezing_rewards(
+        &freezing_info_data.reward_table,
+        freezed_amount,
+        last_getting_gpass,
+        freezing_info_data.reward_period,
+        target_time,
+    )
+    .expect("Projection error");
+    for row in &rows {
+        println!(
+            "  Period {} at {}: GPASS {}, total GPASS {}",
+            row.period, row.time, row.gpass, row.total_gpass,
+        );
+    }
+
+    let unfreeze_royalty_amount = if reward_calc::is_withdraw_royalty(
+        target_time,
+        freezed_time,
+        freezing_info_data.unfreeze_lock_period,
+    )
+    .expect("Invalid freezed time")
+    {
+        reward_calc::calc_royalty_amount(freezing_info_data.unfreeze_royalty, freezed_amount)
+    } else {
+        0
+    };
+    println!(
+        "  Unfreeze at {}: GPASS {}, unfreeze royalty {}, total {}",
+        target_time,
+        rows.last().map(|row| row.total_gpass).unwrap_or(0),
+        amount_to_ui_amount(unfreeze_royalty_amount, 9),
+        amount_to_ui_amount(freezed_amount - unfreeze_royalty_amount, 9),
+    );
+
+    Ok(())
+}
@@ -1,12 +1,27 @@
+use super::utils::get_or_create_token_account;
 use crate::commands;
 use anchor_client::anchor_lang::system_program;
+use anchor_client::solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction};
+use anchor_client::solana_sdk::message::Message;
+use anchor_client::solana_sdk::packet::PACKET_DATA_SIZE;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::ClientError;
 use anchor_client::{solana_sdk::pubkey::Pubkey, Client, Program};
-use clap::{value_t_or_exit, values_t};
+use clap::{value_t, value_t_or_exit, values_t};
 use clap::{ArgMatches, Error};
-use reward_distribution::state::{RewardDistributionInfo, PLAY_TO_EARN_FUND_AUTH_SEED};
+use reward_distribution::state::{
+    ClaimCampaign, RewardDistributionInfo, TransferAllowance, VestingEscrow,
+    PLAY_TO_EARN_FUND_AUTH_SEED, TRANSFER_ALLOWANCE_SEED, VESTING_ESCROW_SEED,
+    VESTING_ESCROW_WALLET_SEED,
+};
+use reward_distribution::utils::{
+    calc_vested_amount, get_claim_leaf, get_merkle_proof, get_merkle_root,
+};
+use spl_token::{amount_to_ui_amount, ui_amount_to_amount};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn handle(
     cmd_matches: &ArgMatches,
@@ -83,6 +98,203 @@ pub fn handle(
             Ok(())
         }
 
+        (commands::reward_distribution::CMD_SET_TRANSFER_ALLOWANCE, Some(arg_matches)) => {
+            let reward_distribution_info =
+                value_t_or_exit!(arg_matches, "reward_distribution_info", Pubkey);
+            let authority = value_t_or_exit!(arg_matches, "authority", Pubkey);
+            let limit = value_t_or_exit!(arg_matches, "limit", f64);
+            let limit = ui_amount_to_amount(limit, 9);
+            let window = value_t_or_exit!(arg_matches, "window", i64);
+            cmd_set_transfer_allowance(
+                &program,
+                reward_distribution_info,
+                authority,
+                limit,
+                window,
+            )
+            .expect("Set transfer allowance error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_SHOW_TRANSFER_ALLOWANCE, Some(arg_matches)) => {
+            let reward_distribution_info =
+                value_t_or_exit!(arg_matches, "reward_distribution_info", Pubkey);
+            let authority = value_t_or_exit!(arg_matches, "authority", Pubkey);
+            let data: TransferAllowance = program
+                .account(get_transfer_allowance_pk(
+                    &program,
+                    reward_distribution_info,
+                    authority,
+                ))
+                .expect("Account fetch error");
+
+            println!("Authority: {}", data.authority);
+            println!("Limit: {} GGWP", amount_to_ui_amount(data.limit, 9));
+            println!("Window: {} sec", data.window);
+            println!("Window start: {}", data.window_start);
+            println!("Used: {} GGWP", amount_to_ui_amount(data.used, 9));
+
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_BATCH_TRANSFER, Some(arg_matches)) => {
+            let reward_distribution_info =
+                value_t_or_exit!(arg_matches, "reward_distribution_info", Pubkey);
+            let csv_file = arg_matches.value_of("csv").unwrap();
+            let payouts = read_amounts_csv(csv_file).expect("CSV read error");
+
+            cmd_batch_transfer(&program, reward_distribution_info, &payouts)
+                .expect("Batch transfer error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_CREATE_CLAIM_CAMPAIGN, Some(arg_matches)) => {
+            let reward_distribution_info =
+                value_t_or_exit!(arg_matches, "reward_distribution_info", Pubkey);
+            let csv_file = arg_matches.value_of("csv").unwrap();
+            let claims = read_amounts_csv(csv_file).expect("CSV read error");
+
+            cmd_create_claim_campaign(&program, reward_distribution_info, &claims)
+                .expect("Create claim campaign error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_CLAIM_FROM_CAMPAIGN, Some(arg_matches)) => {
+            let claim_campaign = value_t_or_exit!(arg_matches, "claim_campaign", Pubkey);
+            let csv_file = arg_matches.value_of("csv").unwrap();
+            let claims = read_amounts_csv(csv_file).expect("CSV read error");
+
+            cmd_claim_from_campaign(&program, claim_campaign, &claims)
+                .expect("Claim from campaign error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_SHOW_CLAIM_CAMPAIGN, Some(arg_matches)) => {
+            let claim_campaign = value_t_or_exit!(arg_matches, "claim_campaign", Pubkey);
+            let data: ClaimCampaign = program
+                .account(claim_campaign)
+                .expect("Account fetch error");
+
+            println!(
+                "Reward distribution info: {}",
+                data.reward_distribution_info
+            );
+            println!("Authority: {}", data.authority);
+            println!("Merkle root: {}", hex_string(&data.merkle_root));
+            println!("Max claims: {}", data.max_claims);
+            println!(
+                "Total amount: {} GGWP",
+                amount_to_ui_amount(data.total_amount, 9)
+            );
+            println!(
+                "Claimed amount: {} GGWP",
+                amount_to_ui_amount(data.claimed_amount, 9)
+            );
+            let claims: u32 = data
+                .claimed_bitmap
+                .iter()
+                .map(|byte| byte.count_ones())
+                .sum();
+            println!("Claims: {}", claims);
+
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_UPDATE_VESTING_DURATION, Some(arg_matches)) => {
+            let reward_distribution_info =
+                value_t_or_exit!(arg_matches, "reward_distribution_info", Pubkey);
+            let vesting_duration = value_t_or_exit!(arg_matches, "vesting_duration", i64);
+
+            cmd_update_vesting_duration(&program, reward_distribution_info, vesting_duration)
+                .expect("Update vesting duration error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_CLAIM, Some(arg_matches)) => {
+            let reward_distribution_info =
+                value_t_or_exit!(arg_matches, "reward_distribution_info", Pubkey);
+
+            cmd_claim(&program, reward_distribution_info).expect("Claim error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::reward_distribution::CMD_SHOW_VESTING_SCHEDULE, Some(arg_matches)) => {
+            let reward_distribution_info =
+                value_t_or_exit!(arg_matches, "reward_distribution_info", Pubkey);
+            let escrows: Vec<(Pubkey, VestingEscrow)> =
+                if let Ok(user) = value_t!(arg_matches, "user", Pubkey) {
+                    let vesting_escrow =
+                        get_vesting_escrow_pk(&program, reward_distribution_info, user);
+                    vec![(
+                        vesting_escrow,
+                        program
+                            .account(vesting_escrow)
+                            .expect("Account fetch error"),
+                    )]
+                } else {
+                    program
+                        .accounts(vec![RpcFilterType::Memcmp(Memcmp {
+                            offset: 8,
+                            bytes: MemcmpEncodedBytes::Bytes(
+                                reward_distribution_info.to_bytes().to_vec(),
+                            ),
+                            encoding: None,
+                        })])
+                        .expect("Accounts fetch error")
+                };
+
+            let current_time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("System time error")
+                .as_secs() as i64;
+            for (vesting_escrow, data) in escrows {
+                let vested_amount =
+                    calc_vested_amount(&data, current_time).expect("Vested amount error");
+                println!("Vesting escrow: {}", vesting_escrow);
+                println!("User: {}", data.user);
+                println!(
+                    "Total amount: {} GGWP",
+                    amount_to_ui_amount(data.total_amount, 9)
+                );
+                println!(
+                    "Released: {} GGWP",
+                    amount_to_ui_amount(data.released_amount, 9)
+                );
+                for tranche in data.tranches.iter() {
+                    println!(
+                        "Tranche: {} GGWP vesting from {} to {}",
+                        amount_to_ui_amount(tranche.amount, 9),
+                        tranche.start_time,
+                        tranche.end_time
+                    );
+                }
+                println!("Vested: {} GGWP", amount_to_ui_amount(vested_amount, 9));
+                println!(
+                    "Claimed: {} GGWP",
+                    amount_to_ui_amount(data.claimed_amount, 9)
+                );
+                println!(
+                    "Claimable: {} GGWP",
+                    amount_to_ui_amount(vested_amount - data.claimed_amount, 9)
+                );
+                println!();
+            }
+
+            Ok(())
+        }
+
         _ => {
             println!("{}", cmd_matches.usage());
             Ok(())
@@ -188,3 +400,334 @@ fn cmd_update_transfer_auth_list(
 
     Ok(())
 }
+
+pub fn get_transfer_allowance_pk(
+    program: &Program,
+    reward_distribution_info: Pubkey,
+    authority: Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            TRANSFER_ALLOWANCE_SEED.as_bytes(),
+            reward_distribution_info.as_ref(),
+            authority.as_ref(),
+        ],
+        &program.id(),
+    )
+    .0
+}
+
+/// Creates the allowance or updates the existing one.
+fn cmd_set_transfer_allowance(
+    program: &Program,
+    reward_distribution_info: Pubkey,
+    authority: Pubkey,
+    limit: u64,
+    window: i64,
+) -> Result<(), ClientError> {
+    let transfer_allowance =
+        get_transfer_allowance_pk(program, reward_distribution_info, authority);
+    if program
+        .account::<TransferAllowance>(transfer_allowance)
+        .is_ok()
+    {
+        program
+            .request()
+            .accounts(reward_distribution::accounts::UpdateTransferAllowance {
+                admin: program.payer(),
+                reward_distribution_info: reward_distribution_info,
+                transfer_allowance: transfer_allowance,
+            })
+            .args(reward_distribution::instruction::UpdateTransferAllowance {
+                limit: limit,
+                window: window,
+            })
+            .send()?;
+    } else {
+        program
+            .request()
+            .accounts(reward_distribution::accounts::CreateTransferAllowance {
+                admin: program.payer(),
+                reward_distribution_info: reward_distribution_info,
+                authority: authority,
+                transfer_allowance: transfer_allowance,
+                system_program: system_program::ID,
+            })
+            .args(reward_distribution::instruction::CreateTransferAllowance {
+                limit: limit,
+                window: window,
+            })
+            .send()?;
+    }
+
+    Ok(())
+}
+
+/// Reads `pubkey,amount` rows with amounts in GGWP. The header row is optional.
+pub fn read_amounts_csv(csv_file: &str) -> Result<Vec<(Pubkey, u64)>, String> {
+    let content = std::fs::read_to_string(csv_file).map_err(|e| e.to_string())?;
+    let mut rows = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (pubkey, amount) = line
+            .split_once(',')
+            .ok_or(format!("{}: invalid row", line))?;
+        let pubkey = match Pubkey::from_str(pubkey.trim()) {
+            Ok(pubkey) => pubkey,
+            Err(_) if i == 0 => continue,
+            Err(_) => return Err(format!("{}: invalid pubkey", line)),
+        };
+        let amount: f64 = amount
+            .trim()
+            .parse()
+            .map_err(|_| format!("{}: invalid amount", line))?;
+        rows.push((pubkey, ui_amount_to_amount(amount, 9)));
+    }
+    Ok(rows)
+}
+
+fn get_claim_leaves(claims: &[(Pubkey, u64)]) -> Vec<[u8; 32]> {
+    claims
+        .iter()
+        .enumerate()
+        .map(|(index, (user, amount))| get_claim_leaf(index as u32, user, *amount))
+        .collect()
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn cmd_create_claim_campaign(
+    program: &Program,
+    reward_distribution_info: Pubkey,
+    claims: &[(Pubkey, u64)],
+) -> Result<(), ClientError> {
+    let claim_campaign = Keypair::new();
+    println!("New claim campaign Pubkey: {}", claim_campaign.pubkey());
+
+    let merkle_root = get_merkle_root(&get_claim_leaves(claims));
+    let total_amount = claims.iter().map(|(_, amount)| amount).sum();
+    println!("Merkle root: {}", hex_string(&merkle_root));
+    println!("Claims: {}", claims.len());
+    println!(
+        "Total amount: {} GGWP",
+        amount_to_ui_amount(total_amount, 9)
+    );
+
+    program
+        .request()
+        .accounts(reward_distribution::accounts::CreateClaimCampaign {
+            authority: program.payer(),
+            reward_distribution_info: reward_distribution_info,
+            transfer_allowance: get_transfer_allowance_pk(
+                program,
+                reward_distribution_info,
+                program.payer(),
+            ),
+            claim_campaign: claim_campaign.pubkey(),
+            system_program: system_program::ID,
+        })
+        .args(reward_distribution::instruction::CreateClaimCampaign {
+            merkle_root: merkle_root,
+            total_amount: total_amount,
+            max_claims: claims.len() as u32,
+        })
+        .signer(&claim_campaign)
+        .send()?;
+
+    Ok(())
+}
+
+fn cmd_claim_from_campaign(
+    program: &Program,
+    claim_campaign: Pubkey,
+    claims: &[(Pubkey, u64)],
+) -> Result<(), ClientError> {
+    let user = program.payer();
+    let index = claims
+        .iter()
+        .position(|(claim_user, _)| *claim_user == user)
+        .ok_or(ClientError::AccountNotFound)?;
+    let amount = claims[index].1;
+    println!("Claim amount: {} GGWP", amount_to_ui_amount(amount, 9));
+
+    let claim_campaign_data: ClaimCampaign = program.account(claim_campaign)?;
+    let reward_distribution_info = claim_campaign_data.reward_distribution_info;
+    let reward_distribution_info_data: RewardDistributionInfo =
+        program.account(reward_distribution_info)?;
+    let (play_to_earn_fund_auth, _) = Pubkey::find_program_address(
+        &[
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.as_ref(),
+        ],
+        &program.id(),
+    );
+    let user_ggwp_wallet =
+        get_or_create_token_account(program, reward_distribution_info_data.ggwp_token, user)?;
+
+    program
+        .request()
+        .accounts(reward_distribution::accounts::ClaimFromCampaign {
+            user: user,
+            reward_distribution_info: reward_distribution_info,
+            claim_campaign: claim_campaign,
+            play_to_earn_fund: reward_distribution_info_data.play_to_earn_fund,
+            play_to_earn_fund_auth: play_to_earn_fund_auth,
+            user_ggwp_wallet: user_ggwp_wallet,
+            token_program: spl_token::id(),
+        })
+        .args(reward_distribution::instruction::ClaimFromCampaign {
+            index: index as u32,
+            amount: amount,
+            proof: get_merkle_proof(&get_claim_leaves(claims), index),
+        })
+        .send()?;
+
+    Ok(())
+}
+
+/// Serialized transaction size: signatures with their short vec length and the message.
+fn get_transaction_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = message.header.num_required_signatures as usize;
+    1 + signatures * 64 + message.serialize().len()
+}
+
+/// Sends the payouts in as few transactions as fit into the packet size.
+fn cmd_batch_transfer(
+    program: &Program,
+    reward_distribution_info: Pubkey,
+    payouts: &[(Pubkey, u64)],
+) -> Result<(), ClientError> {
+    let reward_distribution_info_data: RewardDistributionInfo =
+        program.account(reward_distribution_info)?;
+    let (play_to_earn_fund_auth, _) = Pubkey::find_program_address(
+        &[
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.as_ref(),
+        ],
+        &program.id(),
+    );
+    let transfer_allowance =
+        get_transfer_allowance_pk(program, reward_distribution_info, program.payer());
+
+    let request = |chunk: &[(Pubkey, u64)]| {
+        program
+            .request()
+            .accounts(reward_distribution::accounts::BatchTransfer {
+                authority: program.payer(),
+                reward_distribution_info: reward_distribution_info,
+                transfer_allowance: transfer_allowance,
+                play_to_earn_fund: reward_distribution_info_data.play_to_earn_fund,
+                play_to_earn_fund_auth: play_to_earn_fund_auth,
+                token_program: spl_token::id(),
+            })
+            .accounts(
+                chunk
+                    .iter()
+                    .map(|(recipient, _)| AccountMeta::new(*recipient, false))
+                    .collect::<Vec<_>>(),
+            )
+            .args(reward_distribution::instruction::BatchTransfer {
+                amounts: chunk.iter().map(|(_, amount)| *amount).collect(),
+            })
+    };
+
+    let mut start = 0;
+    while start < payouts.len() {
+        let mut end = start + 1;
+        while end < payouts.len() {
+            let instructions = request(&payouts[start..end + 1]).instructions()?;
+            if get_transaction_size(&instructions, &program.payer()) > PACKET_DATA_SIZE {
+                break;
+            }
+            end += 1;
+        }
+
+        let signature = request(&payouts[start..end]).send()?;
+        println!(
+            "Sent {} transfers ({}..{}): {}",
+            end - start,
+            start,
+            end,
+            signature
+        );
+        start = end;
+    }
+
+    Ok(())
+}
+
+fn cmd_update_vesting_duration(
+    program: &Program,
+    reward_distribution_info: Pubkey,
+    vesting_duration: i64,
+) -> Result<(), ClientError> {
+    program
+        .request()
+        .accounts(reward_distribution::accounts::UpdateParam {
+            authority: program.payer(),
+            reward_distribution_info: reward_distribution_info,
+        })
+        .args(reward_distribution::instruction::UpdateVestingDuration {
+            vesting_duration: vesting_duration,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+pub fn get_vesting_escrow_pk(
+    program: &Program,
+    reward_distribution_info: Pubkey,
+    user: Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            VESTING_ESCROW_SEED.as_bytes(),
+            reward_distribution_info.as_ref(),
+            user.as_ref(),
+        ],
+        &program.id(),
+    )
+    .0
+}
+
+pub fn get_vesting_escrow_wallet_pk(program: &Program, vesting_escrow: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            VESTING_ESCROW_WALLET_SEED.as_bytes(),
+            vesting_escrow.as_ref(),
+        ],
+        &program.id(),
+    )
+    .0
+}
+
+fn cmd_claim(program: &Program, reward_distribution_info: Pubkey) -> Result<(), ClientError> {
+    let user = program.payer();
+    let reward_distribution_info_data: RewardDistributionInfo =
+        program.account(reward_distribution_info)?;
+    let vesting_escrow = get_vesting_escrow_pk(program, reward_distribution_info, user);
+    let user_ggwp_wallet =
+        get_or_create_token_account(program, reward_distribution_info_data.ggwp_token, user)?;
+
+    program
+        .request()
+        .accounts(reward_distribution::accounts::Claim {
+            user: user,
+            reward_distribution_info: reward_distribution_info,
+            vesting_escrow: vesting_escrow,
+            vesting_escrow_wallet: get_vesting_escrow_wallet_pk(program, vesting_escrow),
+            user_ggwp_wallet: user_ggwp_wallet,
+            token_program: spl_token::id(),
+        })
+        .args(reward_distribution::instruction::Claim {})
+        .send()?;
+
+    Ok(())
+}
@@ -1,14 +1,23 @@
-use super::utils::get_or_create_token_account;
+use super::utils::{get_or_create_token_account, get_token_account_data};
 use crate::commands;
+use crate::projection::{self, parse_target_time};
 use anchor_client::anchor_lang::system_program;
+use anchor_client::solana_sdk::instruction::AccountMeta;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::sysvar;
 use anchor_client::ClientError;
 use anchor_client::{solana_sdk::pubkey::Pubkey, Client, Program};
-use clap::value_t_or_exit;
+use clap::{value_t, value_t_or_exit};
 use clap::{ArgMatches, Error};
-use spl_token::ui_amount_to_amount;
-use staking::state::{StakingInfo, UserInfo, STAKING_FUND_AUTH_SEED};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::{amount_to_ui_amount, ui_amount_to_amount};
+use staking::state::{
+    AprSchedule, EpochHistory, LockupOption, PoolMode, Position, StakingInfo, UserInfo,
+    APR_SCHEDULE_SEED, EPOCH_HISTORY_SEED, POSITION_NFT_AUTH_SEED, POSITION_NFT_MINT_SEED,
+    POSITION_SEED, STAKING_FUND_AUTH_SEED,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn handle(
     cmd_matches: &ArgMatches,
@@ -32,6 +41,11 @@ pub fn handle(
             let apr_start = value_t_or_exit!(arg_matches, "apr_start", u8);
             let apr_step = value_t_or_exit!(arg_matches, "apr_step", u8);
             let apr_end = value_t_or_exit!(arg_matches, "apr_end", u8);
+            let pool_mode = match arg_matches.value_of("pool_mode") {
+                Some("reward-per-share") => PoolMode::RewardPerShare,
+                _ => PoolMode::FixedApr,
+            };
+            let position_nft_uri = value_t_or_exit!(arg_matches, "position_nft_uri", String);
 
             cmd_initialize(
                 staking_program,
@@ -47,6 +61,8 @@ pub fn handle(
                 apr_start,
                 apr_step,
                 apr_end,
+                pool_mode,
+                position_nft_uri,
             )
             .expect("Initialize error");
 
@@ -124,11 +140,68 @@ pub fn handle(
             Ok(())
         }
 
+        (commands::staking::CMD_UPDATE_POSITION_NFT_URI, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let position_nft_uri = value_t_or_exit!(arg_matches, "position_nft_uri", String);
+            cmd_update_position_nft_uri(staking_program, staking_info, position_nft_uri)
+                .expect("Update position NFT URI error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_UPDATE_LOCKUP_OPTIONS, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let lockup_options = arg_matches
+                .values_of("lockup_options")
+                .unwrap()
+                .map(parse_lockup_option)
+                .collect::<Result<Vec<LockupOption>, String>>()
+                .expect("Parse lockup options error");
+            cmd_update_lockup_options(staking_program, staking_info, lockup_options)
+                .expect("Update lockup options error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_ADVANCE_EPOCH, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            cmd_advance_epoch(staking_program, staking_info).expect("Advance epoch error");
+
+            println!("Successful");
+            Ok(())
+        }
+
         (commands::staking::CMD_STAKE, Some(arg_matches)) => {
             let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
             let amount = value_t_or_exit!(arg_matches, "amount", f64);
             let amount = ui_amount_to_amount(amount, 9);
-            cmd_stake(staking_program, staking_info, amount).expect("Stake error");
+            let lockup = value_t_or_exit!(arg_matches, "lockup", u8);
+            let auto_compound = arg_matches.is_present("auto_compound");
+            cmd_stake(staking_program, staking_info, amount, lockup, auto_compound)
+                .expect("Stake error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_STAKE_FOR, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let beneficiary = value_t_or_exit!(arg_matches, "beneficiary", Pubkey);
+            let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let amount = ui_amount_to_amount(amount, 9);
+            let lockup = value_t_or_exit!(arg_matches, "lockup", u8);
+            let auto_compound = arg_matches.is_present("auto_compound");
+            cmd_stake_for(
+                staking_program,
+                staking_info,
+                beneficiary,
+                amount,
+                lockup,
+                auto_compound,
+            )
+            .expect("Stake for error");
 
             println!("Successful");
             Ok(())
@@ -136,7 +209,56 @@ pub fn handle(
 
         (commands::staking::CMD_WITHDRAW, Some(arg_matches)) => {
             let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
-            cmd_withdraw(staking_program, staking_info).expect("Withdraw error");
+            let position = value_t_or_exit!(arg_matches, "position", u64);
+            let staker = value_t!(arg_matches, "staker", Pubkey).unwrap_or(staking_program.payer());
+            cmd_withdraw(staking_program, staking_info, staker, position).expect("Withdraw error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_CLAIM_REWARDS, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let position = value_t_or_exit!(arg_matches, "position", u64);
+            let staker = value_t!(arg_matches, "staker", Pubkey).unwrap_or(staking_program.payer());
+            cmd_claim_rewards(staking_program, staking_info, staker, position)
+                .expect("Claim rewards error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_SET_AUTO_COMPOUND, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let position = value_t_or_exit!(arg_matches, "position", u64);
+            let auto_compound = value_t_or_exit!(arg_matches, "auto_compound", bool);
+            let staker = value_t!(arg_matches, "staker", Pubkey).unwrap_or(staking_program.payer());
+            cmd_set_auto_compound(
+                staking_program,
+                staking_info,
+                staker,
+                position,
+                auto_compound,
+            )
+            .expect("Set auto compound error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_COMPOUND, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let position = value_t_or_exit!(arg_matches, "position", u64);
+            let staker = value_t_or_exit!(arg_matches, "staker", Pubkey);
+            cmd_compound(staking_program, staking_info, staker, position).expect("Compound error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_CLAIM_IOU, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            cmd_claim_iou(staking_program, staking_info).expect("Claim IOU error");
 
             println!("Successful");
             Ok(())
@@ -148,6 +270,13 @@ pub fn handle(
                 .account(staking_info)
                 .expect("Error getting staking info data");
             println!("Staking info data: {:?}", staking_info_data);
+            println!("Lockup options:");
+            for (index, option) in staking_info_data.lockup_options.iter().enumerate() {
+                println!(
+                    "{}: lockup {} days, APR boost {} bps",
+                    index, option.lockup_days, option.apr_boost
+                );
+            }
             Ok(())
         }
 
@@ -168,6 +297,105 @@ pub fn handle(
             Ok(())
         }
 
+        (commands::staking::CMD_SHOW_EPOCH_HISTORY, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let (epoch_history, _) = Pubkey::find_program_address(
+                &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+                &staking_program.id(),
+            );
+            let epoch_history_data: EpochHistory = staking_program
+                .account(epoch_history)
+                .expect("Getting epoch history error");
+            for (index, apr) in epoch_history_data.aprs.iter().enumerate() {
+                println!("Epoch {}: APR {} bps", index + 1, apr);
+            }
+            Ok(())
+        }
+
+        (commands::staking::CMD_SHOW_APR_SCHEDULE, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let staking_info_data: StakingInfo = staking_program
+                .account(staking_info)
+                .expect("Error getting staking info data");
+            let (apr_schedule, _) = Pubkey::find_program_address(
+                &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+                &staking_program.id(),
+            );
+            let apr_schedule_data: AprSchedule = staking_program
+                .account(apr_schedule)
+                .expect("Getting APR schedule error");
+
+            let current_time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs() as i64;
+            let (current_epoch, _) = staking::utils::get_epoch_by_time(
+                staking_info_data.start_time,
+                current_time,
+                staking_info_data.epoch_period_days,
+            )
+            .expect("Getting current epoch error");
+
+            for (index, apr) in apr_schedule_data.aprs.iter().enumerate() {
+                let epoch = index as u64 + 1;
+                let mark = if epoch == current_epoch {
+                    " (current)"
+                } else {
+                    ""
+                };
+                println!("Epoch {}: APR {} bps{}", epoch, apr, mark);
+            }
+            println!("Later epochs keep the last APR.");
+            Ok(())
+        }
+
+        (commands::staking::CMD_UPDATE_APR_SCHEDULE, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let file = value_t_or_exit!(arg_matches, "file", String);
+            let (from_epoch, aprs) =
+                read_apr_schedule_file(&file).expect("Read APR schedule error");
+
+            cmd_update_apr_schedule(staking_program, staking_info, from_epoch, aprs)
+                .expect("Update APR schedule error");
+
+            println!("Successful");
+            Ok(())
+        }
+
+        (commands::staking::CMD_POSITIONS, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let user = value_t!(arg_matches, "user", Pubkey).unwrap_or(staking_program.payer());
+            cmd_positions(staking_program, staking_info, user).expect("Show positions error");
+
+            Ok(())
+        }
+
+        (commands::staking::CMD_ESTIMATE, Some(arg_matches)) => {
+            let staking_info = value_t_or_exit!(arg_matches, "staking_info", Pubkey);
+            let target_time = parse_target_time(arg_matches.value_of("date").unwrap())
+                .expect("Target date error");
+            let user = value_t!(arg_matches, "user", Pubkey).ok();
+            let amount = value_t!(arg_matches, "amount", f64)
+                .ok()
+                .map(|amount| ui_amount_to_amount(amount, 9));
+            let stake_time = arg_matches
+                .value_of("stake_date")
+                .map(|value| parse_target_time(value).expect("Stake date error"));
+            let lockup = value_t_or_exit!(arg_matches, "lockup", u8);
+            cmd_estimate(
+                staking_program,
+                staking_info,
+                user,
+                amount,
+                stake_time,
+                lockup,
+                target_time,
+            )
+            .expect("Estimate error");
+
+            Ok(())
+        }
+
         _ => {
             println!("{}", cmd_matches.usage());
             Ok(())
@@ -189,6 +417,8 @@ pub fn cmd_initialize(
     apr_start: u8,
     apr_step: u8,
     apr_end: u8,
+    pool_mode: PoolMode,
+    position_nft_uri: String,
 ) -> Result<(), ClientError> {
     let staking_info = Keypair::new();
     println!("New staking info PK: {}", staking_info.pubkey());
@@ -209,6 +439,19 @@ pub fn cmd_initialize(
         &staking_program.id(),
     );
 
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.pubkey().as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.pubkey().as_ref()],
+        &staking_program.id(),
+    );
+
     let staking_treasury =
         get_or_create_token_account(&staking_program, ggwp_token, staking_treasury_auth)?;
 
@@ -217,6 +460,8 @@ pub fn cmd_initialize(
         .accounts(staking::accounts::Initialize {
             admin: staking_program.payer(),
             staking_info: staking_info.pubkey(),
+            epoch_history: epoch_history,
+            apr_schedule: apr_schedule,
             ggwp_token: ggwp_token,
             accumulative_fund: accumulative_fund,
             treasury: staking_treasury,
@@ -235,6 +480,8 @@ pub fn cmd_initialize(
             apr_start: apr_start,
             apr_step: apr_step,
             apr_end: apr_end,
+            pool_mode: pool_mode,
+            position_nft_uri: position_nft_uri,
         })
         .signer(&staking_info)
         .send()?;
@@ -371,22 +618,199 @@ pub fn cmd_update_royalty(
     Ok(())
 }
 
+pub fn cmd_update_position_nft_uri(
+    staking_program: Program,
+    staking_info: Pubkey,
+    position_nft_uri: String,
+) -> Result<(), ClientError> {
+    staking_program
+        .request()
+        .accounts(staking::accounts::UpdateParam {
+            authority: staking_program.payer(),
+            staking_info: staking_info,
+        })
+        .args(staking::instruction::UpdatePositionNftUri {
+            position_nft_uri: position_nft_uri,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+/// Parses `days:boost_bps` lockup option.
+fn parse_lockup_option(value: &str) -> Result<LockupOption, String> {
+    let (lockup_days, apr_boost) = value
+        .split_once(':')
+        .ok_or(format!("{}: expected days:boost_bps", value))?;
+
+    Ok(LockupOption {
+        lockup_days: lockup_days
+            .parse()
+            .map_err(|_| format!("{}: invalid lockup days", value))?,
+        apr_boost: apr_boost
+            .parse()
+            .map_err(|_| format!("{}: invalid APR boost", value))?,
+    })
+}
+
+pub fn cmd_update_lockup_options(
+    staking_program: Program,
+    staking_info: Pubkey,
+    lockup_options: Vec<LockupOption>,
+) -> Result<(), ClientError> {
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    staking_program
+        .request()
+        .accounts(staking::accounts::UpdateLockupOptions {
+            authority: staking_program.payer(),
+            staking_info: staking_info,
+            apr_schedule,
+        })
+        .args(staking::instruction::UpdateLockupOptions {
+            lockup_options: lockup_options,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+/// Reads `epoch,apr_bps` lines of contiguous epochs.
+fn read_apr_schedule_file(file: &str) -> Result<(u64, Vec<u16>), String> {
+    let content = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+
+    let mut from_epoch = 0;
+    let mut aprs = vec![];
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (epoch, apr) = line
+            .split_once(',')
+            .ok_or(format!("Line {}: expected epoch,apr_bps", line_index + 1))?;
+        let epoch: u64 = epoch
+            .trim()
+            .parse()
+            .map_err(|_| format!("Line {}: invalid epoch", line_index + 1))?;
+        let apr: u16 = apr
+            .trim()
+            .parse()
+            .map_err(|_| format!("Line {}: invalid APR", line_index + 1))?;
+
+        if aprs.is_empty() {
+            from_epoch = epoch;
+        } else if epoch != from_epoch + aprs.len() as u64 {
+            return Err(format!(
+                "Line {}: epochs must be contiguous",
+                line_index + 1
+            ));
+        }
+        aprs.push(apr);
+    }
+
+    if aprs.is_empty() {
+        return Err("Empty APR schedule file".to_string());
+    }
+    Ok((from_epoch, aprs))
+}
+
+/// The max number of APR values in a single update transaction.
+const APR_SCHEDULE_CHUNK_LEN: usize = 200;
+
+pub fn cmd_update_apr_schedule(
+    staking_program: Program,
+    staking_info: Pubkey,
+    from_epoch: u64,
+    aprs: Vec<u16>,
+) -> Result<(), ClientError> {
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    for (chunk_index, chunk) in aprs.chunks(APR_SCHEDULE_CHUNK_LEN).enumerate() {
+        let chunk_from_epoch = from_epoch + (chunk_index * APR_SCHEDULE_CHUNK_LEN) as u64;
+        staking_program
+            .request()
+            .accounts(staking::accounts::UpdateAprSchedule {
+                authority: staking_program.payer(),
+                staking_info: staking_info,
+                apr_schedule: apr_schedule,
+            })
+            .args(staking::instruction::UpdateAprSchedule {
+                from_epoch: chunk_from_epoch,
+                aprs: chunk.to_vec(),
+            })
+            .send()?;
+        println!(
+            "Scheduled epochs {}-{}",
+            chunk_from_epoch,
+            chunk_from_epoch + chunk.len() as u64 - 1
+        );
+    }
+
+    Ok(())
+}
+
+pub fn cmd_advance_epoch(
+    staking_program: Program,
+    staking_info: Pubkey,
+) -> Result<(), ClientError> {
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    staking_program
+        .request()
+        .accounts(staking::accounts::AdvanceEpoch {
+            staking_info: staking_info,
+            epoch_history: epoch_history,
+            apr_schedule: apr_schedule,
+        })
+        .args(staking::instruction::AdvanceEpoch {})
+        .send()?;
+
+    Ok(())
+}
+
 pub fn cmd_stake(
     staking_program: Program,
     staking_info: Pubkey,
     amount: u64,
+    lockup_option: u8,
+    auto_compound: bool,
 ) -> Result<(), ClientError> {
     let staking_info_data: StakingInfo = staking_program.account(staking_info)?;
 
-    let (user_info, _) = Pubkey::find_program_address(
-        &[
-            staking::state::USER_INFO_SEED.as_bytes(),
-            staking_info.as_ref(),
-            staking_program.payer().as_ref(),
-        ],
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
         &staking_program.id(),
     );
 
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (
+        user_info,
+        position,
+        position_nft_mint,
+        position_nft_auth,
+        position_nft_metadata,
+        position_nft_edition,
+    ) = get_new_position_accounts(&staking_program, staking_info, staking_program.payer());
+
     let user_ggwp_wallet = get_or_create_token_account(
         &staking_program,
         staking_info_data.ggwp_token,
@@ -398,22 +822,131 @@ pub fn cmd_stake(
         .accounts(staking::accounts::Stake {
             user: staking_program.payer(),
             user_info: user_info,
+            position: position,
+            position_nft_mint: position_nft_mint,
+            position_nft_auth: position_nft_auth,
+            user_position_nft_wallet: get_associated_token_address(
+                &staking_program.payer(),
+                &position_nft_mint,
+            ),
+            position_nft_metadata: position_nft_metadata,
+            position_nft_edition: position_nft_edition,
             user_ggwp_wallet: user_ggwp_wallet,
             staking_info: staking_info,
+            epoch_history: epoch_history,
+            apr_schedule: apr_schedule,
+            treasury: staking_info_data.treasury,
+            accumulative_fund: staking_info_data.accumulative_fund,
+            staking_fund: staking_info_data.staking_fund,
+            token_metadata_program: staking::metadata::ID,
+            system_program: system_program::ID,
+            token_program: spl_token::id(),
+            associated_token_program: spl_associated_token_account::id(),
+            rent: sysvar::rent::ID,
+        })
+        .args(staking::instruction::Stake {
+            amount: amount,
+            lockup_option: lockup_option,
+            auto_compound: auto_compound,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+pub fn cmd_stake_for(
+    staking_program: Program,
+    staking_info: Pubkey,
+    beneficiary: Pubkey,
+    amount: u64,
+    lockup_option: u8,
+    auto_compound: bool,
+) -> Result<(), ClientError> {
+    let staking_info_data: StakingInfo = staking_program.account(staking_info)?;
+
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (
+        user_info,
+        position,
+        position_nft_mint,
+        position_nft_auth,
+        position_nft_metadata,
+        position_nft_edition,
+    ) = get_new_position_accounts(&staking_program, staking_info, beneficiary);
+
+    let funder_ggwp_wallet = get_or_create_token_account(
+        &staking_program,
+        staking_info_data.ggwp_token,
+        staking_program.payer(),
+    )?;
+
+    staking_program
+        .request()
+        .accounts(staking::accounts::StakeFor {
+            funder: staking_program.payer(),
+            beneficiary: beneficiary,
+            user_info: user_info,
+            position: position,
+            position_nft_mint: position_nft_mint,
+            position_nft_auth: position_nft_auth,
+            user_position_nft_wallet: get_associated_token_address(
+                &beneficiary,
+                &position_nft_mint,
+            ),
+            position_nft_metadata: position_nft_metadata,
+            position_nft_edition: position_nft_edition,
+            funder_ggwp_wallet: funder_ggwp_wallet,
+            staking_info: staking_info,
+            epoch_history: epoch_history,
+            apr_schedule: apr_schedule,
             treasury: staking_info_data.treasury,
             accumulative_fund: staking_info_data.accumulative_fund,
+            staking_fund: staking_info_data.staking_fund,
+            token_metadata_program: staking::metadata::ID,
             system_program: system_program::ID,
             token_program: spl_token::id(),
+            associated_token_program: spl_associated_token_account::id(),
+            rent: sysvar::rent::ID,
+        })
+        .args(staking::instruction::StakeFor {
+            amount: amount,
+            lockup_option: lockup_option,
+            auto_compound: auto_compound,
         })
-        .args(staking::instruction::Stake { amount: amount })
         .send()?;
 
     Ok(())
 }
 
-pub fn cmd_withdraw(staking_program: Program, staking_info: Pubkey) -> Result<(), ClientError> {
+pub fn cmd_withdraw(
+    staking_program: Program,
+    staking_info: Pubkey,
+    staker: Pubkey,
+    position_index: u64,
+) -> Result<(), ClientError> {
     let staking_info_data: StakingInfo = staking_program.account(staking_info)?;
 
+    let position = get_position_pk(&staking_program, staking_info, staker, position_index);
+    let position_data: Position = staking_program.account(position)?;
+
+    let (staker_info, _) = Pubkey::find_program_address(
+        &[
+            staking::state::USER_INFO_SEED.as_bytes(),
+            staking_info.as_ref(),
+            staker.as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
     let (user_info, _) = Pubkey::find_program_address(
         &[
             staking::state::USER_INFO_SEED.as_bytes(),
@@ -423,6 +956,16 @@ pub fn cmd_withdraw(staking_program: Program, staking_info: Pubkey) -> Result<()
         &staking_program.id(),
     );
 
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
     let user_ggwp_wallet = get_or_create_token_account(
         &staking_program,
         staking_info_data.ggwp_token,
@@ -450,8 +993,16 @@ pub fn cmd_withdraw(staking_program: Program, staking_info: Pubkey) -> Result<()
         .accounts(staking::accounts::Withdraw {
             user: staking_program.payer(),
             user_info: user_info,
+            position: position,
+            position_nft_mint: position_data.nft_mint,
+            user_position_nft_wallet: get_associated_token_address(
+                &staking_program.payer(),
+                &position_data.nft_mint,
+            ),
             user_ggwp_wallet: user_ggwp_wallet,
             staking_info: staking_info,
+            epoch_history: epoch_history,
+            apr_schedule: apr_schedule,
             treasury: staking_info_data.treasury,
             treasury_auth: treasury_auth,
             accumulative_fund: staking_info_data.accumulative_fund,
@@ -460,8 +1011,539 @@ pub fn cmd_withdraw(staking_program: Program, staking_info: Pubkey) -> Result<()
             system_program: system_program::ID,
             token_program: spl_token::id(),
         })
+        // Staker info of the transferred position
+        .accounts(if staker != staking_program.payer() {
+            vec![AccountMeta::new(staker_info, false)]
+        } else {
+            vec![]
+        })
         .args(staking::instruction::Withdraw {})
         .send()?;
 
     Ok(())
 }
+
+pub fn cmd_claim_rewards(
+    staking_program: Program,
+    staking_info: Pubkey,
+    staker: Pubkey,
+    position_index: u64,
+) -> Result<(), ClientError> {
+    let staking_info_data: StakingInfo = staking_program.account(staking_info)?;
+
+    let position = get_position_pk(&staking_program, staking_info, staker, position_index);
+    let position_data: Position = staking_program.account(position)?;
+
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let user_ggwp_wallet = get_or_create_token_account(
+        &staking_program,
+        staking_info_data.ggwp_token,
+        staking_program.payer(),
+    )?;
+
+    let (staking_fund_auth, _) = Pubkey::find_program_address(
+        &[
+            staking::state::STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            staking::state::USER_INFO_SEED.as_bytes(),
+            staking_info.as_ref(),
+            staking_program.payer().as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
+    staking_program
+        .request()
+        .accounts(staking::accounts::ClaimRewards {
+            user: staking_program.payer(),
+            staking_info: staking_info,
+            epoch_history: epoch_history,
+            apr_schedule: apr_schedule,
+            user_info: user_info,
+            position: position,
+            user_position_nft_wallet: get_associated_token_address(
+                &staking_program.payer(),
+                &position_data.nft_mint,
+            ),
+            user_ggwp_wallet: user_ggwp_wallet,
+            staking_fund: staking_info_data.staking_fund,
+            staking_fund_auth: staking_fund_auth,
+            system_program: system_program::ID,
+            token_program: spl_token::id(),
+        })
+        .args(staking::instruction::ClaimRewards {})
+        .send()?;
+
+    Ok(())
+}
+
+pub fn cmd_set_auto_compound(
+    staking_program: Program,
+    staking_info: Pubkey,
+    staker: Pubkey,
+    position_index: u64,
+    auto_compound: bool,
+) -> Result<(), ClientError> {
+    let position = get_position_pk(&staking_program, staking_info, staker, position_index);
+    let position_data: Position = staking_program.account(position)?;
+
+    staking_program
+        .request()
+        .accounts(staking::accounts::SetAutoCompound {
+            user: staking_program.payer(),
+            staking_info: staking_info,
+            position: position,
+            user_position_nft_wallet: get_associated_token_address(
+                &staking_program.payer(),
+                &position_data.nft_mint,
+            ),
+        })
+        .args(staking::instruction::SetAutoCompound {
+            auto_compound: auto_compound,
+        })
+        .send()?;
+
+    Ok(())
+}
+
+pub fn cmd_compound(
+    staking_program: Program,
+    staking_info: Pubkey,
+    staker: Pubkey,
+    position_index: u64,
+) -> Result<(), ClientError> {
+    let staking_info_data: StakingInfo = staking_program.account(staking_info)?;
+
+    let position = get_position_pk(&staking_program, staking_info, staker, position_index);
+
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (staker_info, _) = Pubkey::find_program_address(
+        &[
+            staking::state::USER_INFO_SEED.as_bytes(),
+            staking_info.as_ref(),
+            staker.as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
+    let (staking_fund_auth, _) = Pubkey::find_program_address(
+        &[STAKING_FUND_AUTH_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    staking_program
+        .request()
+        .accounts(staking::accounts::Compound {
+            staking_info: staking_info,
+            epoch_history: epoch_history,
+            apr_schedule: apr_schedule,
+            staker_info: staker_info,
+            position: position,
+            staking_fund_auth: staking_fund_auth,
+            staking_fund: staking_info_data.staking_fund,
+            treasury: staking_info_data.treasury,
+            token_program: spl_token::id(),
+        })
+        .args(staking::instruction::Compound {})
+        .send()?;
+
+    Ok(())
+}
+
+pub fn cmd_claim_iou(staking_program: Program, staking_info: Pubkey) -> Result<(), ClientError> {
+    let staking_info_data: StakingInfo = staking_program.account(staking_info)?;
+
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            staking::state::USER_INFO_SEED.as_bytes(),
+            staking_info.as_ref(),
+            staking_program.payer().as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
+    let user_ggwp_wallet = get_or_create_token_account(
+        &staking_program,
+        staking_info_data.ggwp_token,
+        staking_program.payer(),
+    )?;
+
+    let (staking_fund_auth, _) = Pubkey::find_program_address(
+        &[
+            staking::state::STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
+    staking_program
+        .request()
+        .accounts(staking::accounts::ClaimIOU {
+            user: staking_program.payer(),
+            staking_info: staking_info,
+            user_info: user_info,
+            user_ggwp_wallet: user_ggwp_wallet,
+            staking_fund: staking_info_data.staking_fund,
+            staking_fund_auth: staking_fund_auth,
+            token_program: spl_token::id(),
+        })
+        .args(staking::instruction::ClaimIou {})
+        .send()?;
+
+    Ok(())
+}
+
+pub fn cmd_positions(
+    staking_program: Program,
+    staking_info: Pubkey,
+    user: Pubkey,
+) -> Result<(), ClientError> {
+    let mut staking_info_data: StakingInfo = staking_program.account(staking_info)?;
+
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let epoch_history_data: EpochHistory = staking_program.account(epoch_history)?;
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+    let apr_schedule_data: AprSchedule = staking_program.account(apr_schedule)?;
+
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            staking::state::USER_INFO_SEED.as_bytes(),
+            staking_info.as_ref(),
+            user.as_ref(),
+        ],
+        &staking_program.id(),
+    );
+    let user_info_data: UserInfo = staking_program.account(user_info)?;
+
+    // Record not yet advanced epochs locally to project rewards up to now
+    let current_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64;
+    let mut epoch_aprs = epoch_history_data.aprs;
+    staking::utils::advance_epochs(
+        &mut staking_info_data,
+        &apr_schedule_data.aprs,
+        &mut epoch_aprs,
+        current_time,
+    )?;
+    if staking_info_data.pool_mode == PoolMode::RewardPerShare {
+        let staking_fund_data =
+            get_token_account_data(&staking_program, staking_info_data.staking_fund)?;
+        staking::utils::update_reward_per_share(&mut staking_info_data, staking_fund_data.amount)?;
+    }
+
+    println!(
+        "User staked amount: {}",
+        amount_to_ui_amount(user_info_data.amount, 9)
+    );
+    println!(
+        "User unpaid reward IOU: {}",
+        amount_to_ui_amount(user_info_data.reward_iou, 9)
+    );
+    for index in 0..user_info_data.positions_count {
+        let position = get_position_pk(&staking_program, staking_info, user, index);
+        let position_data: Position = match staking_program.account(position) {
+            Ok(position_data) => position_data,
+            Err(_) => continue,
+        };
+
+        let reward = match staking_info_data.pool_mode {
+            PoolMode::FixedApr => {
+                let (reward, _) = staking::utils::calc_user_unclaimed_reward_amount(
+                    staking_info_data.epoch_period_days,
+                    staking_info_data.start_time,
+                    &epoch_aprs,
+                    position_data.amount,
+                    position_data.stake_time,
+                    position_data.claimed_epoch,
+                    position_data.apr_boost,
+                    current_time,
+                )?;
+                reward
+            }
+            PoolMode::RewardPerShare => staking::utils::calc_pending_reward(
+                staking::utils::calc_boosted_amount(position_data.amount, position_data.apr_boost)?,
+                staking_info_data.reward_per_share,
+                position_data.reward_per_share_paid,
+            )?,
+        };
+        let is_hold_royalty = staking::utils::is_withdraw_royalty(
+            current_time,
+            position_data.stake_time,
+            staking_info_data.hold_period_days,
+        )?;
+        let is_locked = !staking::utils::is_lockup_ended(
+            current_time,
+            position_data.stake_time,
+            position_data.lockup_days,
+        )?;
+        println!(
+            "Position {}: NFT mint {}, amount {}, stake time {}, projected reward {}, hold royalty {}, lockup {} days, APR boost {} bps, locked {}, auto compound {}",
+            index,
+            position_data.nft_mint,
+            amount_to_ui_amount(position_data.amount, 9),
+            position_data.stake_time,
+            amount_to_ui_amount(reward, 9),
+            is_hold_royalty,
+            position_data.lockup_days,
+            position_data.apr_boost,
+            is_locked,
+            position_data.auto_compound,
+        );
+    }
+
+    Ok(())
+}
+
+pub fn cmd_estimate(
+    staking_program: Program,
+    staking_info: Pubkey,
+    user: Option<Pubkey>,
+    amount: Option<u64>,
+    stake_time: Option<i64>,
+    lockup_option: u8,
+    target_time: i64,
+) -> Result<(), ClientError> {
+    let staking_info_data: StakingInfo = staking_program.account(staking_info)?;
+    if staking_info_data.pool_mode == PoolMode::RewardPerShare {
+        println!("Rewards of the reward per share pool depend on the future staking fund deposits");
+        return Ok(());
+    }
+
+    let (epoch_history, _) = Pubkey::find_program_address(
+        &[EPOCH_HISTORY_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+    let epoch_history_data: EpochHistory = staking_program.account(epoch_history)?;
+
+    let (apr_schedule, _) = Pubkey::find_program_address(
+        &[APR_SCHEDULE_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+    let apr_schedule_data: AprSchedule = staking_program.account(apr_schedule)?;
+
+    // Epochs after now use the current APR schedule
+    let epoch_aprs = projection::project_epoch_aprs(
+        &staking_info_data,
+        &epoch_history_data.aprs,
+        &apr_schedule_data.aprs,
+        target_time,
+    )
+    .expect("Projection error");
+
+    // Position index, amount, stake time, claimed epoch and lockup option of every estimate
+    let mut estimates = vec![];
+    if let Some(user) = user {
+        let (user_info, _) = Pubkey::find_program_address(
+            &[
+                staking::state::USER_INFO_SEED.as_bytes(),
+                staking_info.as_ref(),
+                user.as_ref(),
+            ],
+            &staking_program.id(),
+        );
+        let user_info_data: UserInfo = staking_program.account(user_info)?;
+        for index in 0..user_info_data.positions_count {
+            let position = get_position_pk(&staking_program, staking_info, user, index);
+            if let Ok(position_data) = staking_program.account::<Position>(position) {
+                estimates.push((
+                    index.to_string(),
+                    position_data.amount,
+                    position_data.stake_time,
+                    position_data.claimed_epoch,
+                    LockupOption {
+                        lockup_days: position_data.lockup_days,
+                        apr_boost: position_data.apr_boost,
+                    },
+                ));
+            }
+        }
+    } else if let Some(amount) = amount {
+        let lockup_option = *staking_info_data
+            .lockup_options
+            .get(lockup_option as usize)
+            .expect("Invalid lockup option");
+        let royalty_amount =
+            staking::utils::calc_royalty_amount(staking_info_data.royalty, amount)?;
+        let stake_time = stake_time.unwrap_or(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs() as i64,
+        );
+        println!("Stake royalty: {}", amount_to_ui_amount(royalty_amount, 9));
+        estimates.push((
+            "hypothetical".to_string(),
+            amount - royalty_amount,
+            stake_time,
+            0,
+            lockup_option,
+        ));
+    }
+
+    for (label, amount, stake_time, claimed_epoch, lockup_option) in estimates {
+        println!(
+            "Position {}: amount {}, stake time {}, lockup {} days, APR boost {} bps",
+            label,
+            amount_to_ui_amount(amount, 9),
+            stake_time,
+            lockup_option.lockup_days,
+            lockup_option.apr_boost,
+        );
+        let rows = projection::project_staking_rewards(
+            &staking_info_data,
+            &epoch_aprs,
+            amount,
+            stake_time,
+            claimed_epoch,
+            lockup_option.apr_boost,
+            target_time,
+        )
+        .expect("Projection error");
+        for row in &rows {
+            println!(
+                "  Epoch {}: APR {} bps, reward {}, total reward {}",
+                row.epoch,
+                row.apr,
+                amount_to_ui_amount(row.reward, 9),
+                amount_to_ui_amount(row.total_reward, 9),
+            );
+        }
+
+        let reward = rows.last().map(|row| row.total_reward).unwrap_or(0);
+        let hold_royalty_amount = if staking::utils::is_withdraw_royalty(
+            target_time,
+            stake_time,
+            staking_info_data.hold_period_days,
+        )? {
+            staking::utils::calc_royalty_amount(staking_info_data.hold_royalty, amount)?
+        } else {
+            0
+        };
+        let is_locked =
+            !staking::utils::is_lockup_ended(target_time, stake_time, lockup_option.lockup_days)?;
+        println!(
+            "  Withdraw at {}: reward {}, hold royalty {}, total {}, locked {}",
+            target_time,
+            amount_to_ui_amount(reward, 9),
+            amount_to_ui_amount(hold_royalty_amount, 9),
+            amount_to_ui_amount(amount - hold_royalty_amount + reward, 9),
+            is_locked,
+        );
+    }
+
+    Ok(())
+}
+
+/// Get user info, position, position NFT mint, auth, metadata and edition of the next position of owner.
+fn get_new_position_accounts(
+    staking_program: &Program,
+    staking_info: Pubkey,
+    owner: Pubkey,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey, Pubkey, Pubkey) {
+    let (user_info, _) = Pubkey::find_program_address(
+        &[
+            staking::state::USER_INFO_SEED.as_bytes(),
+            staking_info.as_ref(),
+            owner.as_ref(),
+        ],
+        &staking_program.id(),
+    );
+
+    let positions_count = staking_program
+        .account::<UserInfo>(user_info)
+        .map(|user_info_data| user_info_data.positions_count)
+        .unwrap_or(0);
+    let position = get_position_pk(staking_program, staking_info, owner, positions_count);
+    println!("New position index: {}", positions_count);
+
+    let (position_nft_mint, _) = Pubkey::find_program_address(
+        &[POSITION_NFT_MINT_SEED.as_bytes(), position.as_ref()],
+        &staking_program.id(),
+    );
+    println!("Position NFT mint: {}", position_nft_mint);
+
+    let (position_nft_auth, _) = Pubkey::find_program_address(
+        &[POSITION_NFT_AUTH_SEED.as_bytes(), staking_info.as_ref()],
+        &staking_program.id(),
+    );
+
+    let (position_nft_metadata, _) = Pubkey::find_program_address(
+        &[
+            staking::metadata::METADATA_SEED.as_bytes(),
+            staking::metadata::ID.as_ref(),
+            position_nft_mint.as_ref(),
+        ],
+        &staking::metadata::ID,
+    );
+
+    let (position_nft_edition, _) = Pubkey::find_program_address(
+        &[
+            staking::metadata::METADATA_SEED.as_bytes(),
+            staking::metadata::ID.as_ref(),
+            position_nft_mint.as_ref(),
+            staking::metadata::EDITION_SEED.as_bytes(),
+        ],
+        &staking::metadata::ID,
+    );
+
+    (
+        user_info,
+        position,
+        position_nft_mint,
+        position_nft_auth,
+        position_nft_metadata,
+        position_nft_edition,
+    )
+}
+
+fn get_position_pk(
+    staking_program: &Program,
+    staking_info: Pubkey,
+    user: Pubkey,
+    index: u64,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            staking_info.as_ref(),
+            user.as_ref(),
+            index.to_le_bytes().as_ref(),
+        ],
+        &staking_program.id(),
+    )
+    .0
+}
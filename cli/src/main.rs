@@ -18,6 +18,7 @@ mod app;
 mod commands;
 mod config;
 mod handlers;
+mod projection;
 
 fn main() {
     let app = app::get_clap_app(crate_name!(), crate_description!(), crate_version!());
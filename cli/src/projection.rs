@@ -0,0 +1,431 @@
+//! Off-chain projection of staking and freezing rewards.
+//! Uses the same reward calculations as the smart contracts.
+use chrono::NaiveDate;
+use freezing::state::RewardTableRow;
+use reward_calc::WAD;
+use staking::state::{StakingInfo, EPOCH_HISTORY_MAX_LEN};
+
+/// Maximum number of projected epochs or reward periods.
+pub const MAX_PROJECTION_ROWS: u64 = 10_000;
+
+#[derive(Debug, PartialEq)]
+pub struct StakingEpochRow {
+    pub epoch: u64,
+    // Applied APR with boost in basis points
+    pub apr: u16,
+    pub reward: u64,
+    pub total_reward: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FreezingPeriodRow {
+    pub period: u64,
+    pub time: i64,
+    pub gpass: u64,
+    pub total_gpass: u64,
+}
+
+/// Parse the target date as `YYYY-MM-DD` (midnight UTC) or unix timestamp.
+pub fn parse_target_time(value: &str) -> Result<i64, String> {
+    if let Ok(time) = value.parse::<i64>() {
+        return Ok(time);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms(0, 0, 0).timestamp())
+        .map_err(|_| {
+            format!(
+                "Invalid date {}, expected YYYY-MM-DD or unix timestamp",
+                value
+            )
+        })
+}
+
+/// Checks the number of projected rows is within the limit.
+fn check_projection_len(rows_count: u64) -> Result<(), String> {
+    if rows_count > MAX_PROJECTION_ROWS {
+        return Err(format!(
+            "Target date is too far: {} rows to project, max {}",
+            rows_count, MAX_PROJECTION_ROWS
+        ));
+    }
+
+    Ok(())
+}
+
+/// Record the APRs of epochs completed until the target time.
+/// Future epochs use the current APR schedule.
+pub fn project_epoch_aprs(
+    staking_info: &StakingInfo,
+    epoch_aprs: &[u16],
+    apr_schedule: &[u16],
+    target_time: i64,
+) -> Result<Vec<u16>, String> {
+    let (target_epoch, _) = reward_calc::get_epoch_by_time(
+        staking_info.start_time,
+        target_time,
+        staking_info.epoch_period_days,
+    )
+    .ok_or("Overflow")?;
+
+    // Epochs after the full history use the last recorded APR
+    let mut epoch_aprs = epoch_aprs.to_vec();
+    let mut epoch = staking_info.epoch;
+    while epoch < target_epoch && epoch_aprs.len() < EPOCH_HISTORY_MAX_LEN {
+        let apr =
+            staking::utils::get_scheduled_apr(apr_schedule, epoch).map_err(|e| e.to_string())?;
+        epoch_aprs.push(apr);
+        epoch += 1;
+    }
+
+    Ok(epoch_aprs)
+}
+
+/// Get the epoch by epoch rewards of the position until the target time.
+/// Reward per share pools can't be projected as rewards depend on future deposits.
+pub fn project_staking_rewards(
+    staking_info: &StakingInfo,
+    epoch_aprs: &[u16],
+    amount: u64,
+    stake_time: i64,
+    claimed_epoch: u64,
+    apr_boost: u16,
+    target_time: i64,
+) -> Result<Vec<StakingEpochRow>, String> {
+    let start_epoch = reward_calc::get_position_start_epoch(
+        staking_info.start_time,
+        stake_time,
+        staking_info.epoch_period_days,
+    )
+    .ok_or("Overflow")?
+    .max(claimed_epoch + 1);
+    let (end_epoch, _) = reward_calc::get_epoch_by_time(
+        staking_info.start_time,
+        target_time,
+        staking_info.epoch_period_days,
+    )
+    .ok_or("Overflow")?;
+    check_projection_len(end_epoch.saturating_sub(start_epoch))?;
+
+    // Growth is compounded epoch by epoch as in the contract
+    let mut rows = vec![];
+    let mut growth = Some(WAD);
+    let mut total_reward = 0;
+    for epoch in start_epoch..end_epoch {
+        let recorded_apr =
+            staking::utils::get_recorded_apr(epoch_aprs, epoch).map_err(|e| e.to_string())?;
+        let apr = reward_calc::calc_boosted_apr(recorded_apr, apr_boost).ok_or("Overflow")?;
+        growth = growth.and_then(|growth| {
+            reward_calc::mul_wad(
+                growth,
+                reward_calc::calc_compound_growth(apr, staking_info.epoch_period_days)?,
+            )
+        });
+        let epoch_total_reward = reward_calc::calc_grown_amount(amount, growth) - amount;
+        rows.push(StakingEpochRow {
+            epoch,
+            apr,
+            reward: epoch_total_reward - total_reward,
+            total_reward: epoch_total_reward,
+        });
+        total_reward = epoch_total_reward;
+    }
+
+    Ok(rows)
+}
+
+/// Get the period by period GPASS earned by freezed amount until the target time.
+pub fn project_freezing_rewards(
+    reward_table: &[RewardTableRow],
+    freezed_amount: u64,
+    last_getting_gpass: i64,
+    reward_period: i64,
+    target_time: i64,
+) -> Result<Vec<FreezingPeriodRow>, String> {
+    let mut rows = vec![];
+    if reward_period <= 0 || target_time < last_getting_gpass {
+        return Ok(rows);
+    }
+
+    let periods_count = target_time
+        .checked_sub(last_getting_gpass)
+        .ok_or("Overflow")?
+        / reward_period;
+    check_projection_len(periods_count as u64)?;
+
+    let gpass_per_period = reward_calc::calc_gpass_per_period(
+        reward_table
+            .iter()
+            .map(|row| (row.ggwp_amount, row.gpass_amount)),
+        freezed_amount,
+    );
+    let mut total_gpass = 0;
+    for period in 1..=periods_count {
+        let time = last_getting_gpass + period * reward_period;
+        let period_total_gpass = reward_calc::calc_earned_gpass(
+            gpass_per_period,
+            time,
+            last_getting_gpass,
+            reward_period,
+        )
+        .ok_or("Overflow")?;
+        rows.push(FreezingPeriodRow {
+            period: period as u64,
+            time,
+            gpass: period_total_gpass - total_gpass,
+            total_gpass: period_total_gpass,
+        });
+        total_gpass = period_total_gpass;
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+#[allow(non_upper_case_globals)]
+mod tests {
+    use super::*;
+    use reward_calc::SECONDS_IN_DAY;
+    use staking::state::{PoolMode, BASE_APR_BOOST};
+
+    const time: i64 = 1660032700;
+    const day: i64 = SECONDS_IN_DAY;
+
+    fn staking_info() -> StakingInfo {
+        StakingInfo {
+            start_time: time,
+            epoch: 1,
+            epoch_period_days: 10,
+            pool_mode: PoolMode::FixedApr,
+            ..Default::default()
+        }
+    }
+
+    fn reward_table() -> Vec<RewardTableRow> {
+        vec![
+            RewardTableRow {
+                ggwp_amount: 1000,
+                gpass_amount: 5,
+            },
+            RewardTableRow {
+                ggwp_amount: 2000,
+                gpass_amount: 10,
+            },
+        ]
+    }
+
+    #[test]
+    pub fn test_parse_target_time() {
+        assert_eq!(parse_target_time("1660032700"), Ok(1660032700));
+        assert_eq!(parse_target_time("2022-08-09"), Ok(1660003200));
+        assert!(parse_target_time("09.08.2022").is_err());
+    }
+
+    #[test]
+    pub fn test_project_epoch_aprs() {
+        let staking_info = staking_info();
+        assert_eq!(
+            project_epoch_aprs(&staking_info, &[], &[4500, 4400, 4300], time + 5 * day),
+            Ok(vec![])
+        );
+        assert_eq!(
+            project_epoch_aprs(&staking_info, &[], &[4500, 4400, 4300], time + 45 * day),
+            Ok(vec![4500, 4400, 4300, 4300])
+        );
+        // Recorded APRs are kept
+        let staking_info = StakingInfo {
+            epoch: 3,
+            ..staking_info
+        };
+        assert_eq!(
+            project_epoch_aprs(
+                &staking_info,
+                &[4000, 3900],
+                &[4500, 4400, 4300],
+                time + 45 * day
+            ),
+            Ok(vec![4000, 3900, 4300, 4300])
+        );
+        // Far epochs use the last recorded APR without growing the history
+        let epoch_aprs = project_epoch_aprs(&staking_info, &[], &[4500], i64::MAX).unwrap();
+        assert_eq!(epoch_aprs.len(), EPOCH_HISTORY_MAX_LEN);
+    }
+
+    #[test]
+    pub fn test_project_staking_rewards() {
+        let staking_info = staking_info();
+        let amount = 19_000_000_000;
+        let epoch_aprs =
+            project_epoch_aprs(&staking_info, &[], &[4500, 4400], time + 55 * day).unwrap();
+
+        // Epoch of stake is not rewarded.
+        // Expected rewards are the exact daily compounding rounded down:
+        // 19e9 * ((1 + 0.44 / 365) ^ (10 * epochs) - 1)
+        let rows = project_staking_rewards(
+            &staking_info,
+            &epoch_aprs,
+            amount,
+            time + 5 * day,
+            0,
+            BASE_APR_BOOST,
+            time + 55 * day,
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                StakingEpochRow {
+                    epoch: 2,
+                    apr: 4400,
+                    reward: 230_287_567,
+                    total_reward: 230_287_567,
+                },
+                StakingEpochRow {
+                    epoch: 3,
+                    apr: 4400,
+                    reward: 233_078_745,
+                    total_reward: 463_366_312,
+                },
+                StakingEpochRow {
+                    epoch: 4,
+                    apr: 4400,
+                    reward: 235_903_753,
+                    total_reward: 699_270_065,
+                },
+                StakingEpochRow {
+                    epoch: 5,
+                    apr: 4400,
+                    reward: 238_762_999,
+                    total_reward: 938_033_064,
+                },
+            ]
+        );
+
+        // Claimed epochs are skipped, APR boost is applied:
+        // 19e9 * ((1 + 0.88 / 365) ^ (10 * epochs) - 1)
+        let rows = project_staking_rewards(
+            &staking_info,
+            &epoch_aprs,
+            amount,
+            time,
+            3,
+            2 * BASE_APR_BOOST,
+            time + 55 * day,
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                StakingEpochRow {
+                    epoch: 4,
+                    apr: 8800,
+                    reward: 463_084_157,
+                    total_reward: 463_084_157,
+                },
+                StakingEpochRow {
+                    epoch: 5,
+                    apr: 8800,
+                    reward: 474_370_838,
+                    total_reward: 937_454_995,
+                },
+            ]
+        );
+
+        // Target before the first rewarded epoch end
+        assert_eq!(
+            project_staking_rewards(
+                &staking_info,
+                &epoch_aprs,
+                amount,
+                time + 5 * day,
+                0,
+                BASE_APR_BOOST,
+                time + 15 * day,
+            ),
+            Ok(vec![])
+        );
+
+        // Projection is bounded
+        let staking_info = StakingInfo {
+            epoch_period_days: 1,
+            ..staking_info
+        };
+        assert!(project_staking_rewards(
+            &staking_info,
+            &epoch_aprs,
+            amount,
+            time,
+            0,
+            BASE_APR_BOOST,
+            time + (MAX_PROJECTION_ROWS as i64 + 2) * day,
+        )
+        .is_err());
+    }
+
+    #[test]
+    pub fn test_project_freezing_rewards() {
+        let reward_table = reward_table();
+
+        assert_eq!(
+            project_freezing_rewards(&reward_table, 1500, time, day, time + day - 1),
+            Ok(vec![])
+        );
+        assert_eq!(
+            project_freezing_rewards(&reward_table, 1500, time, day, time - day),
+            Ok(vec![])
+        );
+        assert_eq!(
+            project_freezing_rewards(&reward_table, 2500, time, day, time + 2 * day + 5),
+            Ok(vec![
+                FreezingPeriodRow {
+                    period: 1,
+                    time: time + day,
+                    gpass: 10,
+                    total_gpass: 10,
+                },
+                FreezingPeriodRow {
+                    period: 2,
+                    time: time + 2 * day,
+                    gpass: 10,
+                    total_gpass: 20,
+                },
+            ])
+        );
+        assert_eq!(
+            project_freezing_rewards(&reward_table, 500, time, day, time + 2 * day),
+            Ok(vec![
+                FreezingPeriodRow {
+                    period: 1,
+                    time: time + day,
+                    gpass: 0,
+                    total_gpass: 0,
+                },
+                FreezingPeriodRow {
+                    period: 2,
+                    time: time + 2 * day,
+                    gpass: 0,
+                    total_gpass: 0,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    pub fn test_project_freezing_rewards_bounded() {
+        let reward_table = reward_table();
+        let max_time = time + MAX_PROJECTION_ROWS as i64 * day;
+
+        let rows = project_freezing_rewards(&reward_table, 1500, time, day, max_time).unwrap();
+        assert_eq!(rows.len() as u64, MAX_PROJECTION_ROWS);
+        assert_eq!(
+            rows.last(),
+            Some(&FreezingPeriodRow {
+                period: MAX_PROJECTION_ROWS,
+                time: max_time,
+                gpass: 5,
+                total_gpass: 5 * MAX_PROJECTION_ROWS,
+            })
+        );
+        assert!(project_freezing_rewards(&reward_table, 1500, time, day, max_time + day).is_err());
+        assert!(project_freezing_rewards(&reward_table, 1500, time, 1, i64::MAX).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Reward calculations shared by the smart contracts and the CLI.
+//! Functions are pure and don't log, None means arithmetic overflow.
+
+/// Fixed-point scale of the compounding growth.
+/// Every multiplication rounds down, so rewards never exceed the exact value.
+/// Daily base and every product lose less than 1e-18 of the growth, so the reward
+/// of D days compounded in E epochs stays within 1 lamport + new amount * (2D + 17E) * 1e-18
+/// below the exact value.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+pub const DAYS_IN_YEAR: u128 = 365;
+pub const MAX_BPS: u128 = 10_000;
+pub const SECONDS_IN_DAY: i64 = 24 * 60 * 60;
+// GGWP token decimals
+pub const DECIMALS: u32 = 9;
+
+/// Multiply fixed-point values rounding down.
+pub fn mul_wad(a: u128, b: u128) -> Option<u128> {
+    (a / WAD)
+        .checked_mul(b)?
+        .checked_add((a % WAD).checked_mul(b)? / WAD)
+}
+
+/// Get the fixed-point growth of daily compounding with APR in basis points for number of days.
+/// None if the growth doesn't fit into u128.
+pub fn calc_compound_growth(apr: u16, days: u16) -> Option<u128> {
+    let mut base = WAD + apr as u128 * WAD / (DAYS_IN_YEAR * MAX_BPS);
+    let mut exp = days;
+    let mut growth = WAD;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            growth = mul_wad(growth, base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul_wad(base, base)?;
+        }
+    }
+
+    Some(growth)
+}
+
+/// Apply fixed-point growth to amount. Saturates at u64::MAX.
+pub fn calc_grown_amount(amount: u64, growth: Option<u128>) -> u64 {
+    if amount == 0 {
+        return 0;
+    }
+    growth
+        .and_then(|growth| mul_wad(amount as u128, growth))
+        .and_then(|amount| u64::try_from(amount).ok())
+        .unwrap_or(u64::MAX)
+}
+
+/// Get the APR in basis points with APR boost in basis points.
+pub fn calc_boosted_apr(apr: u16, apr_boost: u16) -> Option<u16> {
+    u16::try_from(apr as u128 * apr_boost as u128 / MAX_BPS).ok()
+}
+
+/// Get number of epoch by time and whether time is the first day of the epoch.
+pub fn get_epoch_by_time(
+    staking_start_time: i64,
+    time: i64,
+    epoch_period_days: u16,
+) -> Option<(u64, bool)> {
+    let spent_days = time
+        .checked_sub(staking_start_time)?
+        .checked_div(SECONDS_IN_DAY)?;
+    let epoch_period_days = epoch_period_days as i64;
+    let epoch = spent_days.checked_div(epoch_period_days)?;
+
+    Some((epoch as u64 + 1, spent_days % epoch_period_days == 0))
+}
+
+/// Get the first rewarded epoch of the position.
+/// Position staked after the first day of the epoch is rewarded from the next one.
+pub fn get_position_start_epoch(
+    staking_start_time: i64,
+    stake_time: i64,
+    epoch_period_days: u16,
+) -> Option<u64> {
+    let (epoch, is_full_epoch) =
+        get_epoch_by_time(staking_start_time, stake_time, epoch_period_days)?;
+    Some(if is_full_epoch { epoch } else { epoch + 1 })
+}
+
+/// Get the GPASS earned per reward period by freezed amount.
+/// Reward table rows are GGWP and GPASS amounts in ascending order.
+pub fn calc_gpass_per_period(
+    reward_table: impl IntoIterator<Item = (u64, u64)>,
+    freezed_amount: u64,
+) -> u64 {
+    let mut gpass_amount = 0;
+    for (row_ggwp_amount, row_gpass_amount) in reward_table {
+        if freezed_amount < row_ggwp_amount {
+            break;
+        }
+        gpass_amount = row_gpass_amount;
+    }
+
+    gpass_amount
+}
+
+/// Get the GPASS earned for the full reward periods spent since the last getting GPASS.
+pub fn calc_earned_gpass(
+    gpass_per_period: u64,
+    current_time: i64,
+    last_getting_gpass: i64,
+    reward_period: i64,
+) -> Option<u64> {
+    let spent_time = current_time.checked_sub(last_getting_gpass)?;
+    if spent_time < reward_period {
+        return Some(0);
+    }
+
+    let reward_periods_spent = spent_time.checked_div(reward_period)? as u64;
+    gpass_per_period.checked_mul(reward_periods_spent)
+}
+
+/// Get the royalty in percent of amount.
+pub fn calc_royalty_amount(royalty: u8, amount: u64) -> u64 {
+    let multiplier = 10_u64.pow(DECIMALS) as f64;
+    let ui_amount = amount as f64 / multiplier;
+    let royalty_amount = ui_amount / 100.0 * royalty as f64;
+    (royalty_amount * multiplier) as u64
+}
+
+/// Checks freezed time for withdraw royalty.
+pub fn is_withdraw_royalty(
+    current_time: i64,
+    freezed_time: i64,
+    unfreeze_lock_period: i64,
+) -> Option<bool> {
+    Some(current_time.checked_sub(freezed_time)? < unfreeze_lock_period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = SECONDS_IN_DAY;
+
+    #[test]
+    pub fn test_get_epoch_by_time() {
+        assert_eq!(get_epoch_by_time(0, 0, 10), Some((1, true)));
+        assert_eq!(get_epoch_by_time(0, DAY - 1, 10), Some((1, true)));
+        assert_eq!(get_epoch_by_time(0, DAY, 10), Some((1, false)));
+        assert_eq!(get_epoch_by_time(0, 10 * DAY, 10), Some((2, true)));
+        assert_eq!(get_epoch_by_time(0, DAY, 0), None);
+        assert_eq!(get_position_start_epoch(0, 10 * DAY, 10), Some(2));
+        assert_eq!(get_position_start_epoch(0, 11 * DAY, 10), Some(3));
+    }
+
+    #[test]
+    pub fn test_calc_gpass_per_period() {
+        let reward_table = [(1000, 5), (2000, 10)];
+        assert_eq!(calc_gpass_per_period(reward_table, 999), 0);
+        assert_eq!(calc_gpass_per_period(reward_table, 1000), 5);
+        assert_eq!(calc_gpass_per_period(reward_table, 1999), 5);
+        assert_eq!(calc_gpass_per_period(reward_table, u64::MAX), 10);
+        assert_eq!(calc_gpass_per_period([], 1000), 0);
+    }
+
+    #[test]
+    pub fn test_calc_earned_gpass() {
+        assert_eq!(calc_earned_gpass(5, DAY - 1, 0, DAY), Some(0));
+        assert_eq!(calc_earned_gpass(5, -DAY, 0, DAY), Some(0));
+        assert_eq!(calc_earned_gpass(5, 3 * DAY - 1, 0, DAY), Some(10));
+        assert_eq!(calc_earned_gpass(u64::MAX, 2 * DAY, 0, DAY), None);
+        assert_eq!(calc_earned_gpass(5, DAY, 0, 0), None);
+    }
+
+    #[test]
+    pub fn test_calc_royalty_amount() {
+        assert_eq!(calc_royalty_amount(8, 1_000_000_000), 80_000_000);
+        assert_eq!(calc_royalty_amount(0, 1_000_000_000), 0);
+        assert_eq!(calc_royalty_amount(100, 5_000_000_000), 5_000_000_000);
+    }
+
+    #[test]
+    pub fn test_is_withdraw_royalty() {
+        assert_eq!(is_withdraw_royalty(DAY - 1, 0, DAY), Some(true));
+        assert_eq!(is_withdraw_royalty(DAY, 0, DAY), Some(false));
+        assert_eq!(is_withdraw_royalty(i64::MAX, -1, DAY), None);
+    }
+}
@@ -1,5 +1,9 @@
 use crate::error::DistributionError;
-use crate::state::{DistributionInfo, ACCUMULATIVE_FUND_AUTH_SEED};
+use crate::state::{
+    DistributionHistory, DistributionInfo, PendingRecipients, VestingEscrow,
+    ACCUMULATIVE_FUND_AUTH_SEED, DISTRIBUTION_HISTORY_SEED, PENDING_RECIPIENTS_SEED,
+    VESTING_ESCROW_AUTH_SEED, VESTING_ESCROW_SEED,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
@@ -9,6 +13,14 @@ pub struct Initialize<'info> {
     pub admin: Signer<'info>,
     #[account(init, payer = admin, space = DistributionInfo::LEN)]
     pub distribution_info: Box<Account<'info, DistributionInfo>>,
+    #[account(init, payer = admin, space = DistributionHistory::LEN,
+        seeds = [
+            DISTRIBUTION_HISTORY_SEED.as_bytes(),
+            distribution_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub distribution_history: Box<Account<'info, DistributionHistory>>,
 
     pub ggwp_token: Account<'info, Mint>,
     /// CHECK: Accumulative auth account
@@ -28,27 +40,6 @@ pub struct Initialize<'info> {
     )]
     pub accumulative_fund: Box<Account<'info, TokenAccount>>,
 
-    #[account(
-        constraint = play_to_earn_fund.mint == ggwp_token.key()
-        @DistributionError::InvalidPlayToEarnFundMint,
-    )]
-    pub play_to_earn_fund: Box<Account<'info, TokenAccount>>,
-    #[account(
-        constraint = staking_fund.mint == ggwp_token.key()
-        @DistributionError::InvalidStakingFundMint,
-    )]
-    pub staking_fund: Box<Account<'info, TokenAccount>>,
-    #[account(
-        constraint = company_fund.mint == ggwp_token.key()
-        @DistributionError::InvalidCompanyFundMint,
-    )]
-    pub company_fund: Box<Account<'info, TokenAccount>>,
-    #[account(
-        constraint = team_fund.mint == ggwp_token.key()
-        @DistributionError::InvalidTeamFundMint,
-    )]
-    pub team_fund: Box<Account<'info, TokenAccount>>,
-
     // Misc.
     pub system_program: Program<'info, System>,
 }
@@ -60,11 +51,89 @@ pub struct UpdateParam<'info> {
     pub distribution_info: Account<'info, DistributionInfo>,
 }
 
+#[derive(Accounts)]
+pub struct ExecuteRecipientsTimelock<'info> {
+    #[account(mut)]
+    pub distribution_info: Account<'info, DistributionInfo>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRecipients<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub distribution_info: Box<Account<'info, DistributionInfo>>,
+    #[account(init, payer = authority, space = PendingRecipients::LEN,
+        seeds = [
+            PENDING_RECIPIENTS_SEED.as_bytes(),
+            distribution_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pending_recipients: Box<Account<'info, PendingRecipients>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecipients<'info> {
+    pub admin: Signer<'info>,
+    pub distribution_info: Box<Account<'info, DistributionInfo>>,
+    #[account(mut, close = proposer,
+        seeds = [
+            PENDING_RECIPIENTS_SEED.as_bytes(),
+            distribution_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pending_recipients: Box<Account<'info, PendingRecipients>>,
+    /// CHECK: Proposer gets the rent back
+    #[account(mut,
+        constraint = proposer.key() == pending_recipients.proposer
+        @DistributionError::InvalidProposer,
+    )]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecipients<'info> {
+    #[account(mut)]
+    pub distribution_info: Box<Account<'info, DistributionInfo>>,
+    #[account(mut, close = proposer,
+        seeds = [
+            PENDING_RECIPIENTS_SEED.as_bytes(),
+            distribution_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pending_recipients: Box<Account<'info, PendingRecipients>>,
+    /// CHECK: Proposer gets the rent back
+    #[account(mut,
+        constraint = proposer.key() == pending_recipients.proposer
+        @DistributionError::InvalidProposer,
+    )]
+    pub proposer: UncheckedAccount<'info>,
+}
+
 #[derive(Accounts)]
 pub struct Distribute<'info> {
+    pub caller: Signer<'info>,
     #[account(mut)]
     pub distribution_info: Box<Account<'info, DistributionInfo>>,
+    #[account(mut,
+        seeds = [
+            DISTRIBUTION_HISTORY_SEED.as_bytes(),
+            distribution_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub distribution_history: Box<Account<'info, DistributionHistory>>,
 
+    #[account(mut,
+        constraint = ggwp_token.key() == distribution_info.ggwp_token
+        @DistributionError::InvalidGGWPToken,
+    )]
+    pub ggwp_token: Box<Account<'info, Mint>>,
     /// CHECK: Accumulative auth account
     #[account(
         seeds = [
@@ -83,35 +152,91 @@ pub struct Distribute<'info> {
         @DistributionError::InvalidFundPublicKey,
     )]
     pub accumulative_fund: Box<Account<'info, TokenAccount>>,
-
     #[account(mut,
-        constraint = play_to_earn_fund.mint == distribution_info.ggwp_token.key()
-        @DistributionError::InvalidPlayToEarnFundMint,
-        constraint = play_to_earn_fund.key() == distribution_info.play_to_earn_fund
-        @DistributionError::InvalidFundPublicKey,
+        constraint = caller_ggwp_wallet.mint == distribution_info.ggwp_token
+        @DistributionError::InvalidCallerGGWPWalletMint,
+    )]
+    pub caller_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVestingEscrow<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub distribution_info: Box<Account<'info, DistributionInfo>>,
+    #[account(init, payer = admin, space = VestingEscrow::LEN,
+        seeds = [
+            VESTING_ESCROW_SEED.as_bytes(),
+            distribution_info.key().as_ref(),
+            beneficiary_wallet.key().as_ref(),
+        ],
+        bump
     )]
-    pub play_to_earn_fund: Box<Account<'info, TokenAccount>>,
+    pub vesting_escrow: Box<Account<'info, VestingEscrow>>,
+
+    /// CHECK: Escrow auth account
+    #[account(
+        seeds = [
+            VESTING_ESCROW_AUTH_SEED.as_bytes(),
+            vesting_escrow.key().as_ref(),
+        ],
+        bump
+    )]
+    pub escrow_auth: UncheckedAccount<'info>,
+    #[account(
+        constraint = escrow_wallet.mint == distribution_info.ggwp_token
+        @DistributionError::InvalidEscrowWalletMint,
+        constraint = escrow_wallet.owner == escrow_auth.key()
+        @DistributionError::InvalidEscrowWalletOwner,
+    )]
+    pub escrow_wallet: Box<Account<'info, TokenAccount>>,
+    #[account(
+        constraint = beneficiary_wallet.mint == distribution_info.ggwp_token
+        @DistributionError::InvalidBeneficiaryWalletMint,
+    )]
+    pub beneficiary_wallet: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    pub authority: Signer<'info>,
     #[account(mut,
-        constraint = staking_fund.mint == distribution_info.ggwp_token.key()
-        @DistributionError::InvalidStakingFundMint,
-        constraint = staking_fund.key() == distribution_info.staking_fund
-        @DistributionError::InvalidFundPublicKey,
+        seeds = [
+            VESTING_ESCROW_SEED.as_bytes(),
+            vesting_escrow.distribution_info.as_ref(),
+            vesting_escrow.beneficiary_wallet.as_ref(),
+        ],
+        bump
+    )]
+    pub vesting_escrow: Box<Account<'info, VestingEscrow>>,
+
+    /// CHECK: Escrow auth account
+    #[account(
+        seeds = [
+            VESTING_ESCROW_AUTH_SEED.as_bytes(),
+            vesting_escrow.key().as_ref(),
+        ],
+        bump = vesting_escrow.escrow_auth_bump,
     )]
-    pub staking_fund: Box<Account<'info, TokenAccount>>,
+    pub escrow_auth: UncheckedAccount<'info>,
     #[account(mut,
-        constraint = company_fund.mint == distribution_info.ggwp_token.key()
-        @DistributionError::InvalidCompanyFundMint,
-        constraint = company_fund.key() == distribution_info.company_fund
+        constraint = escrow_wallet.key() == vesting_escrow.escrow_wallet
         @DistributionError::InvalidFundPublicKey,
     )]
-    pub company_fund: Box<Account<'info, TokenAccount>>,
+    pub escrow_wallet: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        constraint = team_fund.mint == distribution_info.ggwp_token.key()
-        @DistributionError::InvalidTeamFundMint,
-        constraint = team_fund.key() == distribution_info.team_fund
+        constraint = beneficiary_wallet.key() == vesting_escrow.beneficiary_wallet
         @DistributionError::InvalidFundPublicKey,
+        constraint = beneficiary_wallet.owner == authority.key()
+        @DistributionError::AccessDenied,
     )]
-    pub team_fund: Box<Account<'info, TokenAccount>>,
+    pub beneficiary_wallet: Box<Account<'info, TokenAccount>>,
 
     // Misc.
     pub token_program: Program<'info, Token>,
@@ -30,4 +30,46 @@ pub enum DistributionError {
     InvalidFundPublicKey, // 6009
     #[msg("Empty accumulative fund")]
     EmptyAccumulativeFund, // 6010
+    #[msg("Invalid recipients")]
+    InvalidRecipients, // 6011
+    #[msg("Invalid recipient accounts")]
+    InvalidRecipientAccounts, // 6012
+    #[msg("Invalid recipient fund mint")]
+    InvalidRecipientFundMint, // 6013
+    #[msg("Distribution interval is not passed")]
+    DistributionIntervalNotPassed, // 6014
+    #[msg("Accumulative fund amount is less than minimum")]
+    LessThanMinDistributionAmount, // 6015
+    #[msg("Invalid keeper reward share value")]
+    InvalidKeeperRewardShare, // 6016
+    #[msg("Invalid min distribution interval value")]
+    InvalidMinDistributionInterval, // 6017
+    #[msg("Invalid caller GGWP wallet mint")]
+    InvalidCallerGGWPWalletMint, // 6018
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule, // 6019
+    #[msg("Invalid escrow wallet mint")]
+    InvalidEscrowWalletMint, // 6020
+    #[msg("Invalid escrow wallet owner")]
+    InvalidEscrowWalletOwner, // 6021
+    #[msg("Invalid beneficiary wallet mint")]
+    InvalidBeneficiaryWalletMint, // 6022
+    #[msg("Nothing to claim")]
+    NothingToClaim, // 6023
+    #[msg("Invalid burn share value")]
+    InvalidBurnShare, // 6024
+    #[msg("Invalid GGWP token")]
+    InvalidGGWPToken, // 6025
+    #[msg("Invalid recipients timelock value")]
+    InvalidRecipientsTimelock, // 6026
+    #[msg("Recipients timelock is not passed")]
+    RecipientsTimelockNotPassed, // 6027
+    #[msg("Invalid proposer")]
+    InvalidProposer, // 6028
+    #[msg("No pending recipients timelock")]
+    NoPendingRecipientsTimelock, // 6029
+    #[msg("Invalid vesting escrow")]
+    InvalidVestingEscrow, // 6030
+    #[msg("Too many vesting tranches")]
+    TooManyVestingTranches, // 6031
 }
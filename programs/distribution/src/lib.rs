@@ -1,40 +1,46 @@
 use crate::context::*;
 use crate::error::DistributionError;
-use crate::state::ACCUMULATIVE_FUND_AUTH_SEED;
+use crate::state::{
+    DistributionRecord, FundAmount, Recipient, VestingEscrow, ACCUMULATIVE_FUND_AUTH_SEED, MAX_BPS,
+    MAX_KEEPER_REWARD_SHARE, MIN_RECIPIENTS_TIMELOCK, VESTING_ESCROW_AUTH_SEED,
+};
 use anchor_lang::prelude::*;
-use anchor_spl::token::Transfer;
+use anchor_spl::token::{Burn, TokenAccount, Transfer};
 
 declare_id!("79GShMQgEBcfpiiwkBxv3yBxHqCN8J2E8DhivatqpfYC");
 
 mod context;
 mod error;
 pub mod state;
+pub mod utils;
 
 #[program]
 pub mod distribution {
     use super::*;
 
     /// Initialize with information about funds and shares.
-    pub fn initialize(
-        ctx: Context<Initialize>,
+    /// Recipient fund accounts are passed in the list order through remaining accounts.
+    pub fn initialize<'info>(
+        ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
         update_auth: Pubkey,
-        play_to_earn_fund_share: u8,
-        staking_fund_share: u8,
-        company_fund_share: u8,
-        team_fund_share: u8,
+        recipients: Vec<Recipient>,
+        dust_recipient: Pubkey,
+        recipients_timelock: i64,
     ) -> Result<()> {
         require!(
-            play_to_earn_fund_share <= 100,
-            DistributionError::InvalidShare
+            utils::is_valid_recipients(&recipients, dust_recipient),
+            DistributionError::InvalidRecipients
         );
-        require!(staking_fund_share <= 100, DistributionError::InvalidShare);
-        require!(company_fund_share <= 100, DistributionError::InvalidShare);
-        require!(team_fund_share <= 100, DistributionError::InvalidShare);
-        require!(
-            (play_to_earn_fund_share + staking_fund_share + company_fund_share + team_fund_share)
-                == 100,
-            DistributionError::InvalidShare
+        require_gte!(
+            recipients_timelock,
+            MIN_RECIPIENTS_TIMELOCK,
+            DistributionError::InvalidRecipientsTimelock
         );
+        check_recipient_funds(
+            &recipients,
+            ctx.remaining_accounts,
+            ctx.accounts.ggwp_token.key(),
+        )?;
 
         let distribution_info = &mut ctx.accounts.distribution_info;
         distribution_info.admin = ctx.accounts.admin.key();
@@ -45,14 +51,17 @@ pub mod distribution {
         distribution_info.accumulative_fund_auth_bump = ctx.bumps["accumulative_fund_auth"];
 
         distribution_info.last_distribution = 0;
-        distribution_info.play_to_earn_fund = ctx.accounts.play_to_earn_fund.key();
-        distribution_info.play_to_earn_fund_share = play_to_earn_fund_share;
-        distribution_info.staking_fund = ctx.accounts.staking_fund.key();
-        distribution_info.staking_fund_share = staking_fund_share;
-        distribution_info.company_fund = ctx.accounts.company_fund.key();
-        distribution_info.company_fund_share = company_fund_share;
-        distribution_info.team_fund = ctx.accounts.team_fund.key();
-        distribution_info.team_fund_share = team_fund_share;
+        distribution_info.recipients = recipients;
+        distribution_info.dust_recipient = dust_recipient;
+
+        distribution_info.min_distribution_interval = 0;
+        distribution_info.min_distribution_amount = 0;
+        distribution_info.keeper_reward_share = 0;
+        distribution_info.burn_share = 0;
+        distribution_info.total_burned = 0;
+        distribution_info.recipients_timelock = recipients_timelock;
+        distribution_info.pending_recipients_timelock = 0;
+        distribution_info.pending_recipients_timelock_at = 0;
 
         Ok(())
     }
@@ -85,15 +94,70 @@ pub mod distribution {
         Ok(())
     }
 
-    /// Update authority can set the new shares.
-    pub fn update_shares(
+    /// Admin can set the new min delay in seconds between proposing and executing
+    /// the recipients change. Increase applies at once and drops the pending reduction.
+    /// Reduction is queued behind the current timelock, so it can't speed up the changes.
+    pub fn update_recipients_timelock(
         ctx: Context<UpdateParam>,
-        play_to_earn_fund_share: u8,
-        staking_fund_share: u8,
-        company_fund_share: u8,
-        team_fund_share: u8,
+        recipients_timelock: i64,
     ) -> Result<()> {
+        require_gte!(
+            recipients_timelock,
+            MIN_RECIPIENTS_TIMELOCK,
+            DistributionError::InvalidRecipientsTimelock
+        );
+
+        let distribution_info = &mut ctx.accounts.distribution_info;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            distribution_info.admin,
+            DistributionError::AccessDenied
+        );
+
+        if recipients_timelock >= distribution_info.recipients_timelock {
+            distribution_info.recipients_timelock = recipients_timelock;
+            distribution_info.pending_recipients_timelock = 0;
+            distribution_info.pending_recipients_timelock_at = 0;
+        } else {
+            distribution_info.pending_recipients_timelock = recipients_timelock;
+            distribution_info.pending_recipients_timelock_at = Clock::get()?
+                .unix_timestamp
+                .checked_add(distribution_info.recipients_timelock)
+                .ok_or(DistributionError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    /// Anyone can apply the pending timelock reduction after the current timelock.
+    pub fn execute_recipients_timelock(ctx: Context<ExecuteRecipientsTimelock>) -> Result<()> {
         let distribution_info = &mut ctx.accounts.distribution_info;
+        require_neq!(
+            distribution_info.pending_recipients_timelock,
+            0,
+            DistributionError::NoPendingRecipientsTimelock
+        );
+        require_gte!(
+            Clock::get()?.unix_timestamp,
+            distribution_info.pending_recipients_timelock_at,
+            DistributionError::RecipientsTimelockNotPassed
+        );
+
+        distribution_info.recipients_timelock = distribution_info.pending_recipients_timelock;
+        distribution_info.pending_recipients_timelock = 0;
+        distribution_info.pending_recipients_timelock_at = 0;
+
+        Ok(())
+    }
+
+    /// Update authority can queue the new recipients list.
+    /// Recipient fund accounts are passed in the list order through remaining accounts.
+    pub fn propose_recipients<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProposeRecipients<'info>>,
+        recipients: Vec<Recipient>,
+        dust_recipient: Pubkey,
+    ) -> Result<()> {
+        let distribution_info = &ctx.accounts.distribution_info;
         require_keys_eq!(
             ctx.accounts.authority.key(),
             distribution_info.update_auth,
@@ -101,35 +165,146 @@ pub mod distribution {
         );
 
         require!(
-            play_to_earn_fund_share <= 100,
-            DistributionError::InvalidShare
+            utils::is_valid_recipients(&recipients, dust_recipient),
+            DistributionError::InvalidRecipients
+        );
+        check_recipient_funds(
+            &recipients,
+            ctx.remaining_accounts,
+            distribution_info.ggwp_token,
+        )?;
+
+        let clock = Clock::get()?;
+        let pending_recipients = &mut ctx.accounts.pending_recipients;
+        pending_recipients.distribution_info = distribution_info.key();
+        pending_recipients.proposer = ctx.accounts.authority.key();
+        pending_recipients.recipients = recipients;
+        pending_recipients.dust_recipient = dust_recipient;
+        pending_recipients.proposed_at = clock.unix_timestamp;
+        pending_recipients.executable_at = clock
+            .unix_timestamp
+            .checked_add(distribution_info.recipients_timelock)
+            .ok_or(DistributionError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Admin can cancel the pending recipients change.
+    pub fn cancel_recipients(ctx: Context<CancelRecipients>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.distribution_info.admin,
+            DistributionError::AccessDenied
+        );
+
+        Ok(())
+    }
+
+    /// Anyone can apply the pending recipients change after the timelock.
+    pub fn execute_recipients(ctx: Context<ExecuteRecipients>) -> Result<()> {
+        let pending_recipients = &ctx.accounts.pending_recipients;
+        let clock = Clock::get()?;
+        require_gte!(
+            clock.unix_timestamp,
+            pending_recipients.executable_at,
+            DistributionError::RecipientsTimelockNotPassed
+        );
+
+        let distribution_info = &mut ctx.accounts.distribution_info;
+        distribution_info.recipients = pending_recipients.recipients.clone();
+        distribution_info.dust_recipient = pending_recipients.dust_recipient;
+
+        Ok(())
+    }
+
+    /// Update authority can set the new min interval between distributions in seconds.
+    pub fn update_min_distribution_interval(
+        ctx: Context<UpdateParam>,
+        min_distribution_interval: i64,
+    ) -> Result<()> {
+        require!(
+            min_distribution_interval >= 0,
+            DistributionError::InvalidMinDistributionInterval
+        );
+
+        let distribution_info = &mut ctx.accounts.distribution_info;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            distribution_info.update_auth,
+            DistributionError::AccessDenied
         );
-        require!(staking_fund_share <= 100, DistributionError::InvalidShare);
-        require!(company_fund_share <= 100, DistributionError::InvalidShare);
-        require!(team_fund_share <= 100, DistributionError::InvalidShare);
+
+        distribution_info.min_distribution_interval = min_distribution_interval;
+
+        Ok(())
+    }
+
+    /// Update authority can set the new min accumulative fund amount to distribute.
+    pub fn update_min_distribution_amount(
+        ctx: Context<UpdateParam>,
+        min_distribution_amount: u64,
+    ) -> Result<()> {
+        let distribution_info = &mut ctx.accounts.distribution_info;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            distribution_info.update_auth,
+            DistributionError::AccessDenied
+        );
+
+        distribution_info.min_distribution_amount = min_distribution_amount;
+
+        Ok(())
+    }
+
+    /// Update authority can set the new keeper reward share in basis points.
+    pub fn update_keeper_reward_share(
+        ctx: Context<UpdateParam>,
+        keeper_reward_share: u16,
+    ) -> Result<()> {
         require!(
-            (play_to_earn_fund_share + staking_fund_share + company_fund_share + team_fund_share)
-                == 100,
-            DistributionError::InvalidShare
+            keeper_reward_share <= MAX_KEEPER_REWARD_SHARE,
+            DistributionError::InvalidKeeperRewardShare
         );
 
-        distribution_info.play_to_earn_fund_share = play_to_earn_fund_share;
-        distribution_info.staking_fund_share = staking_fund_share;
-        distribution_info.company_fund_share = company_fund_share;
-        distribution_info.team_fund_share = team_fund_share;
+        let distribution_info = &mut ctx.accounts.distribution_info;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            distribution_info.update_auth,
+            DistributionError::AccessDenied
+        );
+
+        distribution_info.keeper_reward_share = keeper_reward_share;
+
+        Ok(())
+    }
+
+    /// Update authority can set the new burn share in basis points.
+    pub fn update_burn_share(ctx: Context<UpdateParam>, burn_share: u16) -> Result<()> {
+        require!(burn_share <= MAX_BPS, DistributionError::InvalidBurnShare);
+
+        let distribution_info = &mut ctx.accounts.distribution_info;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            distribution_info.update_auth,
+            DistributionError::AccessDenied
+        );
+
+        distribution_info.burn_share = burn_share;
 
         Ok(())
     }
 
     /// Anyone can run the distribution of GGWP tokens.
-    pub fn distribute(ctx: Context<Distribute>) -> Result<()> {
+    /// Caller gets the keeper reward share of the accumulative fund,
+    /// the burn share of the rest is burned.
+    /// Recipient fund accounts are passed in the list order through remaining accounts,
+    /// followed by the vesting escrows of the escrow recipients in the list order.
+    pub fn distribute<'info>(ctx: Context<'_, '_, '_, 'info, Distribute<'info>>) -> Result<()> {
         let distribution_info = &mut ctx.accounts.distribution_info;
         let accumulative_fund = &ctx.accounts.accumulative_fund;
         let accumulative_fund_auth = &ctx.accounts.accumulative_fund_auth;
-        let play_to_earn_fund = &ctx.accounts.play_to_earn_fund;
-        let staking_fund = &ctx.accounts.staking_fund;
-        let company_fund = &ctx.accounts.company_fund;
-        let team_fund = &ctx.accounts.team_fund;
+        let ggwp_token = &ctx.accounts.ggwp_token;
+        let caller_ggwp_wallet = &ctx.accounts.caller_ggwp_wallet;
         let token_program = &ctx.accounts.token_program;
         let clock = Clock::get()?;
 
@@ -138,6 +313,38 @@ pub mod distribution {
             0,
             DistributionError::EmptyAccumulativeFund
         );
+        require_gte!(
+            accumulative_fund.amount,
+            distribution_info.min_distribution_amount,
+            DistributionError::LessThanMinDistributionAmount
+        );
+        require!(
+            utils::is_distribution_interval_passed(
+                clock.unix_timestamp,
+                distribution_info.last_distribution,
+                distribution_info.min_distribution_interval,
+            )?,
+            DistributionError::DistributionIntervalNotPassed
+        );
+        let (funds, vesting_escrows) = ctx.remaining_accounts.split_at(
+            ctx.remaining_accounts
+                .len()
+                .min(distribution_info.recipients.len()),
+        );
+        check_recipient_funds(
+            &distribution_info.recipients,
+            funds,
+            distribution_info.ggwp_token,
+        )?;
+        require_eq!(
+            vesting_escrows.len(),
+            distribution_info
+                .recipients
+                .iter()
+                .filter(|recipient| recipient.is_vesting())
+                .count(),
+            DistributionError::InvalidRecipientAccounts
+        );
 
         let seeds = &[
             ACCUMULATIVE_FUND_AUTH_SEED.as_bytes(),
@@ -149,85 +356,234 @@ pub mod distribution {
         let amount = accumulative_fund.amount;
         msg!("Accumulative fund amount: {}", amount);
 
-        // Transfer GGWP to play to earn fund
-        let play_to_earn_amount =
-            calc_share_amount(distribution_info.play_to_earn_fund_share, amount)?;
-        msg!("Play to earn fund share: {}", play_to_earn_amount);
-        anchor_spl::token::transfer(
-            CpiContext::new_with_signer(
-                token_program.to_account_info(),
-                Transfer {
-                    from: accumulative_fund.to_account_info(),
-                    to: play_to_earn_fund.to_account_info(),
-                    authority: accumulative_fund_auth.to_account_info(),
-                },
-                accumulative_fund_auth_signer,
-            ),
-            play_to_earn_amount,
-        )?;
+        // Transfer GGWP reward to keeper
+        let keeper_reward =
+            utils::calc_share_amount(distribution_info.keeper_reward_share, amount)?;
+        msg!("Keeper reward: {}", keeper_reward);
+        if keeper_reward > 0 {
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: accumulative_fund.to_account_info(),
+                        to: caller_ggwp_wallet.to_account_info(),
+                        authority: accumulative_fund_auth.to_account_info(),
+                    },
+                    accumulative_fund_auth_signer,
+                ),
+                keeper_reward,
+            )?;
+        }
 
-        // Transfer GGWP to staking fund
-        let staking_fund_amount = calc_share_amount(distribution_info.staking_fund_share, amount)?;
-        msg!("Staking fund share: {}", staking_fund_amount);
-        anchor_spl::token::transfer(
-            CpiContext::new_with_signer(
-                token_program.to_account_info(),
-                Transfer {
-                    from: accumulative_fund.to_account_info(),
-                    to: staking_fund.to_account_info(),
-                    authority: accumulative_fund_auth.to_account_info(),
-                },
-                accumulative_fund_auth_signer,
-            ),
-            staking_fund_amount,
+        // Burn GGWP share
+        let funds_amount = amount
+            .checked_sub(keeper_reward)
+            .ok_or(DistributionError::Overflow)?;
+        let burned = utils::calc_share_amount(distribution_info.burn_share, funds_amount)?;
+        msg!("Burned: {}", burned);
+        if burned > 0 {
+            anchor_spl::token::burn(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Burn {
+                        mint: ggwp_token.to_account_info(),
+                        from: accumulative_fund.to_account_info(),
+                        authority: accumulative_fund_auth.to_account_info(),
+                    },
+                    accumulative_fund_auth_signer,
+                ),
+                burned,
+            )?;
+            distribution_info.total_burned = distribution_info
+                .total_burned
+                .checked_add(burned)
+                .ok_or(DistributionError::Overflow)?;
+        }
+
+        let amounts = utils::calc_recipient_amounts(
+            &distribution_info.recipients,
+            distribution_info.dust_recipient,
+            funds_amount
+                .checked_sub(burned)
+                .ok_or(DistributionError::Overflow)?,
         )?;
 
-        // Transfer GGWP to company fund
-        let company_fund_amount = calc_share_amount(distribution_info.company_fund_share, amount)?;
-        msg!("Company fund share: {}", company_fund_amount);
-        anchor_spl::token::transfer(
-            CpiContext::new_with_signer(
-                token_program.to_account_info(),
-                Transfer {
-                    from: accumulative_fund.to_account_info(),
-                    to: company_fund.to_account_info(),
-                    authority: accumulative_fund_auth.to_account_info(),
-                },
-                accumulative_fund_auth_signer,
-            ),
-            company_fund_amount,
+        // Transfer GGWP to recipient funds, escrow shares vest from now
+        let mut vesting_escrows = vesting_escrows.iter();
+        for ((recipient, fund), fund_amount) in distribution_info
+            .recipients
+            .iter()
+            .zip(funds)
+            .zip(amounts.iter().copied())
+        {
+            msg!("Fund {} share: {}", fund.key(), fund_amount);
+            if recipient.is_vesting() {
+                let vesting_escrow = vesting_escrows
+                    .next()
+                    .ok_or(DistributionError::InvalidRecipientAccounts)?;
+                require_keys_eq!(
+                    vesting_escrow.key(),
+                    recipient.vesting_escrow,
+                    DistributionError::InvalidVestingEscrow
+                );
+                let mut vesting_escrow: Account<VestingEscrow> = Account::try_from(vesting_escrow)?;
+                require_keys_eq!(
+                    vesting_escrow.distribution_info,
+                    distribution_info.key(),
+                    DistributionError::InvalidVestingEscrow
+                );
+                require_keys_eq!(
+                    vesting_escrow.escrow_wallet,
+                    recipient.fund,
+                    DistributionError::InvalidVestingEscrow
+                );
+                utils::add_vesting_tranche(&mut vesting_escrow, fund_amount, clock.unix_timestamp)?;
+                vesting_escrow.exit(&crate::ID)?;
+            }
+            if fund_amount == 0 {
+                continue;
+            }
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: accumulative_fund.to_account_info(),
+                        to: fund.clone(),
+                        authority: accumulative_fund_auth.to_account_info(),
+                    },
+                    accumulative_fund_auth_signer,
+                ),
+                fund_amount,
+            )?;
+        }
+
+        distribution_info.last_distribution = clock.unix_timestamp;
+
+        utils::push_distribution_record(
+            &mut ctx.accounts.distribution_history,
+            DistributionRecord {
+                time: clock.unix_timestamp,
+                amount,
+                caller: ctx.accounts.caller.key(),
+                keeper_reward,
+                burned,
+                fund_amounts: distribution_info
+                    .recipients
+                    .iter()
+                    .zip(amounts)
+                    .map(|(recipient, amount)| FundAmount {
+                        fund: recipient.fund,
+                        amount,
+                    })
+                    .collect(),
+            },
         )?;
 
-        // Transfer GGWP to team fund
-        let team_fund_amount = amount
-            .checked_sub(play_to_earn_amount + staking_fund_amount + company_fund_amount)
-            .ok_or(DistributionError::Overflow)?;
-        msg!("Team fund share: {}", team_fund_amount);
+        Ok(())
+    }
+
+    /// Admin can create the vesting escrow of the beneficiary wallet.
+    /// Escrow wallet should be set as the recipient fund to lock the fund share.
+    pub fn create_vesting_escrow(
+        ctx: Context<CreateVestingEscrow>,
+        start_time: i64,
+        cliff_period: i64,
+        vesting_period: i64,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.distribution_info.admin,
+            DistributionError::AccessDenied
+        );
+        require!(
+            cliff_period >= 0 && vesting_period >= cliff_period,
+            DistributionError::InvalidVestingSchedule
+        );
+
+        let vesting_escrow = &mut ctx.accounts.vesting_escrow;
+        vesting_escrow.distribution_info = ctx.accounts.distribution_info.key();
+        vesting_escrow.beneficiary_wallet = ctx.accounts.beneficiary_wallet.key();
+        vesting_escrow.escrow_wallet = ctx.accounts.escrow_wallet.key();
+        vesting_escrow.escrow_auth_bump = ctx.bumps["escrow_auth"];
+
+        vesting_escrow.start_time = start_time;
+        vesting_escrow.cliff_period = cliff_period;
+        vesting_escrow.vesting_period = vesting_period;
+        vesting_escrow.total_claimed = 0;
+        vesting_escrow.released_amount = 0;
+        vesting_escrow.tranches = Vec::new();
+
+        Ok(())
+    }
+
+    /// Owner of the beneficiary wallet can claim GGWP vested in the escrow.
+    /// Every distributed share vests by the escrow schedule from its own arrival.
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let vesting_escrow = &mut ctx.accounts.vesting_escrow;
+        let escrow_auth = &ctx.accounts.escrow_auth;
+        let escrow_wallet = &ctx.accounts.escrow_wallet;
+        let beneficiary_wallet = &ctx.accounts.beneficiary_wallet;
+        let token_program = &ctx.accounts.token_program;
+        let clock = Clock::get()?;
+
+        let vested_amount = utils::calc_escrow_vested_amount(vesting_escrow, clock.unix_timestamp)?;
+        let amount = vested_amount.saturating_sub(vesting_escrow.total_claimed);
+        require_neq!(amount, 0, DistributionError::NothingToClaim);
+        msg!("Vested amount: {}", amount);
+
+        let vesting_escrow_pk = vesting_escrow.key();
+        let seeds = &[
+            VESTING_ESCROW_AUTH_SEED.as_bytes(),
+            vesting_escrow_pk.as_ref(),
+            &[vesting_escrow.escrow_auth_bump],
+        ];
+        let escrow_auth_signer = &[&seeds[..]];
         anchor_spl::token::transfer(
             CpiContext::new_with_signer(
                 token_program.to_account_info(),
                 Transfer {
-                    from: accumulative_fund.to_account_info(),
-                    to: team_fund.to_account_info(),
-                    authority: accumulative_fund_auth.to_account_info(),
+                    from: escrow_wallet.to_account_info(),
+                    to: beneficiary_wallet.to_account_info(),
+                    authority: escrow_auth.to_account_info(),
                 },
-                accumulative_fund_auth_signer,
+                escrow_auth_signer,
             ),
-            team_fund_amount,
+            amount,
         )?;
 
-        distribution_info.last_distribution = clock.unix_timestamp;
+        vesting_escrow.total_claimed = vesting_escrow
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(DistributionError::Overflow)?;
 
         Ok(())
     }
 }
 
-/// Get the percent value.
-pub fn calc_share_amount(share: u8, amount: u64) -> Result<u64> {
-    let ui_amount = anchor_spl::token::spl_token::amount_to_ui_amount(amount, 9);
-    let share_amount = ui_amount / 100.0 * share as f64;
-    Ok(anchor_spl::token::spl_token::ui_amount_to_amount(
-        share_amount,
-        9,
-    ))
+/// Checks the recipient fund accounts match the recipients list.
+fn check_recipient_funds<'info>(
+    recipients: &[Recipient],
+    funds: &[AccountInfo<'info>],
+    ggwp_token: Pubkey,
+) -> Result<()> {
+    require_eq!(
+        funds.len(),
+        recipients.len(),
+        DistributionError::InvalidRecipientAccounts
+    );
+    for (recipient, fund) in recipients.iter().zip(funds) {
+        require_keys_eq!(
+            fund.key(),
+            recipient.fund,
+            DistributionError::InvalidFundPublicKey
+        );
+        let fund: Account<TokenAccount> = Account::try_from(fund)?;
+        require_keys_eq!(
+            fund.mint,
+            ggwp_token,
+            DistributionError::InvalidRecipientFundMint
+        );
+    }
+
+    Ok(())
 }
@@ -3,6 +3,19 @@ use anchor_lang::prelude::*;
 pub const DESCRIMINATOR_LEN: usize = 8;
 
 pub const ACCUMULATIVE_FUND_AUTH_SEED: &str = "accumulative_fund_auth";
+pub const DISTRIBUTION_HISTORY_SEED: &str = "distribution_history";
+pub const VESTING_ESCROW_SEED: &str = "vesting_escrow";
+pub const VESTING_ESCROW_AUTH_SEED: &str = "vesting_escrow_auth";
+pub const PENDING_RECIPIENTS_SEED: &str = "pending_recipients";
+
+pub const MAX_BPS: u16 = 10_000;
+pub const MAX_RECIPIENTS_LEN: usize = 10;
+pub const DISTRIBUTION_HISTORY_MAX_LEN: usize = 20;
+pub const MAX_KEEPER_REWARD_SHARE: u16 = 500;
+pub const MIN_RECIPIENTS_TIMELOCK: i64 = 1;
+// Escrow inflows are grouped into tranches starting at the vesting period fractions
+pub const VESTING_TRANCHE_BUCKETS: i64 = 32;
+pub const MAX_VESTING_TRANCHES: usize = VESTING_TRANCHE_BUCKETS as usize + 1;
 
 #[account]
 #[derive(Default, Debug)]
@@ -15,14 +28,21 @@ pub struct DistributionInfo {
     pub accumulative_fund_auth_bump: u8,
 
     pub last_distribution: i64,
-    pub play_to_earn_fund: Pubkey,
-    pub play_to_earn_fund_share: u8,
-    pub staking_fund: Pubkey,
-    pub staking_fund_share: u8,
-    pub company_fund: Pubkey,
-    pub company_fund_share: u8,
-    pub team_fund: Pubkey,
-    pub team_fund_share: u8,
+    pub recipients: Vec<Recipient>,
+    pub dust_recipient: Pubkey,
+
+    pub min_distribution_interval: i64,
+    pub min_distribution_amount: u64,
+    // Share of the distributed amount paid to the caller in basis points
+    pub keeper_reward_share: u16,
+    // Share of the distributed amount burned in basis points
+    pub burn_share: u16,
+    pub total_burned: u64,
+    // Min delay between proposing and executing the recipients change
+    pub recipients_timelock: i64,
+    // Timelock reduction queued behind the current timelock, applied at the time when nonzero
+    pub pending_recipients_timelock: i64,
+    pub pending_recipients_timelock_at: i64,
 }
 
 impl DistributionInfo {
@@ -31,8 +51,139 @@ impl DistributionInfo {
         32 + // ggwp token mint
         32 + 1 + // accumulative fund + auth bump
         32 + // last distribution timestamp
-        32 + 1 + // play to earn fund + share
-        32 + 1 + // staking fund + share
-        32 + 1 + // company fund + share
-        32 + 1; // team fund + share
+        4 + Recipient::LEN * MAX_RECIPIENTS_LEN + // recipients
+        32 + // dust recipient fund
+        8 + // min distribution interval in seconds
+        8 + // min accumulative fund amount to distribute
+        2 + // keeper reward share
+        2 + // burn share
+        8 + // total burned amount
+        8 + // recipients timelock in seconds
+        8 + 8; // pending timelock reduction + execution timestamp
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct Recipient {
+    pub fund: Pubkey,
+    // Share of the accumulative fund in basis points
+    pub share: u16,
+    // Vesting escrow of the fund, the default pk if the fund is not an escrow wallet
+    pub vesting_escrow: Pubkey,
+}
+
+impl Recipient {
+    pub const LEN: usize = 32 + 2 + 32;
+
+    pub fn is_vesting(&self) -> bool {
+        self.vesting_escrow != Pubkey::default()
+    }
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct DistributionHistory {
+    pub total_distributions: u64,
+    pub records: Vec<DistributionRecord>,
+}
+
+impl DistributionHistory {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        8 + // total distributions
+        4 + DistributionRecord::LEN * DISTRIBUTION_HISTORY_MAX_LEN // ring buffer of the last distributions
+        ;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct DistributionRecord {
+    pub time: i64,
+    pub amount: u64,
+    pub caller: Pubkey,
+    pub keeper_reward: u64,
+    pub burned: u64,
+    pub fund_amounts: Vec<FundAmount>,
+}
+
+impl DistributionRecord {
+    pub const LEN: usize = 8 + // distribution timestamp
+        8 + // distributed amount
+        32 + // caller pk
+        8 + // keeper reward amount
+        8 + // burned amount
+        4 + FundAmount::LEN * MAX_RECIPIENTS_LEN; // amounts received by funds
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct FundAmount {
+    pub fund: Pubkey,
+    pub amount: u64,
+}
+
+impl FundAmount {
+    pub const LEN: usize = 32 + 8;
+}
+
+/// Escrow of the fund share released linearly after the cliff.
+/// Escrow wallet is used as the recipient fund.
+/// Every distributed amount vests from its own arrival in a tranche.
+#[account]
+#[derive(Default, Debug)]
+pub struct VestingEscrow {
+    pub distribution_info: Pubkey,
+    pub beneficiary_wallet: Pubkey,
+    pub escrow_wallet: Pubkey,
+    pub escrow_auth_bump: u8,
+
+    pub start_time: i64,
+    pub cliff_period: i64,
+    pub vesting_period: i64,
+    pub total_claimed: u64,
+    // Amount of the fully vested tranches removed from the list
+    pub released_amount: u64,
+    pub tranches: Vec<VestingTranche>,
+}
+
+impl VestingEscrow {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        32 + // distribution info pk
+        32 + // beneficiary wallet pk
+        32 + 1 + // escrow wallet + auth bump
+        8 + // vesting start timestamp
+        8 + // cliff period in seconds
+        8 + // vesting period in seconds
+        8 + // total claimed amount
+        8 + // released amount
+        4 + VestingTranche::LEN * MAX_VESTING_TRANCHES; // vesting tranches
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct VestingTranche {
+    pub amount: u64,
+    pub start_time: i64,
+}
+
+impl VestingTranche {
+    pub const LEN: usize = 8 + 8;
+}
+
+/// Recipients change queued by the update authority.
+/// It can be executed after the timelock or cancelled by the admin.
+#[account]
+#[derive(Default, Debug)]
+pub struct PendingRecipients {
+    pub distribution_info: Pubkey,
+    pub proposer: Pubkey,
+    pub recipients: Vec<Recipient>,
+    pub dust_recipient: Pubkey,
+    pub proposed_at: i64,
+    pub executable_at: i64,
+}
+
+impl PendingRecipients {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        32 + // distribution info pk
+        32 + // proposer pk
+        4 + Recipient::LEN * MAX_RECIPIENTS_LEN + // recipients
+        32 + // dust recipient fund
+        8 + // proposal timestamp
+        8; // earliest execution timestamp
 }
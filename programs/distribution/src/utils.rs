@@ -0,0 +1,569 @@
+use crate::error::DistributionError;
+use crate::state::{
+    DistributionHistory, DistributionRecord, Recipient, VestingEscrow, VestingTranche,
+    DISTRIBUTION_HISTORY_MAX_LEN, MAX_BPS, MAX_RECIPIENTS_LEN, MAX_VESTING_TRANCHES,
+    VESTING_TRANCHE_BUCKETS,
+};
+use anchor_lang::{prelude::*, solana_program::clock::UnixTimestamp};
+
+/// Checks recipients list. Shares must sum up to 100%,
+/// funds must be unique and the dust recipient must be in the list.
+pub fn is_valid_recipients(recipients: &[Recipient], dust_recipient: Pubkey) -> bool {
+    if recipients.is_empty() || recipients.len() > MAX_RECIPIENTS_LEN {
+        return false;
+    }
+    if recipients.iter().any(|recipient| recipient.share == 0) {
+        return false;
+    }
+    let total_share: u32 = recipients
+        .iter()
+        .map(|recipient| recipient.share as u32)
+        .sum();
+    if total_share != MAX_BPS as u32 {
+        return false;
+    }
+    for (i, recipient) in recipients.iter().enumerate() {
+        if recipients[i + 1..]
+            .iter()
+            .any(|other| other.fund == recipient.fund)
+        {
+            return false;
+        }
+    }
+
+    recipients
+        .iter()
+        .any(|recipient| recipient.fund == dust_recipient)
+}
+
+/// Checks the min interval since the last distribution is passed.
+pub fn is_distribution_interval_passed(
+    current_time: UnixTimestamp,
+    last_distribution: UnixTimestamp,
+    min_distribution_interval: i64,
+) -> Result<bool> {
+    let next_distribution = last_distribution
+        .checked_add(min_distribution_interval)
+        .ok_or(DistributionError::Overflow)?;
+    Ok(current_time >= next_distribution)
+}
+
+/// Get the amount vested linearly from the start time.
+/// Nothing is vested before the end of the cliff.
+pub fn calc_vested_amount(
+    total_amount: u64,
+    start_time: UnixTimestamp,
+    cliff_period: i64,
+    vesting_period: i64,
+    current_time: UnixTimestamp,
+) -> Result<u64> {
+    let spent_time = current_time.saturating_sub(start_time);
+    if spent_time < cliff_period {
+        return Ok(0);
+    }
+    if spent_time >= vesting_period {
+        return Ok(total_amount);
+    }
+
+    let vested_amount = (total_amount as u128)
+        .checked_mul(spent_time as u128)
+        .ok_or(DistributionError::Overflow)?
+        / vesting_period as u128;
+    u64::try_from(vested_amount).map_err(|_| DistributionError::Overflow.into())
+}
+
+/// Get the amount vested in the escrow.
+/// Every tranche vests by the escrow schedule from its own start time.
+pub fn calc_escrow_vested_amount(
+    escrow: &VestingEscrow,
+    current_time: UnixTimestamp,
+) -> Result<u64> {
+    let mut vested_amount = escrow.released_amount;
+    for tranche in escrow.tranches.iter() {
+        let tranche_vested_amount = calc_vested_amount(
+            tranche.amount,
+            tranche.start_time,
+            escrow.cliff_period,
+            escrow.vesting_period,
+            current_time,
+        )?;
+        vested_amount = vested_amount
+            .checked_add(tranche_vested_amount)
+            .ok_or(DistributionError::Overflow)?;
+    }
+    Ok(vested_amount)
+}
+
+/// Add the amount received by the escrow as the vesting tranche.
+/// The tranche starts at the next vesting period fraction from the escrow start time,
+/// so the amount never vests before its arrival and the earlier tranches are not changed.
+/// Fully vested tranches are released to keep the list bounded.
+pub fn add_vesting_tranche(
+    escrow: &mut VestingEscrow,
+    amount: u64,
+    current_time: UnixTimestamp,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let bucket =
+        ((escrow.vesting_period + VESTING_TRANCHE_BUCKETS - 1) / VESTING_TRANCHE_BUCKETS).max(1);
+    // Inflows before the escrow start vest from the start
+    let elapsed_buckets = current_time
+        .saturating_sub(escrow.start_time)
+        .max(0)
+        .checked_add(bucket - 1)
+        .ok_or(DistributionError::Overflow)?
+        / bucket;
+    let start_time = elapsed_buckets
+        .checked_mul(bucket)
+        .and_then(|elapsed| escrow.start_time.checked_add(elapsed))
+        .ok_or(DistributionError::Overflow)?;
+
+    let vesting_period = escrow.vesting_period;
+    let is_released = |tranche: &VestingTranche| {
+        current_time.saturating_sub(tranche.start_time) >= vesting_period
+    };
+    for tranche in escrow
+        .tranches
+        .iter()
+        .filter(|tranche| is_released(tranche))
+    {
+        escrow.released_amount = escrow
+            .released_amount
+            .checked_add(tranche.amount)
+            .ok_or(DistributionError::Overflow)?;
+    }
+    escrow.tranches.retain(|tranche| !is_released(tranche));
+
+    match escrow.tranches.last_mut() {
+        Some(last) if last.start_time == start_time => {
+            last.amount = last
+                .amount
+                .checked_add(amount)
+                .ok_or(DistributionError::Overflow)?;
+        }
+        _ => {
+            require_gt!(
+                MAX_VESTING_TRANCHES,
+                escrow.tranches.len(),
+                DistributionError::TooManyVestingTranches
+            );
+            escrow.tranches.push(VestingTranche { amount, start_time });
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the basis points value of the amount in base units rounded down.
+/// Share of base units is the same for any mint decimals, so the mint isn't read.
+pub fn calc_share_amount(share: u16, amount: u64) -> Result<u64> {
+    let share_amount = (amount as u128)
+        .checked_mul(share as u128)
+        .ok_or(DistributionError::Overflow)?
+        / MAX_BPS as u128;
+    u64::try_from(share_amount).map_err(|_| DistributionError::Overflow.into())
+}
+
+/// Get the amounts of recipients in the list order.
+/// Rounding dust goes to the dust recipient, so amounts sum up to the total.
+pub fn calc_recipient_amounts(
+    recipients: &[Recipient],
+    dust_recipient: Pubkey,
+    amount: u64,
+) -> Result<Vec<u64>> {
+    let mut amounts = recipients
+        .iter()
+        .map(|recipient| calc_share_amount(recipient.share, amount))
+        .collect::<Result<Vec<u64>>>()?;
+
+    let distributed = amounts.iter().try_fold(0u64, |sum, amount| {
+        sum.checked_add(*amount).ok_or(DistributionError::Overflow)
+    })?;
+    let dust = amount
+        .checked_sub(distributed)
+        .ok_or(DistributionError::Overflow)?;
+    let dust_index = recipients
+        .iter()
+        .position(|recipient| recipient.fund == dust_recipient)
+        .ok_or(DistributionError::InvalidRecipients)?;
+    amounts[dust_index] = amounts[dust_index]
+        .checked_add(dust)
+        .ok_or(DistributionError::Overflow)?;
+
+    Ok(amounts)
+}
+
+/// Record the distribution into the history.
+/// The oldest record is overwritten when the history is full.
+pub fn push_distribution_record(
+    history: &mut DistributionHistory,
+    record: DistributionRecord,
+) -> Result<()> {
+    let index = (history.total_distributions % DISTRIBUTION_HISTORY_MAX_LEN as u64) as usize;
+    if history.records.len() < DISTRIBUTION_HISTORY_MAX_LEN {
+        history.records.push(record);
+    } else {
+        history.records[index] = record;
+    }
+    history.total_distributions = history
+        .total_distributions
+        .checked_add(1)
+        .ok_or(DistributionError::Overflow)?;
+
+    Ok(())
+}
+
+/// Get the history records from the oldest to the newest.
+pub fn get_ordered_records(history: &DistributionHistory) -> Vec<&DistributionRecord> {
+    let start = if history.records.len() < DISTRIBUTION_HISTORY_MAX_LEN {
+        0
+    } else {
+        (history.total_distributions % DISTRIBUTION_HISTORY_MAX_LEN as u64) as usize
+    };
+    history.records[start..]
+        .iter()
+        .chain(history.records[..start].iter())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipients(shares: &[u16]) -> Vec<Recipient> {
+        shares
+            .iter()
+            .map(|share| Recipient {
+                fund: Pubkey::new_unique(),
+                share: *share,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn test_is_valid_recipients() {
+        let list = recipients(&[4500, 4000, 500, 1000]);
+        assert!(is_valid_recipients(&list, list[3].fund));
+        assert!(is_valid_recipients(&list, list[0].fund));
+        // Dust recipient is not in the list
+        assert!(!is_valid_recipients(&list, Pubkey::new_unique()));
+
+        let list = recipients(&[10000]);
+        assert!(is_valid_recipients(&list, list[0].fund));
+
+        assert!(!is_valid_recipients(&[], Pubkey::new_unique()));
+
+        let list = recipients(&[4500, 4000, 500, 999]);
+        assert!(!is_valid_recipients(&list, list[0].fund));
+        let list = recipients(&[4500, 4000, 500, 1001]);
+        assert!(!is_valid_recipients(&list, list[0].fund));
+        let list = recipients(&[10000, 0]);
+        assert!(!is_valid_recipients(&list, list[0].fund));
+        let list = recipients(&[u16::MAX, 10000, 1]);
+        assert!(!is_valid_recipients(&list, list[0].fund));
+
+        let list = recipients(&[1000; MAX_RECIPIENTS_LEN]);
+        assert!(is_valid_recipients(&list, list[0].fund));
+        let list = recipients(&[
+            1000, 1000, 1000, 1000, 1000, 1000, 1000, 1000, 1000, 500, 500,
+        ]);
+        assert!(!is_valid_recipients(&list, list[0].fund));
+
+        // Duplicated fund
+        let mut list = recipients(&[5000, 5000]);
+        list[1].fund = list[0].fund;
+        assert!(!is_valid_recipients(&list, list[0].fund));
+    }
+
+    #[test]
+    pub fn test_is_distribution_interval_passed() {
+        let time = 1660032700;
+        assert_eq!(is_distribution_interval_passed(time, 0, 0), Ok(true));
+        assert_eq!(is_distribution_interval_passed(time, 0, 3600), Ok(true));
+        assert_eq!(is_distribution_interval_passed(time, time, 0), Ok(true));
+        assert_eq!(
+            is_distribution_interval_passed(time + 3599, time, 3600),
+            Ok(false)
+        );
+        assert_eq!(
+            is_distribution_interval_passed(time + 3600, time, 3600),
+            Ok(true)
+        );
+        assert!(is_distribution_interval_passed(time, i64::MAX, 1).is_err());
+    }
+
+    #[test]
+    pub fn test_calc_vested_amount() {
+        let time = 1660032700;
+        let day = 24 * 60 * 60;
+        let amount = 1000_000_000_000;
+
+        // Before start and cliff
+        assert_eq!(
+            calc_vested_amount(amount, time, 30 * day, 360 * day, time - day),
+            Ok(0)
+        );
+        assert_eq!(
+            calc_vested_amount(amount, time, 30 * day, 360 * day, time + 30 * day - 1),
+            Ok(0)
+        );
+        // Cliff unlocks the amount vested from the start
+        assert_eq!(
+            calc_vested_amount(amount, time, 30 * day, 360 * day, time + 30 * day),
+            Ok(83_333_333_333)
+        );
+        assert_eq!(
+            calc_vested_amount(amount, time, 30 * day, 360 * day, time + 180 * day),
+            Ok(500_000_000_000)
+        );
+        assert_eq!(
+            calc_vested_amount(amount, time, 30 * day, 360 * day, time + 360 * day),
+            Ok(amount)
+        );
+        assert_eq!(
+            calc_vested_amount(amount, time, 30 * day, 360 * day, time + 1000 * day),
+            Ok(amount)
+        );
+        // No vesting period
+        assert_eq!(calc_vested_amount(amount, time, 0, 0, time), Ok(amount));
+        assert_eq!(
+            calc_vested_amount(u64::MAX, time, 0, 360 * day, time + 359 * day),
+            Ok((u64::MAX as u128 * 359 / 360) as u64)
+        );
+    }
+
+    #[test]
+    pub fn test_add_vesting_tranche() {
+        let time = 1660032700;
+        let day = 24 * 60 * 60;
+        let bucket = 100 * day / VESTING_TRANCHE_BUCKETS;
+        let mut escrow = VestingEscrow {
+            start_time: time,
+            cliff_period: 10 * day,
+            vesting_period: 100 * day,
+            ..Default::default()
+        };
+
+        add_vesting_tranche(&mut escrow, 1000, time).unwrap();
+        add_vesting_tranche(&mut escrow, 0, time + day).unwrap();
+        assert_eq!(escrow.tranches.len(), 1);
+        assert_eq!(calc_escrow_vested_amount(&escrow, time + 10 * day), Ok(100));
+
+        // Half a period later inflow vests from its own arrival
+        add_vesting_tranche(&mut escrow, 1000, time + 50 * day).unwrap();
+        assert_eq!(calc_escrow_vested_amount(&escrow, time + 50 * day), Ok(500));
+        assert_eq!(
+            calc_escrow_vested_amount(&escrow, time + 60 * day - 1),
+            Ok(599)
+        );
+        assert_eq!(calc_escrow_vested_amount(&escrow, time + 60 * day), Ok(700));
+        assert_eq!(
+            calc_escrow_vested_amount(&escrow, time + 100 * day),
+            Ok(1500)
+        );
+        assert_eq!(
+            calc_escrow_vested_amount(&escrow, time + 150 * day),
+            Ok(2000)
+        );
+
+        // Inflows are grouped by the period fractions and never start before the arrival
+        add_vesting_tranche(&mut escrow, 1000, time + 50 * day + 1).unwrap();
+        add_vesting_tranche(&mut escrow, 1000, time + 50 * day + bucket).unwrap();
+        assert_eq!(escrow.tranches.len(), 3);
+        assert_eq!(
+            escrow.tranches[2],
+            VestingTranche {
+                amount: 2000,
+                start_time: time + 50 * day + bucket,
+            }
+        );
+
+        // Inflow after the schedule end is locked again, vested tranches are released
+        add_vesting_tranche(&mut escrow, 1000, time + 1000 * day).unwrap();
+        assert_eq!(escrow.released_amount, 4000);
+        assert_eq!(escrow.tranches.len(), 1);
+        assert_eq!(
+            calc_escrow_vested_amount(&escrow, time + 1000 * day),
+            Ok(4000)
+        );
+        assert_eq!(
+            calc_escrow_vested_amount(&escrow, time + 1100 * day),
+            Ok(5000)
+        );
+
+        // Frequent inflows fit the tranches list
+        for i in 0..10 * VESTING_TRANCHE_BUCKETS {
+            add_vesting_tranche(&mut escrow, 1, time + 2000 * day + i * bucket / 3).unwrap();
+            assert!(escrow.tranches.len() <= MAX_VESTING_TRANCHES);
+        }
+
+        // Inflows before the escrow start vest from the start
+        let mut escrow = VestingEscrow {
+            start_time: time,
+            vesting_period: 100 * day,
+            ..Default::default()
+        };
+        add_vesting_tranche(&mut escrow, 1000, time - day).unwrap();
+        add_vesting_tranche(&mut escrow, 1000, time).unwrap();
+        assert_eq!(escrow.tranches.len(), 1);
+        assert_eq!(
+            calc_escrow_vested_amount(&escrow, time + 50 * day),
+            Ok(1000)
+        );
+        // More than one bucket early
+        add_vesting_tranche(&mut escrow, 1000, time - 10 * day).unwrap();
+        assert_eq!(
+            escrow.tranches,
+            vec![VestingTranche {
+                amount: 3000,
+                start_time: time,
+            }]
+        );
+        assert_eq!(calc_escrow_vested_amount(&escrow, time), Ok(0));
+        assert_eq!(
+            calc_escrow_vested_amount(&escrow, time + 50 * day),
+            Ok(1500)
+        );
+    }
+
+    #[test]
+    pub fn test_calc_share_amount() {
+        assert_eq!(calc_share_amount(4500, 100_000_000_000), Ok(45_000_000_000));
+        assert_eq!(calc_share_amount(1, 100_000_000_000), Ok(10_000_000));
+        assert_eq!(calc_share_amount(3333, 1_000), Ok(333));
+        assert_eq!(calc_share_amount(10000, u64::MAX), Ok(u64::MAX));
+        assert_eq!(calc_share_amount(5000, u64::MAX), Ok(u64::MAX / 2));
+        assert_eq!(calc_share_amount(5000, 0), Ok(0));
+        assert!(calc_share_amount(10001, u64::MAX).is_err());
+        // 1.5 tokens of 6 and 0 decimals mints
+        assert_eq!(calc_share_amount(4500, 1_500_000), Ok(675_000));
+        assert_eq!(calc_share_amount(4500, 1), Ok(0));
+        assert_eq!(calc_share_amount(4500, 3), Ok(1));
+    }
+
+    #[test]
+    pub fn test_calc_recipient_amounts() {
+        let list = recipients(&[4500, 4000, 500, 1000]);
+        assert_eq!(
+            calc_recipient_amounts(&list, list[3].fund, 10000_000_000_000),
+            Ok(vec![
+                4500_000_000_000,
+                4000_000_000_000,
+                500_000_000_000,
+                1000_000_000_000
+            ])
+        );
+
+        let list = recipients(&[3333, 3333, 3334]);
+        assert_eq!(
+            calc_recipient_amounts(&list, list[0].fund, 1),
+            Ok(vec![1, 0, 0])
+        );
+        assert_eq!(
+            calc_recipient_amounts(&list, list[1].fund, 1_000),
+            Ok(vec![333, 334, 333])
+        );
+        assert_eq!(
+            calc_recipient_amounts(&list, list[2].fund, u64::MAX)
+                .unwrap()
+                .iter()
+                .map(|amount| *amount as u128)
+                .sum::<u128>(),
+            u64::MAX as u128
+        );
+
+        assert!(calc_recipient_amounts(&list, Pubkey::new_unique(), 1_000).is_err());
+    }
+
+    #[test]
+    pub fn test_calc_recipient_amounts_sum() {
+        let shares_list: [&[u16]; 6] = [
+            &[10000],
+            &[4500, 4000, 500, 1000],
+            &[3333, 3333, 3334],
+            &[1, 9999],
+            &[1, 1, 1, 1, 1, 1, 1, 1, 1, 9991],
+            &[1000; MAX_RECIPIENTS_LEN],
+        ];
+        let amounts = [
+            0,
+            1,
+            9,
+            10_001,
+            999_999_999,
+            1_000_000_007,
+            9_007_199_254_740_993,
+            u64::MAX / 3,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        for shares in shares_list {
+            let list = recipients(shares);
+            for dust_recipient in list.iter().map(|recipient| recipient.fund) {
+                for amount in amounts {
+                    let result = calc_recipient_amounts(&list, dust_recipient, amount).unwrap();
+                    assert_eq!(
+                        result.iter().map(|amount| *amount as u128).sum::<u128>(),
+                        amount as u128
+                    );
+                    for (recipient, recipient_amount) in list.iter().zip(result) {
+                        let exact = amount as u128 * recipient.share as u128 / MAX_BPS as u128;
+                        if recipient.fund == dust_recipient {
+                            // Dust is less than one base unit per recipient
+                            assert!(recipient_amount as u128 - exact < list.len() as u128);
+                        } else {
+                            assert_eq!(recipient_amount as u128, exact);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_push_distribution_record() {
+        let record = |time: i64| DistributionRecord {
+            time,
+            amount: time as u64 * 100,
+            ..Default::default()
+        };
+        let times = |history: &DistributionHistory| {
+            get_ordered_records(history)
+                .iter()
+                .map(|record| record.time)
+                .collect::<Vec<i64>>()
+        };
+
+        let mut history = DistributionHistory::default();
+        assert_eq!(times(&history), vec![]);
+        push_distribution_record(&mut history, record(1)).unwrap();
+        push_distribution_record(&mut history, record(2)).unwrap();
+        assert_eq!(history.total_distributions, 2);
+        assert_eq!(times(&history), vec![1, 2]);
+
+        for time in 3..=DISTRIBUTION_HISTORY_MAX_LEN as i64 {
+            push_distribution_record(&mut history, record(time)).unwrap();
+        }
+        assert_eq!(
+            times(&history),
+            (1..=DISTRIBUTION_HISTORY_MAX_LEN as i64).collect::<Vec<i64>>()
+        );
+
+        // The oldest records are overwritten
+        push_distribution_record(&mut history, record(100)).unwrap();
+        push_distribution_record(&mut history, record(101)).unwrap();
+        assert_eq!(history.records.len(), DISTRIBUTION_HISTORY_MAX_LEN);
+        assert_eq!(
+            history.total_distributions,
+            DISTRIBUTION_HISTORY_MAX_LEN as u64 + 2
+        );
+        let mut expected = (3..=DISTRIBUTION_HISTORY_MAX_LEN as i64).collect::<Vec<i64>>();
+        expected.extend([100, 101]);
+        assert_eq!(times(&history), expected);
+        assert_eq!(get_ordered_records(&history).last().unwrap().amount, 10100);
+    }
+}
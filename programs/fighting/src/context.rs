@@ -144,6 +144,17 @@ pub struct FinalizeGame<'info> {
         bump = fighting_settings.reward_transfer_auth_bump,
     )]
     pub reward_transfer_auth: UncheckedAccount<'info>,
+    /// CHECK: Reward transfer allowance checked by reward distribution
+    #[account(mut)]
+    pub reward_transfer_allowance: UncheckedAccount<'info>,
+    /// CHECK: User vesting escrow checked by reward distribution
+    #[account(mut)]
+    pub user_vesting_escrow: UncheckedAccount<'info>,
+    /// CHECK: User vesting escrow wallet checked by reward distribution
+    #[account(mut)]
+    pub user_vesting_escrow_wallet: UncheckedAccount<'info>,
+    /// CHECK: GGWP token checked by reward distribution
+    pub ggwp_token: UncheckedAccount<'info>,
 
     #[account(mut,
         constraint = play_to_earn_fund.key() == reward_distribution_info.play_to_earn_fund
@@ -162,4 +173,5 @@ pub struct FinalizeGame<'info> {
     pub reward_distribution_program: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
 }
@@ -1,7 +1,8 @@
 use crate::context::*;
 use crate::error::FightingError;
 use crate::state::{
-    GameResult, IdentityAction, ACTIONS_VEC_MAX, GPASS_BURN_AUTH_SEED, REWARD_TRANSFER_AUTH_SEED,
+    GameResult, IdentityAction, ACTIONS_VEC_MAX, GPASS_BURN_AUTH_SEED, MAX_ROYALTY,
+    REWARD_TRANSFER_AUTH_SEED,
 };
 use anchor_lang::prelude::*;
 
@@ -27,6 +28,7 @@ pub mod fighting {
         royalty: u8,
     ) -> Result<()> {
         require!(afk_timeout > 0, FightingError::InvalidAFKTimeout);
+        require!(royalty <= MAX_ROYALTY, FightingError::InvalidRoyaltyValue);
 
         let fighting_settings = &mut ctx.accounts.fighting_settings;
         fighting_settings.admin = ctx.accounts.admin.key();
@@ -143,7 +145,10 @@ pub mod fighting {
             FightingError::AccessDenied
         );
 
-        require!(royalty > 0, FightingError::InvalidRoyaltyValue);
+        require!(
+            royalty > 0 && royalty <= MAX_ROYALTY,
+            FightingError::InvalidRoyaltyValue
+        );
 
         fighting_settings.royalty = royalty;
 
@@ -221,6 +226,7 @@ pub mod fighting {
         let accumulative_fund = &ctx.accounts.accumulative_fund;
         let freezing_info = &ctx.accounts.freezing_info;
         let reward_transfer_auth = &ctx.accounts.reward_transfer_auth;
+        let reward_transfer_allowance = &ctx.accounts.reward_transfer_allowance;
         let reward_distribution_program = &ctx.accounts.reward_distribution_program;
         let token_program = &ctx.accounts.token_program;
 
@@ -251,6 +257,9 @@ pub mod fighting {
                 msg!("Royalty amount: {}", royalty_amount);
 
                 // Transfer reward_amount - royalty_amount to user
+                let user_reward_amount = reward_amount
+                    .checked_sub(royalty_amount)
+                    .ok_or(FightingError::Overflow)?;
                 let seeds = &[
                     REWARD_TRANSFER_AUTH_SEED.as_bytes(),
                     fighting_settings.to_account_info().key.as_ref(),
@@ -258,21 +267,53 @@ pub mod fighting {
                     &[fighting_settings.reward_transfer_auth_bump],
                 ];
                 let signer = &[&seeds[..]];
-                reward_distribution::cpi::transfer(
-                    CpiContext::new_with_signer(
-                        reward_distribution_program.to_account_info(),
-                        reward_distribution::cpi::accounts::Transfer {
-                            reward_distribution_info: reward_distribution_info.to_account_info(),
-                            authority: reward_transfer_auth.to_account_info(),
-                            to: user_ggwp_wallet.to_account_info(),
-                            play_to_earn_fund: play_to_earn_fund.to_account_info(),
-                            play_to_earn_fund_auth: play_to_earn_fund_auth.to_account_info(),
-                            token_program: token_program.to_account_info(),
-                        },
-                        signer,
-                    ),
-                    reward_amount - royalty_amount,
-                )?;
+                if reward_distribution_info.vesting_duration > 0 {
+                    // User reward vests in the escrow
+                    reward_distribution::cpi::transfer_vested(
+                        CpiContext::new_with_signer(
+                            reward_distribution_program.to_account_info(),
+                            reward_distribution::cpi::accounts::TransferVested {
+                                payer: ctx.accounts.validator.to_account_info(),
+                                authority: reward_transfer_auth.to_account_info(),
+                                reward_distribution_info: reward_distribution_info
+                                    .to_account_info(),
+                                transfer_allowance: reward_transfer_allowance.to_account_info(),
+                                user: ctx.accounts.user.to_account_info(),
+                                vesting_escrow: ctx.accounts.user_vesting_escrow.to_account_info(),
+                                vesting_escrow_wallet: ctx
+                                    .accounts
+                                    .user_vesting_escrow_wallet
+                                    .to_account_info(),
+                                ggwp_token: ctx.accounts.ggwp_token.to_account_info(),
+                                play_to_earn_fund: play_to_earn_fund.to_account_info(),
+                                play_to_earn_fund_auth: play_to_earn_fund_auth.to_account_info(),
+                                system_program: ctx.accounts.system_program.to_account_info(),
+                                token_program: token_program.to_account_info(),
+                                rent: ctx.accounts.rent.to_account_info(),
+                            },
+                            signer,
+                        ),
+                        user_reward_amount,
+                    )?;
+                } else {
+                    reward_distribution::cpi::transfer(
+                        CpiContext::new_with_signer(
+                            reward_distribution_program.to_account_info(),
+                            reward_distribution::cpi::accounts::Transfer {
+                                reward_distribution_info: reward_distribution_info
+                                    .to_account_info(),
+                                authority: reward_transfer_auth.to_account_info(),
+                                transfer_allowance: reward_transfer_allowance.to_account_info(),
+                                to: user_ggwp_wallet.to_account_info(),
+                                play_to_earn_fund: play_to_earn_fund.to_account_info(),
+                                play_to_earn_fund_auth: play_to_earn_fund_auth.to_account_info(),
+                                token_program: token_program.to_account_info(),
+                            },
+                            signer,
+                        ),
+                        user_reward_amount,
+                    )?;
+                }
 
                 // Transfer royalty_amount to accumulative fund
                 reward_distribution::cpi::transfer(
@@ -281,6 +322,7 @@ pub mod fighting {
                         reward_distribution::cpi::accounts::Transfer {
                             reward_distribution_info: reward_distribution_info.to_account_info(),
                             authority: reward_transfer_auth.to_account_info(),
+                            transfer_allowance: reward_transfer_allowance.to_account_info(),
                             to: accumulative_fund.to_account_info(),
                             play_to_earn_fund: play_to_earn_fund.to_account_info(),
                             play_to_earn_fund_auth: play_to_earn_fund_auth.to_account_info(),
@@ -8,6 +8,8 @@ pub const GAME_INFO_SEED: &str = "game_info";
 pub const GPASS_BURN_AUTH_SEED: &str = "gpass_burn_auth";
 pub const REWARD_TRANSFER_AUTH_SEED: &str = "reward_transfer_auth";
 
+pub const MAX_ROYALTY: u8 = 100;
+
 #[account]
 #[derive(Default, Debug)]
 pub struct FightingSettings {
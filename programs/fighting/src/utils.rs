@@ -1,3 +1,4 @@
+use crate::error::FightingError;
 use anchor_lang::prelude::*;
 use anchor_spl::token::spl_token::{amount_to_ui_amount, ui_amount_to_amount};
 
@@ -20,14 +21,13 @@ pub fn calc_reward_amount(
     Ok(ui_amount_to_amount(reward_amount, 9))
 }
 
-/// Calcutale share
+/// Get the percent share of the amount rounded down.
 pub fn calc_share_amount(share: u8, amount: u64) -> Result<u64> {
-    let ui_amount = anchor_spl::token::spl_token::amount_to_ui_amount(amount, 9);
-    let share_amount = ui_amount / 100.0 * share as f64;
-    Ok(anchor_spl::token::spl_token::ui_amount_to_amount(
-        share_amount,
-        9,
-    ))
+    let share_amount = (amount as u128)
+        .checked_mul(share as u128)
+        .ok_or(FightingError::Overflow)?
+        / 100;
+    u64::try_from(share_amount).map_err(|_| FightingError::Overflow.into())
 }
 
 #[cfg(test)]
@@ -40,6 +40,19 @@ mod test {
         assert_eq!(calc_share_amount(10, 100_000_000_000), Ok(10_000_000_000));
         assert_eq!(calc_share_amount(50, 123_000_000_000), Ok(61_500_000_000));
         assert_eq!(calc_share_amount(10, 0), Ok(0));
+        assert_eq!(calc_share_amount(0, 123_000_000_000), Ok(0));
+        assert_eq!(calc_share_amount(100, u64::MAX), Ok(u64::MAX));
+        assert_eq!(calc_share_amount(50, u64::MAX), Ok(u64::MAX / 2));
+        // Rounded down
+        assert_eq!(calc_share_amount(8, 1), Ok(0));
+        assert_eq!(calc_share_amount(8, 13), Ok(1));
+        assert_eq!(calc_share_amount(33, 1_000_001), Ok(330_000));
+        // Exact on amounts not representable in f64
+        assert_eq!(
+            calc_share_amount(8, 9_007_199_254_740_993),
+            Ok(720_575_940_379_279)
+        );
+        assert!(calc_share_amount(101, u64::MAX).is_err());
     }
 
     #[test]
@@ -1,6 +1,7 @@
 use crate::error::*;
 use crate::state::{
-    FreezingInfo, UserInfo, GPASS_MINT_AUTH_SEED, TREASURY_AUTH_SEED, USER_INFO_SEED,
+    FreezingInfo, ReferrerInfo, UserInfo, GPASS_MINT_AUTH_SEED, REFERRER_INFO_SEED,
+    TREASURY_AUTH_SEED, USER_INFO_SEED,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
@@ -61,6 +62,104 @@ pub struct UpdateParam<'info> {
     pub freezing_info: Account<'info, FreezingInfo>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateFreezingInfo<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut,
+        realloc = FreezingInfo::LEN,
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub freezing_info: Box<Account<'info, FreezingInfo>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateUserInfo<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: User system account, checked by PDAs
+    pub user: UncheckedAccount<'info>,
+    /// CHECK: Legacy user info PDA, checked by the discriminator in the instruction
+    #[account(mut,
+        owner = crate::ID,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_info: UncheckedAccount<'info>,
+
+    pub freezing_info: Box<Account<'info, FreezingInfo>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    #[account(init, payer = referrer, space = ReferrerInfo::LEN,
+        seeds = [
+            REFERRER_INFO_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+            referrer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_info: Box<Account<'info, ReferrerInfo>>,
+
+    pub freezing_info: Box<Account<'info, FreezingInfo>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    pub referrer: Signer<'info>,
+    #[account(mut,
+        seeds = [
+            REFERRER_INFO_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+            referrer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_info: Box<Account<'info, ReferrerInfo>>,
+
+    pub freezing_info: Box<Account<'info, FreezingInfo>>,
+
+    #[account(mut,
+        constraint = referrer_ggwp_wallet.mint == freezing_info.ggwp_token
+        @FreezingError::InvalidUserGGWPWalletMint,
+    )]
+    pub referrer_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = treasury.key() == freezing_info.treasury
+        @FreezingError::InvalidTreasuryPK,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Treasury auth PDA
+    #[account(
+        seeds = [
+            TREASURY_AUTH_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+        ],
+        bump = freezing_info.treasury_auth_bump,
+    )]
+    pub treasury_auth: UncheckedAccount<'info>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct Freeze<'info> {
     #[account(mut)]
@@ -159,6 +258,133 @@ pub struct Withdraw<'info> {
     pub gpass_program: AccountInfo<'info>,
 }
 
+/// Accounts of the GPASS withdrawal earned by user.
+pub struct WithdrawGpass<'a, 'info> {
+    pub user_info: &'a mut Account<'info, UserInfo>,
+    pub freezing_info: &'a mut Account<'info, FreezingInfo>,
+    pub gpass_info: &'a Account<'info, GpassInfo>,
+    pub user_gpass_wallet: &'a Account<'info, Wallet>,
+    pub gpass_mint_auth: &'a UncheckedAccount<'info>,
+    pub gpass_program: &'a AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateUserParam<'info> {
+    pub user: Signer<'info>,
+    #[account(mut,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_info: Box<Account<'info, UserInfo>>,
+
+    pub freezing_info: Box<Account<'info, FreezingInfo>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositTipBudget<'info> {
+    pub user: Signer<'info>,
+    #[account(mut,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_info: Box<Account<'info, UserInfo>>,
+
+    pub freezing_info: Box<Account<'info, FreezingInfo>>,
+
+    #[account(mut,
+        constraint = user_ggwp_wallet.mint == freezing_info.ggwp_token
+        @FreezingError::InvalidUserGGWPWalletMint,
+        constraint = user_ggwp_wallet.owner == user.key()
+        @FreezingError::InvalidUserGGWPWalletOwner,
+    )]
+    pub user_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = treasury.key() == freezing_info.treasury
+        @FreezingError::InvalidTreasuryPK,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFor<'info> {
+    pub keeper: Signer<'info>,
+    /// CHECK: User system account, checked by PDAs
+    pub user: UncheckedAccount<'info>,
+    #[account(mut,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_info: Box<Account<'info, UserInfo>>,
+
+    #[account(mut)]
+    pub freezing_info: Box<Account<'info, FreezingInfo>>,
+
+    #[account(mut)]
+    pub gpass_info: Box<Account<'info, GpassInfo>>,
+    #[account(mut,
+        seeds = [
+            gpass::state::USER_WALLET_SEED.as_bytes(),
+            gpass_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        seeds::program = gpass::id(),
+        bump,
+    )]
+    pub user_gpass_wallet: Box<Account<'info, Wallet>>,
+
+    /// CHECK: Mint auth PDA
+    #[account(
+        seeds = [
+            GPASS_MINT_AUTH_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+            gpass_info.key().as_ref(),
+        ],
+        bump = freezing_info.gpass_mint_auth_bump,
+    )]
+    pub gpass_mint_auth: UncheckedAccount<'info>,
+
+    #[account(mut,
+        constraint = keeper_ggwp_wallet.mint == freezing_info.ggwp_token
+        @FreezingError::InvalidKeeperGGWPWalletMint,
+    )]
+    pub keeper_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = treasury.key() == freezing_info.treasury
+        @FreezingError::InvalidTreasuryPK,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Treasury auth PDA
+    #[account(
+        seeds = [
+            TREASURY_AUTH_SEED.as_bytes(),
+            freezing_info.key().as_ref(),
+        ],
+        bump = freezing_info.treasury_auth_bump,
+    )]
+    pub treasury_auth: UncheckedAccount<'info>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+    /// CHECK: GPASS program
+    #[account( constraint = gpass_program.key() == gpass::id() )]
+    pub gpass_program: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct Unfreeze<'info> {
     pub user: Signer<'info>,
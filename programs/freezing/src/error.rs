@@ -47,4 +47,30 @@ pub enum FreezingError {
     AdditionalFreezingNotAvailable, // 6017
     #[msg("Zero GPASS earned")]
     ZeroGpassEarned, // 6018
+
+    // Referral errors
+    #[msg("Invalid referral share value")]
+    InvalidReferralShareValue, // 6019
+    #[msg("User cannot refer himself")]
+    SelfReferral, // 6020
+    #[msg("Referrer accounts are missing")]
+    MissingReferrerAccounts, // 6021
+    #[msg("Invalid referrer info PK")]
+    InvalidReferrerInfoPK, // 6022
+    #[msg("No referral rewards to claim")]
+    ZeroReferralRewards, // 6023
+
+    // Auto claim errors
+    #[msg("Auto claim is disabled by user")]
+    AutoClaimDisabled, // 6024
+    #[msg("Deposit amount cannot be zero")]
+    ZeroDepositAmount, // 6025
+    #[msg("Invalid keeper GGWP wallet mint")]
+    InvalidKeeperGGWPWalletMint, // 6026
+    #[msg("User has nothing freezed")]
+    NothingFreezed, // 6027
+
+    // Migration errors
+    #[msg("User info is already migrated")]
+    UserInfoAlreadyMigrated, // 6028
 }
@@ -1,6 +1,10 @@
 use crate::error::FreezingError;
-use crate::state::{RewardTableRow, GPASS_MINT_AUTH_SEED, TREASURY_AUTH_SEED};
+use crate::state::{
+    ReferrerInfo, RewardTableRow, UserInfo, GPASS_MINT_AUTH_SEED, REFERRER_INFO_SEED,
+    TREASURY_AUTH_SEED,
+};
 use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
 use anchor_spl::token::Transfer;
 use context::*;
 
@@ -62,6 +66,7 @@ pub mod freezing {
         freezing_info.royalty = royalty;
         freezing_info.unfreeze_royalty = unfreeze_royalty;
         freezing_info.unfreeze_lock_period = unfreeze_lock_period;
+        freezing_info.referral_share = 0;
         freezing_info.reward_table = reward_table;
 
         Ok(())
@@ -190,8 +195,139 @@ pub mod freezing {
         Ok(())
     }
 
+    /// Update authority can set the new referral share of royalty in percent.
+    pub fn update_referral_share(ctx: Context<UpdateParam>, referral_share: u8) -> Result<()> {
+        require!(
+            referral_share <= 100,
+            FreezingError::InvalidReferralShareValue
+        );
+
+        let freezing_info = &mut ctx.accounts.freezing_info;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            freezing_info.update_auth,
+            FreezingError::AccessDenied
+        );
+
+        freezing_info.referral_share = referral_share;
+
+        Ok(())
+    }
+
+    /// Admin can reallocate the freezing info created before the referral share.
+    pub fn migrate_freezing_info(ctx: Context<MigrateFreezingInfo>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.freezing_info.admin,
+            FreezingError::AccessDenied
+        );
+
+        Ok(())
+    }
+
+    /// Anyone can reallocate the user info created before the auto claim and referral fields.
+    /// Appended fields are zeroed: auto claim is disabled, no tips and no referrer.
+    pub fn migrate_user_info(ctx: Context<MigrateUserInfo>) -> Result<()> {
+        let payer = &ctx.accounts.payer;
+        let user_info = &ctx.accounts.user_info;
+        let system_program = &ctx.accounts.system_program;
+
+        {
+            let data = user_info.try_borrow_data()?;
+            require!(
+                data.starts_with(&UserInfo::discriminator()),
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            require!(
+                data.len() < UserInfo::LEN,
+                FreezingError::UserInfoAlreadyMigrated
+            );
+        }
+
+        // Top up rent exemption of the new size
+        let rent = Rent::get()?;
+        let lamports = rent
+            .minimum_balance(UserInfo::LEN)
+            .saturating_sub(user_info.lamports());
+        if lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: payer.to_account_info(),
+                        to: user_info.to_account_info(),
+                    },
+                ),
+                lamports,
+            )?;
+        }
+        user_info.realloc(UserInfo::LEN, true)?;
+
+        let mut user_info_data: Account<UserInfo> = Account::try_from(user_info)?;
+        user_info_data.user = ctx.accounts.user.key();
+        user_info_data.exit(ctx.program_id)?;
+
+        Ok(())
+    }
+
+    /// Referrer registers his referral code (own pubkey).
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referrer_info = &mut ctx.accounts.referrer_info;
+        referrer_info.referrer = ctx.accounts.referrer.key();
+        referrer_info.referrals = 0;
+        referrer_info.total_earned = 0;
+        referrer_info.unclaimed_amount = 0;
+
+        Ok(())
+    }
+
+    /// Referrer claims the referral rewards stored in treasury to any GGWP wallet.
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        let referrer_info = &mut ctx.accounts.referrer_info;
+        let freezing_info = &ctx.accounts.freezing_info;
+        let referrer_ggwp_wallet = &ctx.accounts.referrer_ggwp_wallet;
+        let treasury = &ctx.accounts.treasury;
+        let treasury_auth = &ctx.accounts.treasury_auth;
+        let token_program = &ctx.accounts.token_program;
+
+        require_neq!(
+            referrer_info.unclaimed_amount,
+            0,
+            FreezingError::ZeroReferralRewards
+        );
+
+        let seeds = &[
+            TREASURY_AUTH_SEED.as_bytes(),
+            freezing_info.to_account_info().key.as_ref(),
+            &[freezing_info.treasury_auth_bump],
+        ];
+        let treasury_auth_signer = &[&seeds[..]];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: treasury.to_account_info(),
+                    to: referrer_ggwp_wallet.to_account_info(),
+                    authority: treasury_auth.to_account_info(),
+                },
+                treasury_auth_signer,
+            ),
+            referrer_info.unclaimed_amount,
+        )?;
+
+        referrer_info.unclaimed_amount = 0;
+
+        Ok(())
+    }
+
     /// User freezes his amount of GGWP token to get the GPASS.
-    pub fn freeze(ctx: Context<Freeze>, amount: u64) -> Result<()> {
+    /// The referrer is saved on the first freeze only. If the user has the referrer,
+    /// the referrer info must be passed in remaining accounts.
+    pub fn freeze<'info>(
+        ctx: Context<'_, '_, '_, 'info, Freeze<'info>>,
+        amount: u64,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
         let user = &ctx.accounts.user;
         let freezing_info = &mut ctx.accounts.freezing_info;
         let treasury = &ctx.accounts.treasury;
@@ -217,6 +353,20 @@ pub mod freezing {
             user_info.freezed_amount = 0;
             user_info.freezed_time = 0;
             user_info.last_getting_gpass = clock.unix_timestamp;
+            user_info.referrer = None;
+            user_info.auto_claim = false;
+            user_info.keeper_tip = 0;
+            user_info.tip_budget = 0;
+        }
+        user_info.user = user.key();
+
+        let mut is_new_referral = false;
+        if user_info.referrer.is_none() {
+            if let Some(referrer) = referrer {
+                require_keys_neq!(referrer, user.key(), FreezingError::SelfReferral);
+                user_info.referrer = Some(referrer);
+                is_new_referral = true;
+            }
         }
 
         // Calc the royalty
@@ -224,6 +374,7 @@ pub mod freezing {
         let freezed_amount = amount
             .checked_sub(royalty_amount)
             .ok_or(FreezingError::Overflow)?;
+        let mut fund_royalty_amount = royalty_amount;
 
         // Pay amount of GPASS earned by user immediately
         let gpass_earned =
@@ -270,6 +421,54 @@ pub mod freezing {
             )?;
         }
 
+        // Store the referral part of royalty in treasury until the referrer claims it
+        let mut referral_amount = 0;
+        if let Some(referrer) = user_info.referrer {
+            let referrer_info_account = ctx
+                .remaining_accounts
+                .first()
+                .ok_or(FreezingError::MissingReferrerAccounts)?;
+
+            let (referrer_info_pk, _) = Pubkey::find_program_address(
+                &[
+                    REFERRER_INFO_SEED.as_bytes(),
+                    freezing_info.key().as_ref(),
+                    referrer.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                referrer_info_account.key(),
+                referrer_info_pk,
+                FreezingError::InvalidReferrerInfoPK
+            );
+            let mut referrer_info: Account<ReferrerInfo> =
+                Account::try_from(referrer_info_account)?;
+
+            referral_amount =
+                utils::calc_royalty_amount(freezing_info.referral_share, royalty_amount)?;
+            msg!("Referral reward: {}", referral_amount);
+            fund_royalty_amount = royalty_amount
+                .checked_sub(referral_amount)
+                .ok_or(FreezingError::Overflow)?;
+
+            if is_new_referral {
+                referrer_info.referrals = referrer_info
+                    .referrals
+                    .checked_add(1)
+                    .ok_or(FreezingError::Overflow)?;
+            }
+            referrer_info.total_earned = referrer_info
+                .total_earned
+                .checked_add(referral_amount)
+                .ok_or(FreezingError::Overflow)?;
+            referrer_info.unclaimed_amount = referrer_info
+                .unclaimed_amount
+                .checked_add(referral_amount)
+                .ok_or(FreezingError::Overflow)?;
+            referrer_info.exit(ctx.program_id)?;
+        }
+
         // Transfer royalty amount into accumulative fund
         anchor_spl::token::transfer(
             CpiContext::new(
@@ -280,10 +479,10 @@ pub mod freezing {
                     authority: user.to_account_info(),
                 },
             ),
-            royalty_amount,
+            fund_royalty_amount,
         )?;
 
-        // Freeze GGWP, transfer to treasury
+        // Freeze GGWP with the referral reward, transfer to treasury
         anchor_spl::token::transfer(
             CpiContext::new(
                 token_program.to_account_info(),
@@ -293,7 +492,9 @@ pub mod freezing {
                     authority: user.to_account_info(),
                 },
             ),
-            freezed_amount,
+            freezed_amount
+                .checked_add(referral_amount)
+                .ok_or(FreezingError::Overflow)?,
         )?;
 
         freezing_info.total_freezed = freezing_info
@@ -313,67 +514,114 @@ pub mod freezing {
 
     /// In every time user can withdraw GPASS earned.
     pub fn withdraw_gpass(ctx: Context<Withdraw>) -> Result<()> {
+        withdraw_earned_gpass(WithdrawGpass {
+            user_info: &mut ctx.accounts.user_info,
+            freezing_info: &mut ctx.accounts.freezing_info,
+            gpass_info: &ctx.accounts.gpass_info,
+            user_gpass_wallet: &ctx.accounts.user_gpass_wallet,
+            gpass_mint_auth: &ctx.accounts.gpass_mint_auth,
+            gpass_program: &ctx.accounts.gpass_program,
+        })
+    }
+
+    /// User can allow anyone to withdraw his GPASS and set the tip for keeper.
+    pub fn update_auto_claim(
+        ctx: Context<UpdateUserParam>,
+        auto_claim: bool,
+        keeper_tip: u64,
+    ) -> Result<()> {
         let user_info = &mut ctx.accounts.user_info;
-        let freezing_info = &mut ctx.accounts.freezing_info;
-        let gpass_info = &ctx.accounts.gpass_info;
-        let user_gpass_wallet = &ctx.accounts.user_gpass_wallet;
-        let gpass_mint_auth = &ctx.accounts.gpass_mint_auth;
-        let gpass_program = &ctx.accounts.gpass_program;
-        let clock = Clock::get()?;
+        user_info.user = ctx.accounts.user.key();
+        user_info.auto_claim = auto_claim;
+        user_info.keeper_tip = keeper_tip;
 
-        let current_time = clock.unix_timestamp;
-        // Pay current GPASS earned by user
-        let gpass_earned = utils::calc_earned_gpass(
-            &freezing_info.reward_table,
-            user_info.freezed_amount,
-            current_time,
-            user_info.last_getting_gpass,
-            freezing_info.reward_period,
-        )?;
-        if gpass_earned == 0 {
-            msg!("GPASS is not earned yet");
-            return Err(FreezingError::ZeroGpassEarned.into());
-        }
+        Ok(())
+    }
 
-        // Try to reset gpass daily reward
-        let spent_time = clock
-            .unix_timestamp
-            .checked_sub(freezing_info.daily_gpass_reward_last_reset)
-            .ok_or(FreezingError::Overflow)?;
-        if spent_time >= 24 * 60 * 60 {
-            freezing_info.daily_gpass_reward = 0;
-            freezing_info.daily_gpass_reward_last_reset = clock.unix_timestamp;
-        }
+    /// User deposits GGWP into the treasury to pay keeper tips. No royalty taken.
+    pub fn deposit_tip_budget(ctx: Context<DepositTipBudget>, amount: u64) -> Result<()> {
+        let user = &ctx.accounts.user;
+        let user_info = &mut ctx.accounts.user_info;
+        let user_ggwp_wallet = &ctx.accounts.user_ggwp_wallet;
+        let treasury = &ctx.accounts.treasury;
+        let token_program = &ctx.accounts.token_program;
 
-        // Update gpass daily reward
-        freezing_info.daily_gpass_reward = freezing_info
-            .daily_gpass_reward
-            .checked_add(gpass_earned)
-            .ok_or(FreezingError::Overflow)?;
+        require_neq!(amount, 0, FreezingError::ZeroDepositAmount);
+        // Tip budget is returned on unfreeze only
+        require_neq!(user_info.freezed_amount, 0, FreezingError::NothingFreezed);
 
-        msg!("Earned GPASS: {}", gpass_earned);
-        user_info.last_getting_gpass = clock.unix_timestamp;
-        // Mint GPASS to user
-        let seeds = &[
-            GPASS_MINT_AUTH_SEED.as_bytes(),
-            freezing_info.to_account_info().key.as_ref(),
-            gpass_info.to_account_info().key.as_ref(),
-            &[freezing_info.gpass_mint_auth_bump],
-        ];
-        let signer = &[&seeds[..]];
-        gpass::cpi::mint_to(
-            CpiContext::new_with_signer(
-                gpass_program.to_account_info(),
-                gpass::cpi::accounts::MintTo {
-                    authority: gpass_mint_auth.to_account_info(),
-                    gpass_info: gpass_info.to_account_info(),
-                    to: user_gpass_wallet.to_account_info(),
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Transfer {
+                    from: user_ggwp_wallet.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: user.to_account_info(),
                 },
-                signer,
             ),
-            gpass_earned,
+            amount,
         )?;
 
+        user_info.tip_budget = user_info
+            .tip_budget
+            .checked_add(amount)
+            .ok_or(FreezingError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Anyone can withdraw GPASS earned for user with enabled auto claim.
+    /// Keeper gets the tip from user tip budget if it is enough.
+    pub fn withdraw_gpass_for(ctx: Context<WithdrawFor>) -> Result<()> {
+        require!(
+            ctx.accounts.user_info.auto_claim,
+            FreezingError::AutoClaimDisabled
+        );
+
+        withdraw_earned_gpass(WithdrawGpass {
+            user_info: &mut ctx.accounts.user_info,
+            freezing_info: &mut ctx.accounts.freezing_info,
+            gpass_info: &ctx.accounts.gpass_info,
+            user_gpass_wallet: &ctx.accounts.user_gpass_wallet,
+            gpass_mint_auth: &ctx.accounts.gpass_mint_auth,
+            gpass_program: &ctx.accounts.gpass_program,
+        })?;
+
+        let user_info = &mut ctx.accounts.user_info;
+        let freezing_info = &ctx.accounts.freezing_info;
+        let keeper_ggwp_wallet = &ctx.accounts.keeper_ggwp_wallet;
+        let treasury = &ctx.accounts.treasury;
+        let treasury_auth = &ctx.accounts.treasury_auth;
+        let token_program = &ctx.accounts.token_program;
+
+        // Pay tip to keeper from user tip budget
+        if user_info.keeper_tip > 0 && user_info.tip_budget >= user_info.keeper_tip {
+            msg!("Keeper tip: {}", user_info.keeper_tip);
+            let seeds = &[
+                TREASURY_AUTH_SEED.as_bytes(),
+                freezing_info.to_account_info().key.as_ref(),
+                &[freezing_info.treasury_auth_bump],
+            ];
+            let treasury_auth_signer = &[&seeds[..]];
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: treasury.to_account_info(),
+                        to: keeper_ggwp_wallet.to_account_info(),
+                        authority: treasury_auth.to_account_info(),
+                    },
+                    treasury_auth_signer,
+                ),
+                user_info.keeper_tip,
+            )?;
+
+            user_info.tip_budget = user_info
+                .tip_budget
+                .checked_sub(user_info.keeper_tip)
+                .ok_or(FreezingError::Overflow)?;
+        }
+
         Ok(())
     }
 
@@ -491,6 +739,11 @@ pub mod freezing {
                 .ok_or(FreezingError::Overflow)?;
         }
 
+        // Return the unused tip budget
+        amount = amount
+            .checked_add(user_info.tip_budget)
+            .ok_or(FreezingError::Overflow)?;
+
         // Send GGWP to user wallet
         anchor_spl::token::transfer(
             CpiContext::new_with_signer(
@@ -511,7 +764,76 @@ pub mod freezing {
             .ok_or(FreezingError::Overflow)?;
         user_info.freezed_amount = 0;
         user_info.freezed_time = 0;
+        user_info.tip_budget = 0;
 
         Ok(())
     }
 }
+
+/// Mint GPASS earned by user since the last withdrawal.
+fn withdraw_earned_gpass(accounts: WithdrawGpass) -> Result<()> {
+    let WithdrawGpass {
+        user_info,
+        freezing_info,
+        gpass_info,
+        user_gpass_wallet,
+        gpass_mint_auth,
+        gpass_program,
+    } = accounts;
+    let clock = Clock::get()?;
+
+    let current_time = clock.unix_timestamp;
+    // Pay current GPASS earned by user
+    let gpass_earned = utils::calc_earned_gpass(
+        &freezing_info.reward_table,
+        user_info.freezed_amount,
+        current_time,
+        user_info.last_getting_gpass,
+        freezing_info.reward_period,
+    )?;
+    if gpass_earned == 0 {
+        msg!("GPASS is not earned yet");
+        return Err(FreezingError::ZeroGpassEarned.into());
+    }
+
+    // Try to reset gpass daily reward
+    let spent_time = clock
+        .unix_timestamp
+        .checked_sub(freezing_info.daily_gpass_reward_last_reset)
+        .ok_or(FreezingError::Overflow)?;
+    if spent_time >= 24 * 60 * 60 {
+        freezing_info.daily_gpass_reward = 0;
+        freezing_info.daily_gpass_reward_last_reset = clock.unix_timestamp;
+    }
+
+    // Update gpass daily reward
+    freezing_info.daily_gpass_reward = freezing_info
+        .daily_gpass_reward
+        .checked_add(gpass_earned)
+        .ok_or(FreezingError::Overflow)?;
+
+    msg!("Earned GPASS: {}", gpass_earned);
+    user_info.last_getting_gpass = clock.unix_timestamp;
+    // Mint GPASS to user
+    let seeds = &[
+        GPASS_MINT_AUTH_SEED.as_bytes(),
+        freezing_info.to_account_info().key.as_ref(),
+        gpass_info.to_account_info().key.as_ref(),
+        &[freezing_info.gpass_mint_auth_bump],
+    ];
+    let signer = &[&seeds[..]];
+    gpass::cpi::mint_to(
+        CpiContext::new_with_signer(
+            gpass_program.to_account_info(),
+            gpass::cpi::accounts::MintTo {
+                authority: gpass_mint_auth.to_account_info(),
+                gpass_info: gpass_info.to_account_info(),
+                to: user_gpass_wallet.to_account_info(),
+            },
+            signer,
+        ),
+        gpass_earned,
+    )?;
+
+    Ok(())
+}
@@ -8,6 +8,7 @@ const MAX_REWARD_TABLE_LEN: usize = 8 + REWARD_TABLE_ROW_SIZE * MAX_REWARDS_TABL
 pub const GPASS_MINT_AUTH_SEED: &str = "gpass_mint_auth";
 pub const TREASURY_AUTH_SEED: &str = "treasury_auth";
 pub const USER_INFO_SEED: &str = "user_info";
+pub const REFERRER_INFO_SEED: &str = "referrer_info";
 
 #[account]
 #[derive(Default, Debug)]
@@ -36,6 +37,8 @@ pub struct FreezingInfo {
     pub unfreeze_lock_period: i64,
 
     pub reward_table: Vec<RewardTableRow>,
+    // Part of the royalty paid to the user referrer
+    pub referral_share: u8,
 }
 
 impl FreezingInfo {
@@ -53,7 +56,8 @@ impl FreezingInfo {
         8 + // reward period
         1 + 1 + // royalty percents
         8 + // unfreeze lock time in secs
-        MAX_REWARD_TABLE_LEN;
+        MAX_REWARD_TABLE_LEN +
+        1; // referral share percent
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug)]
@@ -69,6 +73,15 @@ pub struct UserInfo {
     pub freezed_amount: u64,
     pub freezed_time: i64,       // UnixTimestamp
     pub last_getting_gpass: i64, // UnixTimestamp
+    pub user: Pubkey,
+    // Anyone can withdraw GPASS for user if enabled
+    pub auto_claim: bool,
+    // GGWP paid to keeper for every auto claim
+    pub keeper_tip: u64,
+    // GGWP deposited for keeper tips (without royalty), stored in treasury
+    pub tip_budget: u64,
+    // Variable length, keep it last for the fixed offsets of the fields above
+    pub referrer: Option<Pubkey>,
 }
 
 impl UserInfo {
@@ -76,5 +89,31 @@ impl UserInfo {
         1 + // is initialized
         8 + // freezed amount
         8 + // freezed time
-        8; // last getting gpass
+        8 + // last getting gpass
+        32 + // user pk
+        1 + // auto claim
+        8 + // keeper tip
+        8 + // tip budget
+        1 + 32; // referrer
+
+    // Offset of the auto claim flag for the accounts filter
+    pub const AUTO_CLAIM_OFFSET: usize = DESCRIMINATOR_LEN + 1 + 8 + 8 + 8 + 32;
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct ReferrerInfo {
+    pub referrer: Pubkey,
+    pub referrals: u64,
+    pub total_earned: u64,
+    // Referral rewards stored in treasury until claimed
+    pub unclaimed_amount: u64,
+}
+
+impl ReferrerInfo {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        32 + // referrer pk
+        8 + // referrals count
+        8 + // total earned
+        8; // unclaimed amount
 }
@@ -3,7 +3,6 @@ use crate::{
     state::{RewardTableRow, MAX_REWARDS_TABLE_ROWS},
 };
 use anchor_lang::{prelude::*, solana_program::clock::UnixTimestamp};
-use anchor_spl::token::spl_token::{amount_to_ui_amount, ui_amount_to_amount};
 
 /// Checks reward table valid.
 pub fn is_reward_table_valid(reward_table: &Vec<RewardTableRow>) -> Result<bool> {
@@ -38,16 +37,12 @@ pub fn earned_gpass_immediately(
     reward_table: &Vec<RewardTableRow>,
     user_ggwp_amount: u64,
 ) -> Result<u64> {
-    let mut earned_gpass = 0;
-    for row in reward_table {
-        if user_ggwp_amount >= row.ggwp_amount {
-            earned_gpass = row.gpass_amount;
-        } else {
-            break;
-        }
-    }
-
-    Ok(earned_gpass)
+    Ok(reward_calc::calc_gpass_per_period(
+        reward_table
+            .iter()
+            .map(|row| (row.ggwp_amount, row.gpass_amount)),
+        user_ggwp_amount,
+    ))
 }
 
 pub fn calc_earned_gpass(
@@ -57,29 +52,23 @@ pub fn calc_earned_gpass(
     last_getting_gpass: UnixTimestamp,
     reward_period: UnixTimestamp,
 ) -> Result<u64> {
-    let spent_time = current_time
-        .checked_sub(last_getting_gpass)
-        .ok_or(FreezingError::Overflow)?;
-    msg!("Spent time: {}", spent_time);
-    if spent_time < reward_period {
-        msg!("Reward period is not passed yet.");
-        return Ok(0);
-    }
-
-    let reward_periods_spent = spent_time
-        .checked_div(reward_period)
-        .ok_or(FreezingError::Overflow)? as u64;
+    msg!(
+        "Spent time: {}",
+        current_time.saturating_sub(last_getting_gpass)
+    );
     let earned_gpass = earned_gpass_immediately(reward_table, user_ggwp_amount)?;
-    Ok(earned_gpass
-        .checked_mul(reward_periods_spent)
-        .ok_or(FreezingError::Overflow)?)
+    Ok(reward_calc::calc_earned_gpass(
+        earned_gpass,
+        current_time,
+        last_getting_gpass,
+        reward_period,
+    )
+    .ok_or(FreezingError::Overflow)?)
 }
 
 /// Get the percent value.
 pub fn calc_royalty_amount(royalty: u8, amount: u64) -> Result<u64> {
-    let ui_amount = amount_to_ui_amount(amount, 9);
-    let royalty_amount = ui_amount / 100.0 * royalty as f64;
-    Ok(ui_amount_to_amount(royalty_amount, 9))
+    Ok(reward_calc::calc_royalty_amount(royalty, amount))
 }
 
 /// Checks freezed time for withdraw royalty.
@@ -88,18 +77,16 @@ pub fn is_withdraw_royalty(
     freezed_time: UnixTimestamp,
     unfreeze_lock_period: UnixTimestamp,
 ) -> Result<bool> {
-    let spent_time = current_time
-        .checked_sub(freezed_time)
-        .ok_or(FreezingError::Overflow)?;
-    if spent_time >= unfreeze_lock_period {
-        return Ok(false);
-    }
-    Ok(true)
+    Ok(
+        reward_calc::is_withdraw_royalty(current_time, freezed_time, unfreeze_lock_period)
+            .ok_or(FreezingError::Overflow)?,
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::UserInfo;
 
     #[test]
     pub fn test_is_reward_table_valid() {
@@ -374,4 +361,22 @@ mod tests {
         assert_eq!(is_withdraw_royalty(1660032700, 1660032500, 100), Ok(false));
         assert_eq!(is_withdraw_royalty(1660032700, 1660032300, 100), Ok(false));
     }
+
+    #[test]
+    pub fn test_user_info_auto_claim_offset() {
+        for referrer in [None, Some(Pubkey::new_unique())] {
+            let user_info = UserInfo {
+                is_initialized: true,
+                freezed_amount: u64::MAX,
+                user: Pubkey::new_unique(),
+                auto_claim: true,
+                referrer,
+                ..Default::default()
+            };
+            let mut data = vec![];
+            user_info.try_serialize(&mut data).unwrap();
+            assert!(data.len() <= UserInfo::LEN);
+            assert_eq!(data[UserInfo::AUTO_CLAIM_OFFSET], 1);
+        }
+    }
 }
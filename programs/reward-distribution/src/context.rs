@@ -1,5 +1,9 @@
 use crate::error::RewardDistributionError;
-use crate::state::{RewardDistributionInfo, PLAY_TO_EARN_FUND_AUTH_SEED};
+use crate::state::{
+    ClaimCampaign, RewardDistributionInfo, TransferAllowance, VestingEscrow,
+    PLAY_TO_EARN_FUND_AUTH_SEED, TRANSFER_ALLOWANCE_SEED, VESTING_ESCROW_SEED,
+    VESTING_ESCROW_WALLET_SEED,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, TokenAccount, Token};
 
@@ -39,11 +43,58 @@ pub struct UpdateParam<'info> {
     pub reward_distribution_info: Account<'info, RewardDistributionInfo>,
 }
 
+#[derive(Accounts)]
+pub struct CreateTransferAllowance<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+
+    /// CHECK: Transfer authority
+    pub authority: UncheckedAccount<'info>,
+    #[account(init, payer = admin, space = TransferAllowance::LEN,
+        seeds = [
+            TRANSFER_ALLOWANCE_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub transfer_allowance: Box<Account<'info, TransferAllowance>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTransferAllowance<'info> {
+    pub admin: Signer<'info>,
+    pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+
+    #[account(mut,
+        seeds = [
+            TRANSFER_ALLOWANCE_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            transfer_allowance.authority.as_ref(),
+        ],
+        bump
+    )]
+    pub transfer_allowance: Box<Account<'info, TransferAllowance>>,
+}
+
 #[derive(Accounts)]
 pub struct Transfer<'info> {
     pub authority: Signer<'info>,
 
     pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+    #[account(mut,
+        seeds = [
+            TRANSFER_ALLOWANCE_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub transfer_allowance: Box<Account<'info, TransferAllowance>>,
 
     #[account(mut,
         constraint = play_to_earn_fund.mint == reward_distribution_info.ggwp_token
@@ -70,3 +121,204 @@ pub struct Transfer<'info> {
     // Misc.
     pub token_program: Program<'info, Token>,
 }
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32], total_amount: u64, max_claims: u32)]
+pub struct CreateClaimCampaign<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+    #[account(mut,
+        seeds = [
+            TRANSFER_ALLOWANCE_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub transfer_allowance: Box<Account<'info, TransferAllowance>>,
+
+    #[account(init, payer = authority,
+        space = ClaimCampaign::LEN + ClaimCampaign::bitmap_len(max_claims),
+    )]
+    pub claim_campaign: Box<Account<'info, ClaimCampaign>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFromCampaign<'info> {
+    pub user: Signer<'info>,
+    pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+    #[account(mut,
+        has_one = reward_distribution_info,
+    )]
+    pub claim_campaign: Box<Account<'info, ClaimCampaign>>,
+
+    #[account(mut,
+        constraint = play_to_earn_fund.mint == reward_distribution_info.ggwp_token
+        @RewardDistributionError::InvalidPlayToEarnFundMint,
+        constraint = play_to_earn_fund.owner == play_to_earn_fund_auth.key()
+        @RewardDistributionError::InvalidPlayToEarnFundOwner,
+    )]
+    pub play_to_earn_fund: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Accumulative auth account
+    #[account(
+        seeds = [
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+        ],
+        bump = reward_distribution_info.play_to_earn_fund_auth_bump,
+    )]
+    pub play_to_earn_fund_auth: UncheckedAccount<'info>,
+
+    #[account(mut,
+        constraint = user_ggwp_wallet.mint == reward_distribution_info.ggwp_token
+        @RewardDistributionError::InvalidUserGGWPWallet,
+        constraint = user_ggwp_wallet.owner == user.key()
+        @RewardDistributionError::InvalidUserGGWPWallet,
+    )]
+    pub user_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BatchTransfer<'info> {
+    pub authority: Signer<'info>,
+
+    pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+    #[account(mut,
+        seeds = [
+            TRANSFER_ALLOWANCE_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub transfer_allowance: Box<Account<'info, TransferAllowance>>,
+
+    #[account(mut,
+        constraint = play_to_earn_fund.mint == reward_distribution_info.ggwp_token
+        @RewardDistributionError::InvalidPlayToEarnFundMint,
+        constraint = play_to_earn_fund.owner == play_to_earn_fund_auth.key()
+        @RewardDistributionError::InvalidPlayToEarnFundOwner,
+    )]
+    pub play_to_earn_fund: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Accumulative auth account
+    #[account(
+        seeds = [
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+        ],
+        bump = reward_distribution_info.play_to_earn_fund_auth_bump,
+    )]
+    pub play_to_earn_fund_auth: UncheckedAccount<'info>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TransferVested<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+    #[account(mut,
+        seeds = [
+            TRANSFER_ALLOWANCE_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub transfer_allowance: Box<Account<'info, TransferAllowance>>,
+
+    /// CHECK: Rewards recipient
+    pub user: UncheckedAccount<'info>,
+    #[account(init_if_needed, payer = payer, space = VestingEscrow::LEN,
+        seeds = [
+            VESTING_ESCROW_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub vesting_escrow: Box<Account<'info, VestingEscrow>>,
+    #[account(init_if_needed, payer = payer,
+        seeds = [
+            VESTING_ESCROW_WALLET_SEED.as_bytes(),
+            vesting_escrow.key().as_ref(),
+        ],
+        bump,
+        token::mint = ggwp_token,
+        token::authority = vesting_escrow,
+    )]
+    pub vesting_escrow_wallet: Box<Account<'info, TokenAccount>>,
+    #[account(
+        constraint = ggwp_token.key() == reward_distribution_info.ggwp_token
+        @RewardDistributionError::InvalidGGWPToken,
+    )]
+    pub ggwp_token: Box<Account<'info, Mint>>,
+
+    #[account(mut,
+        constraint = play_to_earn_fund.mint == reward_distribution_info.ggwp_token
+        @RewardDistributionError::InvalidPlayToEarnFundMint,
+        constraint = play_to_earn_fund.owner == play_to_earn_fund_auth.key()
+        @RewardDistributionError::InvalidPlayToEarnFundOwner,
+    )]
+    pub play_to_earn_fund: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Accumulative auth account
+    #[account(
+        seeds = [
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+        ],
+        bump = reward_distribution_info.play_to_earn_fund_auth_bump,
+    )]
+    pub play_to_earn_fund_auth: UncheckedAccount<'info>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    pub user: Signer<'info>,
+    pub reward_distribution_info: Box<Account<'info, RewardDistributionInfo>>,
+
+    #[account(mut,
+        seeds = [
+            VESTING_ESCROW_SEED.as_bytes(),
+            reward_distribution_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = vesting_escrow.bump,
+    )]
+    pub vesting_escrow: Box<Account<'info, VestingEscrow>>,
+    #[account(mut,
+        seeds = [
+            VESTING_ESCROW_WALLET_SEED.as_bytes(),
+            vesting_escrow.key().as_ref(),
+        ],
+        bump = vesting_escrow.wallet_bump,
+    )]
+    pub vesting_escrow_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut,
+        constraint = user_ggwp_wallet.mint == reward_distribution_info.ggwp_token
+        @RewardDistributionError::InvalidUserGGWPWallet,
+        constraint = user_ggwp_wallet.owner == user.key()
+        @RewardDistributionError::InvalidUserGGWPWallet,
+    )]
+    pub user_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
@@ -21,4 +21,32 @@ pub enum RewardDistributionError {
     // Reward distribution errors
     #[msg("Invalid transfer authority")]
     InvalidTransferAuthority, // 6005
+    #[msg("Invalid allowance window")]
+    InvalidAllowanceWindow, // 6006
+    #[msg("Transfer allowance exceeded")]
+    AllowanceExceeded, // 6007
+    #[msg("Invalid max claims")]
+    InvalidMaxClaims, // 6008
+    #[msg("Invalid claim index")]
+    InvalidClaimIndex, // 6009
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof, // 6010
+    #[msg("Already claimed")]
+    AlreadyClaimed, // 6011
+    #[msg("Campaign amount exceeded")]
+    CampaignAmountExceeded, // 6012
+    #[msg("Invalid user GGWP wallet")]
+    InvalidUserGGWPWallet, // 6013
+    #[msg("Invalid recipient accounts")]
+    InvalidRecipientAccounts, // 6014
+    #[msg("Invalid recipient mint")]
+    InvalidRecipientMint, // 6015
+    #[msg("Invalid vesting duration")]
+    InvalidVestingDuration, // 6016
+    #[msg("Vesting is disabled")]
+    VestingDisabled, // 6017
+    #[msg("Nothing to claim")]
+    NothingToClaim, // 6018
+    #[msg("Invalid GGWP token")]
+    InvalidGGWPToken, // 6019
 }
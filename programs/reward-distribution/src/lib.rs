@@ -1,11 +1,16 @@
 use crate::context::*;
 use crate::error::RewardDistributionError;
-use crate::state::{MAX_TRANSFER_AUTH_LIST, PLAY_TO_EARN_FUND_AUTH_SEED};
+use crate::state::{
+    ClaimCampaign, MAX_CAMPAIGN_CLAIMS, MAX_TRANSFER_AUTH_LIST, PLAY_TO_EARN_FUND_AUTH_SEED,
+    VESTING_ESCROW_SEED,
+};
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 
 mod context;
 mod error;
 pub mod state;
+pub mod utils;
 
 declare_id!("5ihGT7nkjxfo1M43NZrPbbDBG4Js215ftJp6uksnNCEP");
 
@@ -84,6 +89,74 @@ pub mod reward_distribution {
         Ok(())
     }
 
+    /// Update auth can set the vesting duration in seconds of vested transfers.
+    /// Zero duration disables vested transfers.
+    pub fn update_vesting_duration(ctx: Context<UpdateParam>, vesting_duration: i64) -> Result<()> {
+        let reward_distribution_info = &mut ctx.accounts.reward_distribution_info;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            reward_distribution_info.update_auth,
+            RewardDistributionError::AccessDenied
+        );
+
+        require_gte!(
+            vesting_duration,
+            0,
+            RewardDistributionError::InvalidVestingDuration
+        );
+
+        reward_distribution_info.vesting_duration = vesting_duration;
+
+        Ok(())
+    }
+
+    /// Admin can create the allowance of the transfer authority.
+    /// Authority can transfer up to the limit per window in seconds.
+    pub fn create_transfer_allowance(
+        ctx: Context<CreateTransferAllowance>,
+        limit: u64,
+        window: i64,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.reward_distribution_info.admin,
+            RewardDistributionError::AccessDenied
+        );
+        require!(window > 0, RewardDistributionError::InvalidAllowanceWindow);
+
+        let transfer_allowance = &mut ctx.accounts.transfer_allowance;
+        transfer_allowance.reward_distribution_info = ctx.accounts.reward_distribution_info.key();
+        transfer_allowance.authority = ctx.accounts.authority.key();
+        transfer_allowance.limit = limit;
+        transfer_allowance.window = window;
+        transfer_allowance.window_start = 0;
+        transfer_allowance.used = 0;
+
+        Ok(())
+    }
+
+    /// Admin can set the new limit and window of the transfer allowance.
+    /// Amount used in the current window is kept.
+    pub fn update_transfer_allowance(
+        ctx: Context<UpdateTransferAllowance>,
+        limit: u64,
+        window: i64,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.reward_distribution_info.admin,
+            RewardDistributionError::AccessDenied
+        );
+        require!(window > 0, RewardDistributionError::InvalidAllowanceWindow);
+
+        let transfer_allowance = &mut ctx.accounts.transfer_allowance;
+        transfer_allowance.limit = limit;
+        transfer_allowance.window = window;
+
+        Ok(())
+    }
+
+    /// Transfer authority can send GGWP from the play to earn fund within its allowance.
     pub fn transfer(ctx: Context<Transfer>, amount: u64) -> Result<()> {
         let reward_distribution_info = &ctx.accounts.reward_distribution_info;
         let authority = &ctx.accounts.authority;
@@ -100,6 +173,12 @@ pub mod reward_distribution {
             return Err(RewardDistributionError::InvalidTransferAuthority.into());
         }
 
+        utils::spend_allowance(
+            &mut ctx.accounts.transfer_allowance,
+            amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+
         let seeds = &[
             PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
             reward_distribution_info.to_account_info().key.as_ref(),
@@ -121,4 +200,285 @@ pub mod reward_distribution {
 
         Ok(())
     }
+
+    /// Transfer authority can send GGWP to many recipients within its allowance.
+    /// Recipient token accounts are passed in the amounts order through remaining accounts.
+    pub fn batch_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchTransfer<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        let reward_distribution_info = &ctx.accounts.reward_distribution_info;
+        let authority = &ctx.accounts.authority;
+        let play_to_earn_fund = &ctx.accounts.play_to_earn_fund;
+        let play_to_earn_fund_auth = &ctx.accounts.play_to_earn_fund_auth;
+        let token_program = &ctx.accounts.token_program;
+
+        if !reward_distribution_info
+            .transfer_auth_list
+            .contains(authority.key)
+        {
+            msg!("Invalid transfer authority");
+            return Err(RewardDistributionError::InvalidTransferAuthority.into());
+        }
+        require_eq!(
+            ctx.remaining_accounts.len(),
+            amounts.len(),
+            RewardDistributionError::InvalidRecipientAccounts
+        );
+        for recipient in ctx.remaining_accounts {
+            let recipient: Account<TokenAccount> = Account::try_from(recipient)?;
+            require_keys_eq!(
+                recipient.mint,
+                reward_distribution_info.ggwp_token,
+                RewardDistributionError::InvalidRecipientMint
+            );
+        }
+
+        let total_amount = amounts.iter().try_fold(0u64, |sum, amount| {
+            sum.checked_add(*amount)
+                .ok_or(RewardDistributionError::Overflow)
+        })?;
+        utils::spend_allowance(
+            &mut ctx.accounts.transfer_allowance,
+            total_amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let seeds = &[
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.to_account_info().key.as_ref(),
+            &[reward_distribution_info.play_to_earn_fund_auth_bump],
+        ];
+        let signer = &[&seeds[..]];
+        for (recipient, amount) in ctx.remaining_accounts.iter().zip(amounts) {
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    anchor_spl::token::Transfer {
+                        authority: play_to_earn_fund_auth.to_account_info(),
+                        from: play_to_earn_fund.to_account_info(),
+                        to: recipient.clone(),
+                    },
+                    signer,
+                ),
+                amount,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Transfer authority can create the campaign of rewards claimed with Merkle proofs.
+    /// Campaign total amount is spent from the authority allowance.
+    pub fn create_claim_campaign(
+        ctx: Context<CreateClaimCampaign>,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        max_claims: u32,
+    ) -> Result<()> {
+        let reward_distribution_info = &ctx.accounts.reward_distribution_info;
+        let authority = &ctx.accounts.authority;
+
+        if !reward_distribution_info
+            .transfer_auth_list
+            .contains(authority.key)
+        {
+            msg!("Invalid transfer authority");
+            return Err(RewardDistributionError::InvalidTransferAuthority.into());
+        }
+        require!(
+            max_claims > 0 && max_claims <= MAX_CAMPAIGN_CLAIMS,
+            RewardDistributionError::InvalidMaxClaims
+        );
+
+        utils::spend_allowance(
+            &mut ctx.accounts.transfer_allowance,
+            total_amount,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let claim_campaign = &mut ctx.accounts.claim_campaign;
+        claim_campaign.reward_distribution_info = reward_distribution_info.key();
+        claim_campaign.authority = authority.key();
+        claim_campaign.merkle_root = merkle_root;
+        claim_campaign.max_claims = max_claims;
+        claim_campaign.total_amount = total_amount;
+        claim_campaign.claimed_amount = 0;
+        claim_campaign.claimed_bitmap = vec![0; ClaimCampaign::bitmap_len(max_claims)];
+
+        Ok(())
+    }
+
+    /// User claims the campaign reward with the Merkle proof.
+    pub fn claim_from_campaign(
+        ctx: Context<ClaimFromCampaign>,
+        index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let reward_distribution_info = &ctx.accounts.reward_distribution_info;
+        let claim_campaign = &mut ctx.accounts.claim_campaign;
+        let play_to_earn_fund = &ctx.accounts.play_to_earn_fund;
+        let play_to_earn_fund_auth = &ctx.accounts.play_to_earn_fund_auth;
+        let user_ggwp_wallet = &ctx.accounts.user_ggwp_wallet;
+        let token_program = &ctx.accounts.token_program;
+
+        require!(
+            index < claim_campaign.max_claims,
+            RewardDistributionError::InvalidClaimIndex
+        );
+        require!(
+            !utils::is_claimed(&claim_campaign.claimed_bitmap, index),
+            RewardDistributionError::AlreadyClaimed
+        );
+        require!(
+            utils::verify_merkle_proof(
+                &proof,
+                &claim_campaign.merkle_root,
+                utils::get_claim_leaf(index, ctx.accounts.user.key, amount),
+            ),
+            RewardDistributionError::InvalidMerkleProof
+        );
+
+        let claimed_amount = claim_campaign
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(RewardDistributionError::Overflow)?;
+        require_gte!(
+            claim_campaign.total_amount,
+            claimed_amount,
+            RewardDistributionError::CampaignAmountExceeded
+        );
+        claim_campaign.claimed_amount = claimed_amount;
+        utils::set_claimed(&mut claim_campaign.claimed_bitmap, index);
+
+        let seeds = &[
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.to_account_info().key.as_ref(),
+            &[reward_distribution_info.play_to_earn_fund_auth_bump],
+        ];
+        let signer = &[&seeds[..]];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    authority: play_to_earn_fund_auth.to_account_info(),
+                    from: play_to_earn_fund.to_account_info(),
+                    to: user_ggwp_wallet.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// Transfer authority can send GGWP to the user vesting escrow.
+    /// The escrow releases it linearly over the vesting duration.
+    pub fn transfer_vested(ctx: Context<TransferVested>, amount: u64) -> Result<()> {
+        let reward_distribution_info = &ctx.accounts.reward_distribution_info;
+        let authority = &ctx.accounts.authority;
+        let play_to_earn_fund = &ctx.accounts.play_to_earn_fund;
+        let play_to_earn_fund_auth = &ctx.accounts.play_to_earn_fund_auth;
+        let vesting_escrow = &mut ctx.accounts.vesting_escrow;
+        let vesting_escrow_wallet = &ctx.accounts.vesting_escrow_wallet;
+        let token_program = &ctx.accounts.token_program;
+
+        if !reward_distribution_info
+            .transfer_auth_list
+            .contains(authority.key)
+        {
+            msg!("Invalid transfer authority");
+            return Err(RewardDistributionError::InvalidTransferAuthority.into());
+        }
+        require!(
+            reward_distribution_info.vesting_duration > 0,
+            RewardDistributionError::VestingDisabled
+        );
+
+        let clock = Clock::get()?;
+        utils::spend_allowance(
+            &mut ctx.accounts.transfer_allowance,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        // New escrow
+        if vesting_escrow.user == Pubkey::default() {
+            vesting_escrow.reward_distribution_info = reward_distribution_info.key();
+            vesting_escrow.user = ctx.accounts.user.key();
+            vesting_escrow.bump = ctx.bumps["vesting_escrow"];
+            vesting_escrow.wallet_bump = ctx.bumps["vesting_escrow_wallet"];
+        }
+        utils::deposit_vesting(
+            vesting_escrow,
+            amount,
+            clock.unix_timestamp,
+            reward_distribution_info.vesting_duration,
+        )?;
+
+        let seeds = &[
+            PLAY_TO_EARN_FUND_AUTH_SEED.as_bytes(),
+            reward_distribution_info.to_account_info().key.as_ref(),
+            &[reward_distribution_info.play_to_earn_fund_auth_bump],
+        ];
+        let signer = &[&seeds[..]];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    authority: play_to_earn_fund_auth.to_account_info(),
+                    from: play_to_earn_fund.to_account_info(),
+                    to: vesting_escrow_wallet.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    /// User claims the vested amount from the escrow.
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        let reward_distribution_info = &ctx.accounts.reward_distribution_info;
+        let user = &ctx.accounts.user;
+        let vesting_escrow = &mut ctx.accounts.vesting_escrow;
+        let vesting_escrow_wallet = &ctx.accounts.vesting_escrow_wallet;
+        let user_ggwp_wallet = &ctx.accounts.user_ggwp_wallet;
+        let token_program = &ctx.accounts.token_program;
+
+        let vested_amount =
+            utils::calc_vested_amount(vesting_escrow, Clock::get()?.unix_timestamp)?;
+        let amount = vested_amount
+            .checked_sub(vesting_escrow.claimed_amount)
+            .ok_or(RewardDistributionError::Overflow)?;
+        require!(amount > 0, RewardDistributionError::NothingToClaim);
+        vesting_escrow.claimed_amount = vested_amount;
+
+        let reward_distribution_info_key = reward_distribution_info.key();
+        let user_key = user.key();
+        let seeds = &[
+            VESTING_ESCROW_SEED.as_bytes(),
+            reward_distribution_info_key.as_ref(),
+            user_key.as_ref(),
+            &[vesting_escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    authority: vesting_escrow.to_account_info(),
+                    from: vesting_escrow_wallet.to_account_info(),
+                    to: user_ggwp_wallet.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
 }
@@ -5,6 +5,14 @@ pub const MAX_TRANSFER_AUTH_LIST: usize = 6;
 const MAX_TRANSFER_AUTH_LIST_LEN: usize = MAX_TRANSFER_AUTH_LIST * 32;
 
 pub const PLAY_TO_EARN_FUND_AUTH_SEED: &str = "play_to_earn_fund_auth";
+pub const TRANSFER_ALLOWANCE_SEED: &str = "transfer_allowance";
+pub const VESTING_ESCROW_SEED: &str = "vesting_escrow";
+pub const VESTING_ESCROW_WALLET_SEED: &str = "vesting_escrow_wallet";
+
+pub const MAX_CAMPAIGN_CLAIMS: u32 = 64 * 1024;
+// Vested transfers are grouped into tranches starting at the vesting duration fractions
+pub const VESTING_TRANCHE_BUCKETS: i64 = 32;
+pub const MAX_VESTING_TRANCHES: usize = VESTING_TRANCHE_BUCKETS as usize + 1;
 
 #[account]
 #[derive(Default, Debug)]
@@ -16,6 +24,8 @@ pub struct RewardDistributionInfo {
     pub play_to_earn_fund: Pubkey,
     pub play_to_earn_fund_auth_bump: u8,
     pub transfer_auth_list: Vec<Pubkey>,
+    // Vested transfers are disabled when zero
+    pub vesting_duration: i64,
 }
 
 impl RewardDistributionInfo {
@@ -23,5 +33,99 @@ impl RewardDistributionInfo {
         32 + 32 + // admin, update auth pks
         32 + // ggwp token mint
         32 + 1 + // accumulative fund + auth bump
-        MAX_TRANSFER_AUTH_LIST_LEN;
+        MAX_TRANSFER_AUTH_LIST_LEN +
+        8; // vesting duration
+}
+
+/// Budget of the transfer authority per window.
+/// Used amount is reset when the window is passed.
+#[account]
+#[derive(Default, Debug)]
+pub struct TransferAllowance {
+    pub reward_distribution_info: Pubkey,
+    pub authority: Pubkey,
+
+    pub limit: u64,
+    pub window: i64,
+    pub window_start: i64,
+    pub used: u64,
+}
+
+impl TransferAllowance {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        32 + // reward distribution info pk
+        32 + // transfer authority pk
+        8 + // limit per window
+        8 + // window in seconds
+        8 + // current window start timestamp
+        8; // amount used in the current window
+}
+
+/// Rewards campaign claimed by users with the Merkle proof of (index, user, amount) leaf.
+#[account]
+#[derive(Default, Debug)]
+pub struct ClaimCampaign {
+    pub reward_distribution_info: Pubkey,
+    pub authority: Pubkey,
+
+    pub merkle_root: [u8; 32],
+    pub max_claims: u32,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    // Bit per leaf index
+    pub claimed_bitmap: Vec<u8>,
+}
+
+impl ClaimCampaign {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        32 + // reward distribution info pk
+        32 + // creator authority pk
+        32 + // merkle root
+        4 + // max claims
+        8 + // total amount
+        8 + // claimed amount
+        4; // claimed bitmap len
+
+    pub fn bitmap_len(max_claims: u32) -> usize {
+        (max_claims as usize).div_ceil(8)
+    }
+}
+
+/// Vested rewards of the user.
+/// Every transfer vests linearly over its own tranche schedule.
+#[account]
+#[derive(Default, Debug)]
+pub struct VestingEscrow {
+    pub reward_distribution_info: Pubkey,
+    pub user: Pubkey,
+    pub bump: u8,
+    pub wallet_bump: u8,
+
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    // Amount of the fully vested tranches removed from the list
+    pub released_amount: u64,
+    pub tranches: Vec<VestingTranche>,
+}
+
+impl VestingEscrow {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        32 + // reward distribution info pk
+        32 + // user pk
+        1 + 1 + // escrow + wallet bumps
+        8 + // total deposited amount
+        8 + // claimed amount
+        8 + // released amount
+        4 + VestingTranche::LEN * MAX_VESTING_TRANCHES; // vesting tranches
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct VestingTranche {
+    pub amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+impl VestingTranche {
+    pub const LEN: usize = 8 + 8 + 8;
 }
@@ -0,0 +1,397 @@
+use crate::error::RewardDistributionError;
+use crate::state::{
+    TransferAllowance, VestingEscrow, VestingTranche, MAX_VESTING_TRANCHES, VESTING_TRANCHE_BUCKETS,
+};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{clock::UnixTimestamp, keccak},
+};
+
+/// Spend the amount from the transfer allowance.
+/// The new window starts from the current time when the previous one is passed.
+pub fn spend_allowance(
+    allowance: &mut TransferAllowance,
+    amount: u64,
+    current_time: UnixTimestamp,
+) -> Result<()> {
+    let window_end = allowance
+        .window_start
+        .checked_add(allowance.window)
+        .ok_or(RewardDistributionError::Overflow)?;
+    if current_time >= window_end {
+        allowance.window_start = current_time;
+        allowance.used = 0;
+    }
+
+    let used = allowance
+        .used
+        .checked_add(amount)
+        .ok_or(RewardDistributionError::Overflow)?;
+    require_gte!(
+        allowance.limit,
+        used,
+        RewardDistributionError::AllowanceExceeded
+    );
+    allowance.used = used;
+
+    Ok(())
+}
+
+/// Get the Merkle leaf of the user claim.
+pub fn get_claim_leaf(index: u32, user: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[&index.to_le_bytes(), user.as_ref(), &amount.to_le_bytes()]).0
+}
+
+/// Hash of the sorted pair, so proofs don't need the node positions.
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak::hashv(&[a, b]).0
+    } else {
+        keccak::hashv(&[b, a]).0
+    }
+}
+
+/// Checks the leaf is in the tree with the root.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling))
+        == *root
+}
+
+/// Get the tree levels from the leaves to the root.
+/// The last node of the odd level is moved to the next level as is.
+fn get_merkle_levels(leaves: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves.to_vec()];
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let level = levels.last().unwrap();
+        let next_level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next_level);
+    }
+    levels
+}
+
+/// Get the Merkle root of the leaves.
+pub fn get_merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    get_merkle_levels(leaves)
+        .last()
+        .and_then(|level| level.first().copied())
+        .unwrap_or_default()
+}
+
+/// Get the Merkle proof of the leaf by index.
+pub fn get_merkle_proof(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+    let mut proof = vec![];
+    let mut index = index;
+    for level in get_merkle_levels(leaves) {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    proof
+}
+
+/// Checks the claim bit of the index.
+pub fn is_claimed(bitmap: &[u8], index: u32) -> bool {
+    bitmap[index as usize / 8] & (1 << (index % 8)) != 0
+}
+
+/// Sets the claim bit of the index.
+pub fn set_claimed(bitmap: &mut [u8], index: u32) {
+    bitmap[index as usize / 8] |= 1 << (index % 8);
+}
+
+/// Amount of the tranche vested linearly by the time.
+pub fn calc_tranche_vested_amount(
+    tranche: &VestingTranche,
+    current_time: UnixTimestamp,
+) -> Result<u64> {
+    if current_time >= tranche.end_time {
+        return Ok(tranche.amount);
+    }
+    if current_time <= tranche.start_time {
+        return Ok(0);
+    }
+
+    let vested_amount = (tranche.amount as u128)
+        .checked_mul((current_time - tranche.start_time) as u128)
+        .ok_or(RewardDistributionError::Overflow)?
+        / (tranche.end_time - tranche.start_time) as u128;
+    Ok(vested_amount as u64)
+}
+
+/// Amount vested by the time: released tranches plus the vested part of every tranche.
+pub fn calc_vested_amount(escrow: &VestingEscrow, current_time: UnixTimestamp) -> Result<u64> {
+    let mut vested_amount = escrow.released_amount;
+    for tranche in escrow.tranches.iter() {
+        vested_amount = vested_amount
+            .checked_add(calc_tranche_vested_amount(tranche, current_time)?)
+            .ok_or(RewardDistributionError::Overflow)?;
+    }
+    Ok(vested_amount)
+}
+
+/// Deposit the amount to the escrow as the tranche vesting over the duration.
+/// The tranche starts at the next duration fraction, so the amount never vests before
+/// its arrival and the earlier tranches are not changed.
+/// Fully vested tranches are released to keep the list bounded. When the list is still full,
+/// e.g. after the duration change, the unvested rest of the last tranche vests with the amount.
+pub fn deposit_vesting(
+    escrow: &mut VestingEscrow,
+    amount: u64,
+    current_time: UnixTimestamp,
+    duration: i64,
+) -> Result<()> {
+    let bucket = ((duration + VESTING_TRANCHE_BUCKETS - 1) / VESTING_TRANCHE_BUCKETS).max(1);
+    let start_time = current_time
+        .checked_add(bucket - 1)
+        .map(|time| time / bucket * bucket)
+        .ok_or(RewardDistributionError::Overflow)?;
+    let end_time = start_time
+        .checked_add(duration)
+        .ok_or(RewardDistributionError::Overflow)?;
+
+    for tranche in escrow
+        .tranches
+        .iter()
+        .filter(|tranche| tranche.end_time <= current_time)
+    {
+        escrow.released_amount = escrow
+            .released_amount
+            .checked_add(tranche.amount)
+            .ok_or(RewardDistributionError::Overflow)?;
+    }
+    escrow
+        .tranches
+        .retain(|tranche| tranche.end_time > current_time);
+
+    escrow.total_amount = escrow
+        .total_amount
+        .checked_add(amount)
+        .ok_or(RewardDistributionError::Overflow)?;
+    let is_full = escrow.tranches.len() >= MAX_VESTING_TRANCHES;
+    match escrow.tranches.last_mut() {
+        Some(last) if last.start_time == start_time && last.end_time == end_time => {
+            last.amount = last
+                .amount
+                .checked_add(amount)
+                .ok_or(RewardDistributionError::Overflow)?;
+        }
+        Some(last) if is_full => {
+            // Vested part is released, so the vested amount stays the same
+            let vested_amount = calc_tranche_vested_amount(last, current_time)?;
+            escrow.released_amount = escrow
+                .released_amount
+                .checked_add(vested_amount)
+                .ok_or(RewardDistributionError::Overflow)?;
+            last.amount = (last.amount - vested_amount)
+                .checked_add(amount)
+                .ok_or(RewardDistributionError::Overflow)?;
+            last.start_time = last.start_time.max(start_time);
+            last.end_time = last.end_time.max(end_time);
+        }
+        _ => {
+            escrow.tranches.push(VestingTranche {
+                amount,
+                start_time,
+                end_time,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ClaimCampaign;
+
+    #[test]
+    pub fn test_spend_allowance() {
+        let time = 1660032700;
+        let mut allowance = TransferAllowance {
+            limit: 1000,
+            window: 3600,
+            ..Default::default()
+        };
+
+        // The first window starts on the first spend
+        spend_allowance(&mut allowance, 400, time).unwrap();
+        assert_eq!(allowance.window_start, time);
+        assert_eq!(allowance.used, 400);
+        spend_allowance(&mut allowance, 600, time + 3599).unwrap();
+        assert_eq!(allowance.used, 1000);
+        assert!(spend_allowance(&mut allowance, 1, time + 3599).is_err());
+        assert_eq!(allowance.used, 1000);
+        spend_allowance(&mut allowance, 0, time + 3599).unwrap();
+
+        // Usage is reset in the next window
+        spend_allowance(&mut allowance, 1, time + 3600).unwrap();
+        assert_eq!(allowance.window_start, time + 3600);
+        assert_eq!(allowance.used, 1);
+        assert!(spend_allowance(&mut allowance, 1000, time + 3700).is_err());
+        spend_allowance(&mut allowance, 1000, time + 10000).unwrap();
+        assert_eq!(allowance.window_start, time + 10000);
+        assert_eq!(allowance.used, 1000);
+
+        allowance.limit = u64::MAX;
+        assert!(spend_allowance(&mut allowance, u64::MAX, time + 10000).is_err());
+    }
+
+    #[test]
+    pub fn test_merkle_proof() {
+        let users: Vec<Pubkey> = (0..7).map(|_| Pubkey::new_unique()).collect();
+        for len in 1..=users.len() {
+            let leaves: Vec<[u8; 32]> = users[..len]
+                .iter()
+                .enumerate()
+                .map(|(i, user)| get_claim_leaf(i as u32, user, (i as u64 + 1) * 1000))
+                .collect();
+            let root = get_merkle_root(&leaves);
+            for (i, user) in users[..len].iter().enumerate() {
+                let proof = get_merkle_proof(&leaves, i);
+                let amount = (i as u64 + 1) * 1000;
+                assert!(verify_merkle_proof(
+                    &proof,
+                    &root,
+                    get_claim_leaf(i as u32, user, amount)
+                ));
+                // Another amount, index or user
+                assert!(!verify_merkle_proof(
+                    &proof,
+                    &root,
+                    get_claim_leaf(i as u32, user, amount + 1)
+                ));
+                assert!(!verify_merkle_proof(
+                    &proof,
+                    &root,
+                    get_claim_leaf(i as u32 + 1, user, amount)
+                ));
+                assert!(!verify_merkle_proof(
+                    &proof,
+                    &root,
+                    get_claim_leaf(i as u32, &Pubkey::new_unique(), amount)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_claimed_bitmap() {
+        let mut bitmap = vec![0u8; ClaimCampaign::bitmap_len(20)];
+        assert_eq!(bitmap.len(), 3);
+        assert!(!is_claimed(&bitmap, 0));
+        set_claimed(&mut bitmap, 0);
+        set_claimed(&mut bitmap, 9);
+        set_claimed(&mut bitmap, 19);
+        assert!(is_claimed(&bitmap, 0));
+        assert!(!is_claimed(&bitmap, 1));
+        assert!(!is_claimed(&bitmap, 8));
+        assert!(is_claimed(&bitmap, 9));
+        assert!(is_claimed(&bitmap, 19));
+        assert_eq!(bitmap, vec![0b1, 0b10, 0b1000]);
+    }
+
+    #[test]
+    pub fn test_vesting() {
+        let time = 1660032000;
+        let mut escrow = VestingEscrow::default();
+
+        deposit_vesting(&mut escrow, 1000, time, 128).unwrap();
+        assert_eq!(
+            escrow.tranches,
+            vec![VestingTranche {
+                amount: 1000,
+                start_time: time,
+                end_time: time + 128,
+            }]
+        );
+        assert_eq!(calc_vested_amount(&escrow, time).unwrap(), 0);
+        assert_eq!(calc_vested_amount(&escrow, time + 32).unwrap(), 250);
+        assert_eq!(calc_vested_amount(&escrow, time + 127).unwrap(), 992);
+        assert_eq!(calc_vested_amount(&escrow, time + 128).unwrap(), 1000);
+        assert_eq!(calc_vested_amount(&escrow, time + 1000).unwrap(), 1000);
+
+        // Deposit half a duration later does not delay the first one
+        deposit_vesting(&mut escrow, 1000, time + 64, 128).unwrap();
+        assert_eq!(escrow.total_amount, 2000);
+        assert_eq!(escrow.tranches.len(), 2);
+        assert_eq!(calc_vested_amount(&escrow, time + 64).unwrap(), 500);
+        assert_eq!(calc_vested_amount(&escrow, time + 128).unwrap(), 1500);
+        assert_eq!(calc_vested_amount(&escrow, time + 192).unwrap(), 2000);
+
+        // Deposit between the duration fractions starts at the next one
+        deposit_vesting(&mut escrow, 400, time + 65, 128).unwrap();
+        assert_eq!(
+            escrow.tranches[2],
+            VestingTranche {
+                amount: 400,
+                start_time: time + 68,
+                end_time: time + 196,
+            }
+        );
+        // Deposit in the same duration fraction joins the last tranche
+        deposit_vesting(&mut escrow, 100, time + 66, 128).unwrap();
+        assert_eq!(escrow.tranches.len(), 3);
+        assert_eq!(escrow.tranches[2].amount, 500);
+        assert_eq!(calc_vested_amount(&escrow, time + 68).unwrap(), 531 + 31);
+
+        // Fully vested tranches are released on the deposit
+        deposit_vesting(&mut escrow, 300, time + 300, 30).unwrap();
+        assert_eq!(escrow.released_amount, 2500);
+        assert_eq!(escrow.total_amount, 2800);
+        assert_eq!(escrow.tranches.len(), 1);
+        assert_eq!(calc_vested_amount(&escrow, time + 310).unwrap(), 2600);
+        assert_eq!(calc_vested_amount(&escrow, time + 330).unwrap(), 2800);
+    }
+
+    #[test]
+    pub fn test_vesting_tranches_bound() {
+        let time = 1660032000;
+        let duration = 1000;
+        let mut escrow = VestingEscrow::default();
+
+        // Deposit every second keeps at most one tranche per duration fraction
+        for i in 0..10 * duration {
+            deposit_vesting(&mut escrow, 1, time + i, duration).unwrap();
+            assert!(escrow.tranches.len() <= MAX_VESTING_TRANCHES);
+        }
+        assert_eq!(escrow.total_amount, 10 * duration as u64);
+        assert!(calc_vested_amount(&escrow, time + 10 * duration).unwrap() < escrow.total_amount);
+        assert_eq!(
+            calc_vested_amount(&escrow, time + 11 * duration + 32).unwrap(),
+            escrow.total_amount
+        );
+
+        // Duration change keeps the live tranches of the old duration fractions
+        let mut escrow = VestingEscrow::default();
+        for i in 0..MAX_VESTING_TRANCHES as i64 {
+            deposit_vesting(&mut escrow, 1000, time + i * 990, 32_000).unwrap();
+        }
+        assert_eq!(escrow.tranches.len(), MAX_VESTING_TRANCHES);
+        for i in 0..10 * duration {
+            let current_time = time + 31_700 + i;
+            let vested_amount = calc_vested_amount(&escrow, current_time).unwrap();
+            deposit_vesting(&mut escrow, 1, current_time, duration).unwrap();
+            assert!(escrow.tranches.len() <= MAX_VESTING_TRANCHES);
+            assert_eq!(
+                calc_vested_amount(&escrow, current_time).unwrap(),
+                vested_amount
+            );
+        }
+        assert_eq!(escrow.total_amount, 33_000 + 10 * duration as u64);
+        assert_eq!(
+            calc_vested_amount(&escrow, time + 33_000 + 32_000 + 11 * duration).unwrap(),
+            escrow.total_amount
+        );
+    }
+}
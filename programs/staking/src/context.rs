@@ -1,8 +1,12 @@
 use crate::error::StakingError;
+use crate::metadata::{self, EDITION_SEED, METADATA_SEED};
 use crate::state::{
-    StakingInfo, UserInfo, STAKING_FUND_AUTH_SEED, TREASURY_AUTH_SEED, USER_INFO_SEED,
+    AprSchedule, EpochHistory, Position, StakingInfo, UserInfo, APR_SCHEDULE_SEED,
+    EPOCH_HISTORY_SEED, POSITION_NFT_AUTH_SEED, POSITION_NFT_MINT_SEED, POSITION_SEED,
+    STAKING_FUND_AUTH_SEED, TREASURY_AUTH_SEED, USER_INFO_SEED,
 };
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
 #[derive(Accounts)]
@@ -11,6 +15,22 @@ pub struct Initialize<'info> {
     pub admin: Signer<'info>,
     #[account(init, payer = admin, space = StakingInfo::LEN)]
     pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(init, payer = admin, space = EpochHistory::LEN,
+        seeds = [
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_history: Box<Account<'info, EpochHistory>>,
+    #[account(init, payer = admin, space = AprSchedule::LEN,
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
 
     /// CHECK: Treasury auth PDA
     #[account(
@@ -63,12 +83,79 @@ pub struct UpdateParam<'info> {
     pub staking_info: Account<'info, StakingInfo>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateLockupOptions<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAprSchedule<'info> {
+    pub authority: Signer<'info>,
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceEpoch<'info> {
+    #[account(mut)]
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_history: Box<Account<'info, EpochHistory>>,
+    #[account(
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
+}
+
 #[derive(Accounts)]
 pub struct Stake<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(mut)]
     pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_history: Box<Account<'info, EpochHistory>>,
+    #[account(
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
 
     #[account(init_if_needed, payer = user, space = UserInfo::LEN,
         seeds = [
@@ -79,6 +166,64 @@ pub struct Stake<'info> {
         bump
     )]
     pub user_info: Box<Account<'info, UserInfo>>,
+    #[account(init, payer = user, space = Position::LEN,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            user.key().as_ref(),
+            user_info.positions_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(init, payer = user,
+        seeds = [
+            POSITION_NFT_MINT_SEED.as_bytes(),
+            position.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = position_nft_auth,
+    )]
+    pub position_nft_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Position NFT mint and metadata update auth PDA
+    #[account(
+        seeds = [
+            POSITION_NFT_AUTH_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub position_nft_auth: UncheckedAccount<'info>,
+    #[account(init, payer = user,
+        associated_token::mint = position_nft_mint,
+        associated_token::authority = user,
+    )]
+    pub user_position_nft_wallet: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Position NFT metadata PDA, initialized by token metadata program
+    #[account(mut,
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            metadata::ID.as_ref(),
+            position_nft_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = metadata::ID,
+    )]
+    pub position_nft_metadata: UncheckedAccount<'info>,
+    /// CHECK: Position NFT master edition PDA, initialized by token metadata program
+    #[account(mut,
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            metadata::ID.as_ref(),
+            position_nft_mint.key().as_ref(),
+            EDITION_SEED.as_bytes(),
+        ],
+        bump,
+        seeds::program = metadata::ID,
+    )]
+    pub position_nft_edition: UncheckedAccount<'info>,
 
     #[account(mut,
         constraint = user_ggwp_wallet.mint == staking_info.ggwp_token
@@ -100,9 +245,172 @@ pub struct Stake<'info> {
     )]
     pub accumulative_fund: Box<Account<'info, TokenAccount>>,
 
+    #[account(
+        constraint = staking_fund.key() == staking_info.staking_fund
+        @StakingError::InvalidStakingFundPK,
+    )]
+    pub staking_fund: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    /// CHECK: Metaplex token metadata program
+    #[account(address = metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct StakeFor<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    /// CHECK: Owner of the new position
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_history: Box<Account<'info, EpochHistory>>,
+    #[account(
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
+
+    #[account(init_if_needed, payer = funder, space = UserInfo::LEN,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            beneficiary.key().as_ref(),
+        ],
+        bump
+    )]
+    pub user_info: Box<Account<'info, UserInfo>>,
+    #[account(init, payer = funder, space = Position::LEN,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            beneficiary.key().as_ref(),
+            user_info.positions_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(init, payer = funder,
+        seeds = [
+            POSITION_NFT_MINT_SEED.as_bytes(),
+            position.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = position_nft_auth,
+    )]
+    pub position_nft_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Position NFT mint and metadata update auth PDA
+    #[account(
+        seeds = [
+            POSITION_NFT_AUTH_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub position_nft_auth: UncheckedAccount<'info>,
+    #[account(init, payer = funder,
+        associated_token::mint = position_nft_mint,
+        associated_token::authority = beneficiary,
+    )]
+    pub user_position_nft_wallet: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Position NFT metadata PDA, initialized by token metadata program
+    #[account(mut,
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            metadata::ID.as_ref(),
+            position_nft_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = metadata::ID,
+    )]
+    pub position_nft_metadata: UncheckedAccount<'info>,
+    /// CHECK: Position NFT master edition PDA, initialized by token metadata program
+    #[account(mut,
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            metadata::ID.as_ref(),
+            position_nft_mint.key().as_ref(),
+            EDITION_SEED.as_bytes(),
+        ],
+        bump,
+        seeds::program = metadata::ID,
+    )]
+    pub position_nft_edition: UncheckedAccount<'info>,
+
+    #[account(mut,
+        constraint = funder_ggwp_wallet.mint == staking_info.ggwp_token
+        @StakingError::InvalidUserGGWPWalletMint,
+        constraint = funder_ggwp_wallet.owner == funder.key()
+        @StakingError::InvalidUserGGWPWalletOwner,
+    )]
+    pub funder_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut,
+        constraint = treasury.key() == staking_info.treasury
+        @StakingError::InvalidTreasuryPK,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut,
+        constraint = accumulative_fund.key() == staking_info.accumulative_fund
+        @StakingError::InvalidAccumulativeFundPK,
+    )]
+    pub accumulative_fund: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = staking_fund.key() == staking_info.staking_fund
+        @StakingError::InvalidStakingFundPK,
+    )]
+    pub staking_fund: Box<Account<'info, TokenAccount>>,
+
     // Misc.
+    /// CHECK: Metaplex token metadata program
+    #[account(address = metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Accounts of the new position staked by funder for owner.
+pub struct StakePosition<'a, 'info> {
+    pub funder: &'a Signer<'info>,
+    pub owner: Pubkey,
+    pub staking_info: &'a mut Account<'info, StakingInfo>,
+    pub epoch_history: &'a mut Account<'info, EpochHistory>,
+    pub apr_schedule: &'a Account<'info, AprSchedule>,
+    pub user_info: &'a mut Account<'info, UserInfo>,
+    pub position: &'a mut Account<'info, Position>,
+    pub position_nft_mint: &'a Account<'info, Mint>,
+    pub position_nft_auth: &'a UncheckedAccount<'info>,
+    pub user_position_nft_wallet: &'a Account<'info, TokenAccount>,
+    pub position_nft_metadata: &'a UncheckedAccount<'info>,
+    pub position_nft_edition: &'a UncheckedAccount<'info>,
+    pub funder_ggwp_wallet: &'a Account<'info, TokenAccount>,
+    pub treasury: &'a Account<'info, TokenAccount>,
+    pub accumulative_fund: &'a Account<'info, TokenAccount>,
+    pub staking_fund: &'a Account<'info, TokenAccount>,
+    pub token_metadata_program: &'a UncheckedAccount<'info>,
+    pub system_program: &'a Program<'info, System>,
+    pub token_program: &'a Program<'info, Token>,
 }
 
 #[derive(Accounts)]
@@ -111,6 +419,22 @@ pub struct Withdraw<'info> {
     pub user: Signer<'info>,
     #[account(mut)]
     pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_history: Box<Account<'info, EpochHistory>>,
+    #[account(
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
 
     #[account(init_if_needed, payer = user, space = UserInfo::LEN,
         seeds = [
@@ -121,6 +445,31 @@ pub struct Withdraw<'info> {
         bump
     )]
     pub user_info: Box<Account<'info, UserInfo>>,
+    #[account(mut, close = user,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            position.user.as_ref(),
+            position.index.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(mut,
+        constraint = position_nft_mint.key() == position.nft_mint
+        @StakingError::InvalidPositionNFTMint,
+    )]
+    pub position_nft_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        constraint = user_position_nft_wallet.mint == position.nft_mint
+        @StakingError::InvalidPositionNFTMint,
+        constraint = user_position_nft_wallet.owner == user.key()
+        @StakingError::InvalidPositionNFTWalletOwner,
+        constraint = user_position_nft_wallet.amount == 1
+        @StakingError::NotPositionHolder,
+    )]
+    pub user_position_nft_wallet: Box<Account<'info, TokenAccount>>,
 
     #[account(mut,
         constraint = user_ggwp_wallet.mint == staking_info.ggwp_token
@@ -163,6 +512,8 @@ pub struct Withdraw<'info> {
     pub accumulative_fund: Box<Account<'info, TokenAccount>>,
 
     #[account(mut,
+        constraint = staking_fund.key() == staking_info.staking_fund
+        @StakingError::InvalidStakingFundPK,
         constraint = staking_fund.mint == staking_info.ggwp_token
         @StakingError::InvalidStakingFundMint,
         constraint = staking_fund.owner == staking_fund_auth.key()
@@ -174,3 +525,232 @@ pub struct Withdraw<'info> {
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_history: Box<Account<'info, EpochHistory>>,
+    #[account(
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
+
+    #[account(init_if_needed, payer = user, space = UserInfo::LEN,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub user_info: Box<Account<'info, UserInfo>>,
+    #[account(mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            position.user.as_ref(),
+            position.index.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(
+        constraint = user_position_nft_wallet.mint == position.nft_mint
+        @StakingError::InvalidPositionNFTMint,
+        constraint = user_position_nft_wallet.owner == user.key()
+        @StakingError::InvalidPositionNFTWalletOwner,
+        constraint = user_position_nft_wallet.amount == 1
+        @StakingError::NotPositionHolder,
+    )]
+    pub user_position_nft_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut,
+        constraint = user_ggwp_wallet.mint == staking_info.ggwp_token
+        @StakingError::InvalidUserGGWPWalletMint,
+        constraint = user_ggwp_wallet.owner == user.key()
+        @StakingError::InvalidUserGGWPWalletOwner,
+    )]
+    pub user_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Staking fund auth PDA
+    #[account(
+        seeds = [
+            STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub staking_fund_auth: UncheckedAccount<'info>,
+
+    #[account(mut,
+        constraint = staking_fund.key() == staking_info.staking_fund
+        @StakingError::InvalidStakingFundPK,
+        constraint = staking_fund.mint == staking_info.ggwp_token
+        @StakingError::InvalidStakingFundMint,
+        constraint = staking_fund.owner == staking_fund_auth.key()
+        @StakingError::InvalidStakingFundOwner,
+    )]
+    pub staking_fund: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    pub user: Signer<'info>,
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            position.user.as_ref(),
+            position.index.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(
+        constraint = user_position_nft_wallet.mint == position.nft_mint
+        @StakingError::InvalidPositionNFTMint,
+        constraint = user_position_nft_wallet.owner == user.key()
+        @StakingError::InvalidPositionNFTWalletOwner,
+        constraint = user_position_nft_wallet.amount == 1
+        @StakingError::NotPositionHolder,
+    )]
+    pub user_position_nft_wallet: Box<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct Compound<'info> {
+    #[account(mut)]
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut,
+        seeds = [
+            EPOCH_HISTORY_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub epoch_history: Box<Account<'info, EpochHistory>>,
+    #[account(
+        seeds = [
+            APR_SCHEDULE_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump
+    )]
+    pub apr_schedule: Box<Account<'info, AprSchedule>>,
+
+    #[account(mut,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            position.user.as_ref(),
+        ],
+        bump
+    )]
+    pub staker_info: Box<Account<'info, UserInfo>>,
+    #[account(mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            position.user.as_ref(),
+            position.index.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    /// CHECK: Staking fund auth PDA
+    #[account(
+        seeds = [
+            STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub staking_fund_auth: UncheckedAccount<'info>,
+
+    #[account(mut,
+        constraint = staking_fund.key() == staking_info.staking_fund
+        @StakingError::InvalidStakingFundPK,
+        constraint = staking_fund.mint == staking_info.ggwp_token
+        @StakingError::InvalidStakingFundMint,
+        constraint = staking_fund.owner == staking_fund_auth.key()
+        @StakingError::InvalidStakingFundOwner,
+    )]
+    pub staking_fund: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut,
+        constraint = treasury.key() == staking_info.treasury
+        @StakingError::InvalidTreasuryPK,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimIOU<'info> {
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub staking_info: Box<Account<'info, StakingInfo>>,
+
+    #[account(mut,
+        seeds = [
+            USER_INFO_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub user_info: Box<Account<'info, UserInfo>>,
+
+    #[account(mut,
+        constraint = user_ggwp_wallet.mint == staking_info.ggwp_token
+        @StakingError::InvalidUserGGWPWalletMint,
+        constraint = user_ggwp_wallet.owner == user.key()
+        @StakingError::InvalidUserGGWPWalletOwner,
+    )]
+    pub user_ggwp_wallet: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Staking fund auth PDA
+    #[account(
+        seeds = [
+            STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub staking_fund_auth: UncheckedAccount<'info>,
+
+    #[account(mut,
+        constraint = staking_fund.key() == staking_info.staking_fund
+        @StakingError::InvalidStakingFundPK,
+        constraint = staking_fund.mint == staking_info.ggwp_token
+        @StakingError::InvalidStakingFundMint,
+        constraint = staking_fund.owner == staking_fund_auth.key()
+        @StakingError::InvalidStakingFundOwner,
+    )]
+    pub staking_fund: Box<Account<'info, TokenAccount>>,
+
+    // Misc.
+    pub token_program: Program<'info, Token>,
+}
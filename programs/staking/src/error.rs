@@ -49,4 +49,38 @@ pub enum StakingError {
     AdditionalStakeNotAllowed, // 6018
     #[msg("Nothing to withdraw")]
     NothingToWithdraw, // 6019
+    #[msg("Epoch APR not recorded")]
+    EpochNotRecorded, // 6020
+    #[msg("Nothing to claim")]
+    NothingToClaim, // 6021
+    #[msg("Invalid staking fund PK")]
+    InvalidStakingFundPK, // 6022
+    #[msg("Staking fund can't cover reward liabilities")]
+    InsufficientStakingFund, // 6023
+    #[msg("Invalid APR schedule")]
+    InvalidAPRSchedule, // 6024
+    #[msg("APR can be scheduled for future epochs only")]
+    InvalidAPRScheduleEpoch, // 6025
+    #[msg("Invalid position NFT mint PK")]
+    InvalidPositionNFTMint, // 6026
+    #[msg("Invalid position NFT wallet owner")]
+    InvalidPositionNFTWalletOwner, // 6027
+    #[msg("Position NFT is not held by user")]
+    NotPositionHolder, // 6028
+    #[msg("Invalid lockup options")]
+    InvalidLockupOptions, // 6029
+    #[msg("Invalid lockup option")]
+    InvalidLockupOption, // 6030
+    #[msg("Position lockup is not ended")]
+    LockupNotEnded, // 6031
+    #[msg("Position is not auto-compounding")]
+    NotAutoCompoundPosition, // 6032
+    #[msg("Invalid staker info")]
+    InvalidStakerInfo, // 6033
+    #[msg("Invalid position NFT URI")]
+    InvalidPositionNFTUri, // 6034
+    #[msg("Too many reward accruals")]
+    TooManyRewardAccruals, // 6035
+    #[msg("Reward exceeds the accrued liability")]
+    InvalidRewardLiability, // 6036
 }
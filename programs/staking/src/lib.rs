@@ -1,15 +1,22 @@
 use crate::context::*;
 use crate::error::StakingError;
-use crate::state::{STAKING_FUND_AUTH_SEED, TREASURY_AUTH_SEED};
+use crate::state::{
+    LockupOption, PoolMode, UserInfo, BASE_APR_BOOST, POSITION_NFT_AUTH_SEED, POSITION_NFT_SYMBOL,
+    STAKING_FUND_AUTH_SEED, TREASURY_AUTH_SEED, USER_INFO_SEED,
+};
 use anchor_lang::prelude::*;
-use anchor_spl::token::Transfer;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{Burn, CloseAccount, MintTo, Transfer};
 
 mod context;
 mod error;
+pub mod metadata;
 pub mod state;
-mod utils;
+pub mod utils;
 
-declare_id!("ELRFw9awBQFuvvxnf3R1Xaihdy6ypWY7sH6nTNXh8EX1");
+// Position based account layouts are incompatible with the legacy staking program,
+// so the program is deployed under a new program id instead of upgrading it
+declare_id!("DfgyH3eTkyFK16Did8Dd5xjtbQENHfqzLgD5tD4F6MWJ");
 
 #[program]
 pub mod staking {
@@ -27,6 +34,8 @@ pub mod staking {
         apr_start: u8,
         apr_step: u8,
         apr_end: u8,
+        pool_mode: PoolMode,
+        position_nft_uri: String,
     ) -> Result<()> {
         require_neq!(epoch_period_days, 0, StakingError::InvalidEpochPeriodDays);
         require_neq!(min_stake_amount, 0, StakingError::InvalidMinStakeAmount);
@@ -38,6 +47,10 @@ pub mod staking {
         require_neq!(apr_start, 0, StakingError::InvalidAPR);
         require_neq!(apr_step, 0, StakingError::InvalidAPR);
         require_neq!(apr_end, 0, StakingError::InvalidAPR);
+        require!(
+            utils::is_valid_position_nft_uri(&position_nft_uri),
+            StakingError::InvalidPositionNFTUri
+        );
 
         let clock = Clock::get()?;
 
@@ -54,6 +67,17 @@ pub mod staking {
         staking_info.treasury_auth_bump = ctx.bumps["treasury_auth"];
 
         staking_info.total_staked = 0;
+        staking_info.reward_liability = 0;
+        staking_info.total_iou = 0;
+        staking_info.pool_mode = pool_mode;
+        staking_info.reward_per_share = 0;
+        staking_info.reward_reserve = 0;
+        staking_info.total_boosted_staked = 0;
+        staking_info.lockup_options = vec![LockupOption {
+            lockup_days: 0,
+            apr_boost: BASE_APR_BOOST,
+        }];
+        staking_info.reward_accruals = vec![];
         staking_info.start_time = clock.unix_timestamp;
         staking_info.epoch = 1;
         staking_info.epoch_period_days = epoch_period_days;
@@ -64,6 +88,13 @@ pub mod staking {
         staking_info.apr_start = apr_start;
         staking_info.apr_step = apr_step;
         staking_info.apr_end = apr_end;
+        staking_info.position_nft_uri = position_nft_uri;
+
+        let epoch_history = &mut ctx.accounts.epoch_history;
+        epoch_history.aprs = vec![];
+
+        let apr_schedule = &mut ctx.accounts.apr_schedule;
+        apr_schedule.aprs = utils::build_apr_schedule(apr_start, apr_step, apr_end)?;
 
         Ok(())
     }
@@ -183,78 +214,202 @@ pub mod staking {
         Ok(())
     }
 
-    /// User can stake amount of GGWP to earn extra GGWP.
-    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
-        let user = &ctx.accounts.user;
+    /// Update authority can set new position NFT metadata base URI.
+    pub fn update_position_nft_uri(
+        ctx: Context<UpdateParam>,
+        position_nft_uri: String,
+    ) -> Result<()> {
+        let authority = &ctx.accounts.authority;
         let staking_info = &mut ctx.accounts.staking_info;
-        let user_info = &mut ctx.accounts.user_info;
-        let user_ggwp_wallet = &ctx.accounts.user_ggwp_wallet;
-        let treasury = &ctx.accounts.treasury;
-        let accumulative_fund = &ctx.accounts.accumulative_fund;
-        let token_program = &ctx.accounts.token_program;
-        let clock = Clock::get()?;
 
         require!(
-            amount >= staking_info.min_stake_amount,
-            StakingError::MinStakeAmountExceeded
+            utils::is_valid_position_nft_uri(&position_nft_uri),
+            StakingError::InvalidPositionNFTUri
+        );
+        require_keys_eq!(
+            authority.key(),
+            staking_info.update_auth,
+            StakingError::AccessDenied
         );
+        staking_info.position_nft_uri = position_nft_uri;
 
-        if !user_info.is_initialized {
-            user_info.is_initialized = true;
-            user_info.amount = 0;
-            user_info.stake_time = 0;
-        }
+        Ok(())
+    }
 
-        require_eq!(user_info.amount, 0, StakingError::AdditionalStakeNotAllowed);
+    /// Update authority can set the lockup options table.
+    /// Positions keep the lockup and APR boost chosen at stake.
+    pub fn update_lockup_options(
+        ctx: Context<UpdateLockupOptions>,
+        lockup_options: Vec<LockupOption>,
+    ) -> Result<()> {
+        let authority = &ctx.accounts.authority;
+        let staking_info = &mut ctx.accounts.staking_info;
+        let apr_schedule = &ctx.accounts.apr_schedule;
 
-        // TODO: mint NFT
+        require!(
+            utils::is_valid_lockup_options(&lockup_options),
+            StakingError::InvalidLockupOptions
+        );
+        // Boosted APR overflow would fail every epoch advance
+        let max_apr_boost = lockup_options
+            .iter()
+            .map(|option| option.apr_boost)
+            .max()
+            .unwrap_or(BASE_APR_BOOST);
+        require!(
+            utils::is_valid_boosted_aprs(&apr_schedule.aprs, max_apr_boost),
+            StakingError::InvalidLockupOptions
+        );
+        require_keys_eq!(
+            authority.key(),
+            staking_info.update_auth,
+            StakingError::AccessDenied
+        );
+        staking_info.lockup_options = lockup_options;
 
-        let royalty_amount = utils::calc_royalty_amount(staking_info.royalty, amount)?;
-        msg!("Accumulative fund royalty: {}", royalty_amount);
-        // Transfer royalty into accumulative fund
-        anchor_spl::token::transfer(
-            CpiContext::new(
-                token_program.to_account_info(),
-                Transfer {
-                    from: user_ggwp_wallet.to_account_info(),
-                    to: accumulative_fund.to_account_info(),
-                    authority: user.to_account_info(),
-                },
-            ),
-            royalty_amount,
-        )?;
+        Ok(())
+    }
 
-        let amount = amount
-            .checked_sub(royalty_amount)
-            .ok_or(StakingError::Overflow)?;
+    /// Update authority can set APRs in basis points for the future epochs.
+    pub fn update_apr_schedule(
+        ctx: Context<UpdateAprSchedule>,
+        from_epoch: u64,
+        aprs: Vec<u16>,
+    ) -> Result<()> {
+        let authority = &ctx.accounts.authority;
+        let staking_info = &ctx.accounts.staking_info;
+        let apr_schedule = &mut ctx.accounts.apr_schedule;
+        let clock = Clock::get()?;
 
-        // Transfer amount into treasury
-        anchor_spl::token::transfer(
-            CpiContext::new(
-                token_program.to_account_info(),
-                Transfer {
-                    from: user_ggwp_wallet.to_account_info(),
-                    to: treasury.to_account_info(),
-                    authority: user.to_account_info(),
-                },
-            ),
-            amount,
+        require_keys_eq!(
+            authority.key(),
+            staking_info.update_auth,
+            StakingError::AccessDenied
+        );
+        require!(aprs.iter().all(|apr| *apr != 0), StakingError::InvalidAPR);
+        // Boosted APR overflow would fail every epoch advance
+        require!(
+            utils::is_valid_boosted_aprs(&aprs, utils::get_max_apr_boost(staking_info)),
+            StakingError::InvalidAPR
+        );
+
+        let (current_epoch, _) = utils::get_epoch_by_time(
+            staking_info.start_time,
+            clock.unix_timestamp,
+            staking_info.epoch_period_days,
         )?;
+        utils::set_scheduled_aprs(&mut apr_schedule.aprs, current_epoch, from_epoch, &aprs)?;
 
-        user_info.amount = amount;
-        user_info.stake_time = clock.unix_timestamp;
-        staking_info.total_staked = staking_info
-            .total_staked
-            .checked_add(amount)
-            .ok_or(StakingError::Overflow)?;
+        Ok(())
+    }
+
+    /// Anyone can move the staking to the current epoch and record the applied APR.
+    pub fn advance_epoch(ctx: Context<AdvanceEpoch>) -> Result<()> {
+        let staking_info = &mut ctx.accounts.staking_info;
+        let epoch_history = &mut ctx.accounts.epoch_history;
+        let apr_schedule = &ctx.accounts.apr_schedule;
+        let clock = Clock::get()?;
+
+        let advanced = utils::advance_epochs(
+            staking_info,
+            &apr_schedule.aprs,
+            &mut epoch_history.aprs,
+            clock.unix_timestamp,
+        )?;
+        msg!("Epochs advanced: {}", advanced);
+        msg!("Current epoch: {}", staking_info.epoch);
 
         Ok(())
     }
 
-    /// User can withdraw full amount of GGWP with extra reward.
-    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+    /// User can stake amount of GGWP to earn extra GGWP.
+    pub fn stake(
+        ctx: Context<Stake>,
+        amount: u64,
+        lockup_option: u8,
+        auto_compound: bool,
+    ) -> Result<()> {
+        let position_nft_auth_bump = ctx.bumps["position_nft_auth"];
+        let owner = ctx.accounts.user.key();
+        stake_position(
+            StakePosition {
+                funder: &ctx.accounts.user,
+                owner,
+                staking_info: &mut ctx.accounts.staking_info,
+                epoch_history: &mut ctx.accounts.epoch_history,
+                apr_schedule: &ctx.accounts.apr_schedule,
+                user_info: &mut ctx.accounts.user_info,
+                position: &mut ctx.accounts.position,
+                position_nft_mint: &ctx.accounts.position_nft_mint,
+                position_nft_auth: &ctx.accounts.position_nft_auth,
+                user_position_nft_wallet: &ctx.accounts.user_position_nft_wallet,
+                position_nft_metadata: &ctx.accounts.position_nft_metadata,
+                position_nft_edition: &ctx.accounts.position_nft_edition,
+                funder_ggwp_wallet: &ctx.accounts.user_ggwp_wallet,
+                treasury: &ctx.accounts.treasury,
+                accumulative_fund: &ctx.accounts.accumulative_fund,
+                staking_fund: &ctx.accounts.staking_fund,
+                token_metadata_program: &ctx.accounts.token_metadata_program,
+                system_program: &ctx.accounts.system_program,
+                token_program: &ctx.accounts.token_program,
+            },
+            position_nft_auth_bump,
+            amount,
+            lockup_option,
+            auto_compound,
+        )
+    }
+
+    /// Funder can stake amount of GGWP into the new position of beneficiary.
+    /// Position NFT is minted to beneficiary.
+    pub fn stake_for(
+        ctx: Context<StakeFor>,
+        amount: u64,
+        lockup_option: u8,
+        auto_compound: bool,
+    ) -> Result<()> {
+        let position_nft_auth_bump = ctx.bumps["position_nft_auth"];
+        let owner = ctx.accounts.beneficiary.key();
+        stake_position(
+            StakePosition {
+                funder: &ctx.accounts.funder,
+                owner,
+                staking_info: &mut ctx.accounts.staking_info,
+                epoch_history: &mut ctx.accounts.epoch_history,
+                apr_schedule: &ctx.accounts.apr_schedule,
+                user_info: &mut ctx.accounts.user_info,
+                position: &mut ctx.accounts.position,
+                position_nft_mint: &ctx.accounts.position_nft_mint,
+                position_nft_auth: &ctx.accounts.position_nft_auth,
+                user_position_nft_wallet: &ctx.accounts.user_position_nft_wallet,
+                position_nft_metadata: &ctx.accounts.position_nft_metadata,
+                position_nft_edition: &ctx.accounts.position_nft_edition,
+                funder_ggwp_wallet: &ctx.accounts.funder_ggwp_wallet,
+                treasury: &ctx.accounts.treasury,
+                accumulative_fund: &ctx.accounts.accumulative_fund,
+                staking_fund: &ctx.accounts.staking_fund,
+                token_metadata_program: &ctx.accounts.token_metadata_program,
+                system_program: &ctx.accounts.system_program,
+                token_program: &ctx.accounts.token_program,
+            },
+            position_nft_auth_bump,
+            amount,
+            lockup_option,
+            auto_compound,
+        )
+    }
+
+    /// Position NFT holder can withdraw full amount of GGWP of the position with extra reward.
+    /// Staker info is passed through remaining accounts if the holder is not the staker.
+    pub fn withdraw<'info>(ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>) -> Result<()> {
+        let user = &ctx.accounts.user;
         let staking_info = &mut ctx.accounts.staking_info;
+        let epoch_history = &mut ctx.accounts.epoch_history;
+        let apr_schedule = &ctx.accounts.apr_schedule;
         let user_info = &mut ctx.accounts.user_info;
+        let position = &ctx.accounts.position;
+        let position_nft_mint = &ctx.accounts.position_nft_mint;
+        let user_position_nft_wallet = &ctx.accounts.user_position_nft_wallet;
         let user_ggwp_wallet = &ctx.accounts.user_ggwp_wallet;
         let treasury = &ctx.accounts.treasury;
         let treasury_auth = &ctx.accounts.treasury_auth;
@@ -264,19 +419,55 @@ pub mod staking {
         let token_program = &ctx.accounts.token_program;
         let clock = Clock::get()?;
 
-        let mut amount = user_info.amount;
+        let mut amount = position.amount;
         require_neq!(amount, 0, StakingError::NothingToWithdraw);
+        require!(
+            utils::is_lockup_ended(
+                clock.unix_timestamp,
+                position.stake_time,
+                position.lockup_days,
+            )?,
+            StakingError::LockupNotEnded
+        );
+        let boosted_amount = utils::calc_boosted_amount(amount, position.apr_boost)?;
 
-        let user_reward = utils::calc_user_reward_amount(
-            staking_info.epoch_period_days,
-            staking_info.start_time,
-            staking_info.apr_start,
-            staking_info.apr_step,
-            staking_info.apr_end,
-            amount,
-            user_info.stake_time,
+        if !user_info.is_initialized {
+            user_info.is_initialized = true;
+            user_info.amount = 0;
+            user_info.positions_count = 0;
+            user_info.reward_iou = 0;
+        }
+
+        utils::advance_epochs(
+            staking_info,
+            &apr_schedule.aprs,
+            &mut epoch_history.aprs,
             clock.unix_timestamp,
         )?;
+
+        let user_reward = match staking_info.pool_mode {
+            PoolMode::FixedApr => {
+                let (user_reward, _) = utils::calc_user_unclaimed_reward_amount(
+                    staking_info.epoch_period_days,
+                    staking_info.start_time,
+                    &epoch_history.aprs,
+                    amount,
+                    position.stake_time,
+                    position.claimed_epoch,
+                    position.apr_boost,
+                    clock.unix_timestamp,
+                )?;
+                user_reward
+            }
+            PoolMode::RewardPerShare => {
+                utils::update_reward_per_share(staking_info, staking_fund.amount)?;
+                utils::calc_pending_reward(
+                    boosted_amount,
+                    staking_info.reward_per_share,
+                    position.reward_per_share_paid,
+                )?
+            }
+        };
         msg!("User reward: {}", user_reward);
 
         let seeds = &[
@@ -296,7 +487,7 @@ pub mod staking {
         // Get withdraw royalty if needed and transfer
         if utils::is_withdraw_royalty(
             clock.unix_timestamp,
-            user_info.stake_time,
+            position.stake_time,
             staking_info.hold_period_days,
         )? {
             let withdraw_royalty_amount =
@@ -319,6 +510,11 @@ pub mod staking {
                 .ok_or(StakingError::Overflow)?;
         }
 
+        // Unpaid reward part is recorded as IOU if staking fund is short
+        let paid_reward = std::cmp::min(user_reward, staking_fund.amount);
+        let reward_iou = user_reward - paid_reward;
+        msg!("User reward IOU: {}", reward_iou);
+
         // Transfer GGWP reward to user from staking fund
         anchor_spl::token::transfer(
             CpiContext::new_with_signer(
@@ -330,9 +526,44 @@ pub mod staking {
                 },
                 staking_fund_auth_signer,
             ),
-            user_reward,
+            paid_reward,
         )?;
 
+        match staking_info.pool_mode {
+            PoolMode::FixedApr => {
+                utils::release_accrued_reward(
+                    staking_info,
+                    position.apr_boost,
+                    user_reward,
+                    false,
+                )?;
+                // Position leaves the accrual with the staked amount
+                let start_epoch = utils::get_position_start_epoch(
+                    staking_info.start_time,
+                    position.stake_time,
+                    staking_info.epoch_period_days,
+                )?;
+                utils::remove_reward_accrual(
+                    staking_info,
+                    position.apr_boost,
+                    position.amount,
+                    start_epoch,
+                )?;
+            }
+            PoolMode::RewardPerShare => {
+                staking_info.reward_reserve =
+                    staking_info.reward_reserve.saturating_sub(paid_reward);
+            }
+        }
+        staking_info.total_iou = staking_info
+            .total_iou
+            .checked_add(reward_iou)
+            .ok_or(StakingError::Overflow)?;
+        user_info.reward_iou = user_info
+            .reward_iou
+            .checked_add(reward_iou)
+            .ok_or(StakingError::Overflow)?;
+
         // Transfer GGWP staked tokens to user from treasury
         anchor_spl::token::transfer(
             CpiContext::new_with_signer(
@@ -347,13 +578,577 @@ pub mod staking {
             amount,
         )?;
 
-        user_info.amount = 0;
-        user_info.stake_time = 0;
+        // Burn position NFT and close the user NFT wallet
+        anchor_spl::token::burn(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Burn {
+                    mint: position_nft_mint.to_account_info(),
+                    from: user_position_nft_wallet.to_account_info(),
+                    authority: user.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        anchor_spl::token::close_account(CpiContext::new(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: user_position_nft_wallet.to_account_info(),
+                destination: user.to_account_info(),
+                authority: user.to_account_info(),
+            },
+        ))?;
+
+        // Staked amount is accounted to the staker, who may have transferred the position NFT
+        if position.user == user.key() {
+            user_info.amount = user_info
+                .amount
+                .checked_sub(position.amount)
+                .ok_or(StakingError::Overflow)?;
+        } else {
+            let staker_info = ctx
+                .remaining_accounts
+                .first()
+                .ok_or(StakingError::InvalidStakerInfo)?;
+            let (staker_info_pk, _) = Pubkey::find_program_address(
+                &[
+                    USER_INFO_SEED.as_bytes(),
+                    staking_info.key().as_ref(),
+                    position.user.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            require_keys_eq!(
+                staker_info.key(),
+                staker_info_pk,
+                StakingError::InvalidStakerInfo
+            );
+            let mut staker_info: Account<UserInfo> = Account::try_from(staker_info)?;
+            staker_info.amount = staker_info
+                .amount
+                .checked_sub(position.amount)
+                .ok_or(StakingError::Overflow)?;
+            staker_info.exit(ctx.program_id)?;
+        }
+        staking_info.total_staked = staking_info
+            .total_staked
+            .checked_sub(position.amount)
+            .ok_or(StakingError::Overflow)?;
+        staking_info.total_boosted_staked = staking_info
+            .total_boosted_staked
+            .checked_sub(boosted_amount)
+            .ok_or(StakingError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Position NFT holder can claim rewards of the completed epochs and keep the position staked.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let staking_info = &mut ctx.accounts.staking_info;
+        let epoch_history = &mut ctx.accounts.epoch_history;
+        let apr_schedule = &ctx.accounts.apr_schedule;
+        let user_info = &mut ctx.accounts.user_info;
+        let position = &mut ctx.accounts.position;
+        let user_ggwp_wallet = &ctx.accounts.user_ggwp_wallet;
+        let staking_fund = &ctx.accounts.staking_fund;
+        let staking_fund_auth = &ctx.accounts.staking_fund_auth;
+        let token_program = &ctx.accounts.token_program;
+        let clock = Clock::get()?;
+
+        require_neq!(position.amount, 0, StakingError::NothingToClaim);
+
+        if !user_info.is_initialized {
+            user_info.is_initialized = true;
+            user_info.amount = 0;
+            user_info.positions_count = 0;
+            user_info.reward_iou = 0;
+        }
+
+        utils::advance_epochs(
+            staking_info,
+            &apr_schedule.aprs,
+            &mut epoch_history.aprs,
+            clock.unix_timestamp,
+        )?;
+
+        let (user_reward, last_epoch) = match staking_info.pool_mode {
+            PoolMode::FixedApr => {
+                let (user_reward, last_epoch) = utils::calc_user_unclaimed_reward_amount(
+                    staking_info.epoch_period_days,
+                    staking_info.start_time,
+                    &epoch_history.aprs,
+                    position.amount,
+                    position.stake_time,
+                    position.claimed_epoch,
+                    position.apr_boost,
+                    clock.unix_timestamp,
+                )?;
+                require_neq!(
+                    last_epoch,
+                    position.claimed_epoch,
+                    StakingError::NothingToClaim
+                );
+                (user_reward, last_epoch)
+            }
+            PoolMode::RewardPerShare => {
+                utils::update_reward_per_share(staking_info, staking_fund.amount)?;
+                let user_reward = utils::calc_pending_reward(
+                    utils::calc_boosted_amount(position.amount, position.apr_boost)?,
+                    staking_info.reward_per_share,
+                    position.reward_per_share_paid,
+                )?;
+                require_neq!(user_reward, 0, StakingError::NothingToClaim);
+                (user_reward, position.claimed_epoch)
+            }
+        };
+        msg!("User reward: {}", user_reward);
+
+        let seeds = &[
+            STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.to_account_info().key.as_ref(),
+            &[staking_info.staking_fund_auth_bump],
+        ];
+        let staking_fund_auth_signer = &[&seeds[..]];
+
+        // Unpaid reward part is recorded as IOU if staking fund is short
+        let paid_reward = std::cmp::min(user_reward, staking_fund.amount);
+        let reward_iou = user_reward - paid_reward;
+        msg!("User reward IOU: {}", reward_iou);
+
+        // Transfer GGWP reward to user from staking fund
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: staking_fund.to_account_info(),
+                    to: user_ggwp_wallet.to_account_info(),
+                    authority: staking_fund_auth.to_account_info(),
+                },
+                staking_fund_auth_signer,
+            ),
+            paid_reward,
+        )?;
+
+        match staking_info.pool_mode {
+            PoolMode::FixedApr => {
+                utils::release_accrued_reward(
+                    staking_info,
+                    position.apr_boost,
+                    user_reward,
+                    false,
+                )?;
+            }
+            PoolMode::RewardPerShare => {
+                staking_info.reward_reserve =
+                    staking_info.reward_reserve.saturating_sub(paid_reward);
+            }
+        }
+        staking_info.total_iou = staking_info
+            .total_iou
+            .checked_add(reward_iou)
+            .ok_or(StakingError::Overflow)?;
+        user_info.reward_iou = user_info
+            .reward_iou
+            .checked_add(reward_iou)
+            .ok_or(StakingError::Overflow)?;
+
+        position.claimed_epoch = last_epoch;
+        position.reward_per_share_paid = staking_info.reward_per_share;
+
+        Ok(())
+    }
+
+    /// Position NFT holder can switch compounding of the position rewards.
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, auto_compound: bool) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        position.auto_compound = auto_compound;
+
+        Ok(())
+    }
+
+    /// Anyone can compound rewards of the completed epochs into the auto-compounding position.
+    pub fn compound(ctx: Context<Compound>) -> Result<()> {
+        let staking_info = &mut ctx.accounts.staking_info;
+        let epoch_history = &mut ctx.accounts.epoch_history;
+        let apr_schedule = &ctx.accounts.apr_schedule;
+        let staker_info = &mut ctx.accounts.staker_info;
+        let position = &mut ctx.accounts.position;
+        let staking_fund = &ctx.accounts.staking_fund;
+        let staking_fund_auth = &ctx.accounts.staking_fund_auth;
+        let treasury = &ctx.accounts.treasury;
+        let token_program = &ctx.accounts.token_program;
+        let clock = Clock::get()?;
+
+        require!(
+            position.auto_compound,
+            StakingError::NotAutoCompoundPosition
+        );
+
+        utils::advance_epochs(
+            staking_info,
+            &apr_schedule.aprs,
+            &mut epoch_history.aprs,
+            clock.unix_timestamp,
+        )?;
+
+        let boosted_amount = utils::calc_boosted_amount(position.amount, position.apr_boost)?;
+        let (reward, last_epoch) = match staking_info.pool_mode {
+            PoolMode::FixedApr => {
+                let (reward, last_epoch) = utils::calc_user_unclaimed_reward_amount(
+                    staking_info.epoch_period_days,
+                    staking_info.start_time,
+                    &epoch_history.aprs,
+                    position.amount,
+                    position.stake_time,
+                    position.claimed_epoch,
+                    position.apr_boost,
+                    clock.unix_timestamp,
+                )?;
+                require_neq!(
+                    last_epoch,
+                    position.claimed_epoch,
+                    StakingError::NothingToClaim
+                );
+                (reward, last_epoch)
+            }
+            PoolMode::RewardPerShare => {
+                utils::update_reward_per_share(staking_info, staking_fund.amount)?;
+                let reward = utils::calc_pending_reward(
+                    boosted_amount,
+                    staking_info.reward_per_share,
+                    position.reward_per_share_paid,
+                )?;
+                require_neq!(reward, 0, StakingError::NothingToClaim);
+                (reward, position.claimed_epoch)
+            }
+        };
+        msg!("Compounded reward: {}", reward);
+        require!(
+            reward <= staking_fund.amount,
+            StakingError::InsufficientStakingFund
+        );
+
+        let seeds = &[
+            STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.to_account_info().key.as_ref(),
+            &[staking_info.staking_fund_auth_bump],
+        ];
+        let staking_fund_auth_signer = &[&seeds[..]];
+
+        // Move GGWP reward from staking fund into treasury as the position stake
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: staking_fund.to_account_info(),
+                    to: treasury.to_account_info(),
+                    authority: staking_fund_auth.to_account_info(),
+                },
+                staking_fund_auth_signer,
+            ),
+            reward,
+        )?;
+
+        let new_amount = position
+            .amount
+            .checked_add(reward)
+            .ok_or(StakingError::Overflow)?;
+        let new_boosted_amount = utils::calc_boosted_amount(new_amount, position.apr_boost)?;
+
+        // Compounded reward keeps accruing as the staked amount
+        match staking_info.pool_mode {
+            PoolMode::FixedApr => {
+                utils::release_accrued_reward(staking_info, position.apr_boost, reward, true)?
+            }
+            PoolMode::RewardPerShare => {
+                staking_info.reward_reserve = staking_info.reward_reserve.saturating_sub(reward);
+            }
+        }
         staking_info.total_staked = staking_info
             .total_staked
+            .checked_add(reward)
+            .ok_or(StakingError::Overflow)?;
+        staking_info.total_boosted_staked = staking_info
+            .total_boosted_staked
+            .checked_sub(boosted_amount)
+            .and_then(|total| total.checked_add(new_boosted_amount))
+            .ok_or(StakingError::Overflow)?;
+        staker_info.amount = staker_info
+            .amount
+            .checked_add(reward)
+            .ok_or(StakingError::Overflow)?;
+
+        position.amount = new_amount;
+        position.claimed_epoch = last_epoch;
+        position.reward_per_share_paid = staking_info.reward_per_share;
+
+        Ok(())
+    }
+
+    /// User can claim the unpaid rewards IOU when staking fund is refilled.
+    pub fn claim_iou(ctx: Context<ClaimIOU>) -> Result<()> {
+        let staking_info = &mut ctx.accounts.staking_info;
+        let user_info = &mut ctx.accounts.user_info;
+        let user_ggwp_wallet = &ctx.accounts.user_ggwp_wallet;
+        let staking_fund = &ctx.accounts.staking_fund;
+        let staking_fund_auth = &ctx.accounts.staking_fund_auth;
+        let token_program = &ctx.accounts.token_program;
+
+        require_neq!(user_info.reward_iou, 0, StakingError::NothingToClaim);
+        let amount = std::cmp::min(user_info.reward_iou, staking_fund.amount);
+        require_neq!(amount, 0, StakingError::InsufficientStakingFund);
+
+        let seeds = &[
+            STAKING_FUND_AUTH_SEED.as_bytes(),
+            staking_info.to_account_info().key.as_ref(),
+            &[staking_info.staking_fund_auth_bump],
+        ];
+        let staking_fund_auth_signer = &[&seeds[..]];
+
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: staking_fund.to_account_info(),
+                    to: user_ggwp_wallet.to_account_info(),
+                    authority: staking_fund_auth.to_account_info(),
+                },
+                staking_fund_auth_signer,
+            ),
+            amount,
+        )?;
+
+        user_info.reward_iou = user_info
+            .reward_iou
             .checked_sub(amount)
             .ok_or(StakingError::Overflow)?;
+        staking_info.total_iou = staking_info.total_iou.saturating_sub(amount);
+        if staking_info.pool_mode == PoolMode::RewardPerShare {
+            staking_info.reward_reserve = staking_info.reward_reserve.saturating_sub(amount);
+        }
 
         Ok(())
     }
 }
+
+/// Stake amount of funder GGWP into the new position of owner and mint the position NFT to owner.
+fn stake_position(
+    accounts: StakePosition,
+    position_nft_auth_bump: u8,
+    amount: u64,
+    lockup_option: u8,
+    auto_compound: bool,
+) -> Result<()> {
+    let StakePosition {
+        funder,
+        owner,
+        staking_info,
+        epoch_history,
+        apr_schedule,
+        user_info,
+        position,
+        position_nft_mint,
+        position_nft_auth,
+        user_position_nft_wallet,
+        position_nft_metadata,
+        position_nft_edition,
+        funder_ggwp_wallet,
+        treasury,
+        accumulative_fund,
+        staking_fund,
+        token_metadata_program,
+        system_program,
+        token_program,
+    } = accounts;
+    let clock = Clock::get()?;
+
+    utils::advance_epochs(
+        staking_info,
+        &apr_schedule.aprs,
+        &mut epoch_history.aprs,
+        clock.unix_timestamp,
+    )?;
+
+    require!(
+        amount >= staking_info.min_stake_amount,
+        StakingError::MinStakeAmountExceeded
+    );
+    let lockup_option = *staking_info
+        .lockup_options
+        .get(lockup_option as usize)
+        .ok_or(StakingError::InvalidLockupOption)?;
+
+    if staking_info.pool_mode == PoolMode::RewardPerShare {
+        utils::update_reward_per_share(staking_info, staking_fund.amount)?;
+    }
+
+    if !user_info.is_initialized {
+        user_info.is_initialized = true;
+        user_info.amount = 0;
+        user_info.positions_count = 0;
+        user_info.reward_iou = 0;
+    }
+
+    let royalty_amount = utils::calc_royalty_amount(staking_info.royalty, amount)?;
+    msg!("Accumulative fund royalty: {}", royalty_amount);
+    // Transfer royalty into accumulative fund
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: funder_ggwp_wallet.to_account_info(),
+                to: accumulative_fund.to_account_info(),
+                authority: funder.to_account_info(),
+            },
+        ),
+        royalty_amount,
+    )?;
+
+    let amount = amount
+        .checked_sub(royalty_amount)
+        .ok_or(StakingError::Overflow)?;
+
+    let boosted_amount = utils::calc_boosted_amount(amount, lockup_option.apr_boost)?;
+    let new_total_boosted_staked = staking_info
+        .total_boosted_staked
+        .checked_add(boosted_amount)
+        .ok_or(StakingError::Overflow)?;
+    if staking_info.pool_mode == PoolMode::FixedApr {
+        require!(
+            utils::is_staking_fund_covered(
+                staking_fund.amount,
+                staking_info,
+                &apr_schedule.aprs,
+                new_total_boosted_staked,
+            )?,
+            StakingError::InsufficientStakingFund
+        );
+    }
+
+    // Transfer amount into treasury
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: funder_ggwp_wallet.to_account_info(),
+                to: treasury.to_account_info(),
+                authority: funder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    position.user = owner;
+    position.index = user_info.positions_count;
+    position.amount = amount;
+    position.stake_time = clock.unix_timestamp;
+    position.lockup_days = lockup_option.lockup_days;
+    position.apr_boost = lockup_option.apr_boost;
+    position.auto_compound = auto_compound;
+    position.claimed_epoch = 0;
+    position.nft_mint = position_nft_mint.key();
+    position.reward_per_share_paid = staking_info.reward_per_share;
+    msg!("New position index: {}", position.index);
+
+    let seeds = &[
+        POSITION_NFT_AUTH_SEED.as_bytes(),
+        staking_info.to_account_info().key.as_ref(),
+        &[position_nft_auth_bump],
+    ];
+    let position_nft_auth_signer = &[&seeds[..]];
+
+    // Mint position NFT to owner
+    anchor_spl::token::mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: position_nft_mint.to_account_info(),
+                to: user_position_nft_wallet.to_account_info(),
+                authority: position_nft_auth.to_account_info(),
+            },
+            position_nft_auth_signer,
+        ),
+        1,
+    )?;
+
+    // Position NFT metadata is served by the position pk
+    let position_pk = position.key();
+    invoke_signed(
+        &metadata::create_metadata_accounts_v3(
+            metadata::ID,
+            position_nft_metadata.key(),
+            position_nft_mint.key(),
+            position_nft_auth.key(),
+            funder.key(),
+            position_nft_auth.key(),
+            utils::get_position_nft_name(&position_pk),
+            POSITION_NFT_SYMBOL.to_string(),
+            utils::get_position_nft_uri(&staking_info.position_nft_uri, &position_pk),
+            None,
+            0,
+            true,
+            true,
+            None,
+            None,
+            None,
+        ),
+        &[
+            position_nft_metadata.to_account_info(),
+            position_nft_mint.to_account_info(),
+            position_nft_auth.to_account_info(),
+            funder.to_account_info(),
+            system_program.to_account_info(),
+            token_metadata_program.to_account_info(),
+        ],
+        position_nft_auth_signer,
+    )?;
+
+    // Master edition takes over the mint authority and keeps position NFT supply 1 of 1
+    invoke_signed(
+        &metadata::create_master_edition_v3(
+            metadata::ID,
+            position_nft_edition.key(),
+            position_nft_mint.key(),
+            position_nft_auth.key(),
+            position_nft_auth.key(),
+            position_nft_metadata.key(),
+            funder.key(),
+            Some(0),
+        ),
+        &[
+            position_nft_edition.to_account_info(),
+            position_nft_mint.to_account_info(),
+            position_nft_auth.to_account_info(),
+            funder.to_account_info(),
+            position_nft_metadata.to_account_info(),
+            token_program.to_account_info(),
+            system_program.to_account_info(),
+            token_metadata_program.to_account_info(),
+        ],
+        position_nft_auth_signer,
+    )?;
+
+    user_info.positions_count = user_info
+        .positions_count
+        .checked_add(1)
+        .ok_or(StakingError::Overflow)?;
+    user_info.amount = user_info
+        .amount
+        .checked_add(amount)
+        .ok_or(StakingError::Overflow)?;
+    staking_info.total_staked = staking_info
+        .total_staked
+        .checked_add(amount)
+        .ok_or(StakingError::Overflow)?;
+    staking_info.total_boosted_staked = new_total_boosted_staked;
+    if staking_info.pool_mode == PoolMode::FixedApr {
+        let start_epoch = utils::get_position_start_epoch(
+            staking_info.start_time,
+            position.stake_time,
+            staking_info.epoch_period_days,
+        )?;
+        utils::add_reward_accrual(staking_info, position.apr_boost, amount, start_epoch)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Metaplex token metadata program of the position NFTs.
+pub use mpl_token_metadata::instruction::{create_master_edition_v3, create_metadata_accounts_v3};
+pub use mpl_token_metadata::state::{
+    EDITION as EDITION_SEED, MAX_NAME_LENGTH, MAX_URI_LENGTH, PREFIX as METADATA_SEED,
+};
+pub use mpl_token_metadata::ID;
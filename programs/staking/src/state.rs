@@ -1,10 +1,30 @@
 use anchor_lang::prelude::*;
+use core::fmt;
 
 pub const DESCRIMINATOR_LEN: usize = 8;
 
 pub const TREASURY_AUTH_SEED: &str = "treasury_auth";
 pub const STAKING_FUND_AUTH_SEED: &str = "staking_fund_auth";
 pub const USER_INFO_SEED: &str = "user_info";
+pub const EPOCH_HISTORY_SEED: &str = "epoch_history";
+pub const POSITION_SEED: &str = "position";
+pub const APR_SCHEDULE_SEED: &str = "apr_schedule";
+pub const POSITION_NFT_MINT_SEED: &str = "position_nft_mint";
+pub const POSITION_NFT_AUTH_SEED: &str = "position_nft_auth";
+
+pub const POSITION_NFT_NAME: &str = "GGWP Staking Position";
+pub const POSITION_NFT_SYMBOL: &str = "sGGWP";
+// Position NFT base URI fits the Metaplex URI limit with the `/<position pk>.json` suffix
+pub const MAX_POSITION_NFT_URI_LEN: usize = 150;
+
+pub const EPOCH_HISTORY_MAX_LEN: usize = 1000;
+pub const APR_SCHEDULE_MAX_LEN: usize = 1000;
+pub const LOCKUP_OPTIONS_MAX_LEN: usize = 8;
+// Positions keep the APR boost of replaced lockup options
+pub const REWARD_ACCRUALS_MAX_LEN: usize = 2 * LOCKUP_OPTIONS_MAX_LEN;
+
+/// APR boost of 1x in basis points.
+pub const BASE_APR_BOOST: u16 = 10_000;
 
 #[account]
 #[derive(Default, Debug)]
@@ -31,6 +51,15 @@ pub struct StakingInfo {
     pub apr_start: u8,
     pub apr_step: u8,
     pub apr_end: u8,
+    pub reward_liability: u64,
+    pub total_iou: u64,
+    pub pool_mode: PoolMode,
+    pub reward_per_share: u128,
+    pub reward_reserve: u64,
+    pub total_boosted_staked: u64,
+    pub lockup_options: Vec<LockupOption>,
+    pub position_nft_uri: String,
+    pub reward_accruals: Vec<RewardAccrual>,
 }
 
 impl StakingInfo {
@@ -48,7 +77,66 @@ impl StakingInfo {
         2 + // hold period in days
         1 + // hold royalty percent
         1 + // royalty
-        1 + 1 + 1; // start, step, end apr
+        1 + 1 + 1 + // start, step, end apr
+        8 + // accrued unpaid reward liability
+        8 + // total unpaid rewards IOU
+        PoolMode::LEN + // pool mode
+        16 + // accumulated reward per share
+        8 + // staking fund amount distributed over stakers
+        8 + // total staked with APR boost
+        4 + LockupOption::LEN * LOCKUP_OPTIONS_MAX_LEN + // lockup options
+        4 + MAX_POSITION_NFT_URI_LEN + // position NFT metadata base URI
+        4 + RewardAccrual::LEN * REWARD_ACCRUALS_MAX_LEN; // reward liability accruals
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, Default, PartialEq)]
+pub struct LockupOption {
+    pub lockup_days: u16,
+    // APR multiplier in basis points
+    pub apr_boost: u16,
+}
+
+impl LockupOption {
+    pub const LEN: usize = 2 + 2;
+}
+
+/// Reward liability accrual of the positions with the same APR boost.
+/// Amount compounds by the boosted APR of every epoch like the position rewards.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, Default, PartialEq)]
+pub struct RewardAccrual {
+    pub apr_boost: u16,
+    // Staked amount with the unpaid compounded rewards
+    pub amount: u64,
+    // Staked amount of the positions starting from the next epoch
+    pub pending_amount: u64,
+    pub positions_count: u64,
+}
+
+impl RewardAccrual {
+    pub const LEN: usize = 2 + 8 + 8 + 8;
+}
+
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, Default, PartialEq)]
+pub enum PoolMode {
+    // Rewards by APR schedule
+    #[default]
+    FixedApr,
+    // Staking fund deposits spread over stakers pro rata
+    RewardPerShare,
+}
+
+impl PoolMode {
+    pub const LEN: usize = 1;
+}
+
+impl fmt::Display for PoolMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolMode::FixedApr => write!(f, "PoolMode::FixedApr"),
+            PoolMode::RewardPerShare => write!(f, "PoolMode::RewardPerShare"),
+        }
+    }
 }
 
 #[account]
@@ -56,13 +144,69 @@ impl StakingInfo {
 pub struct UserInfo {
     pub is_initialized: bool,
     pub amount: u64,
-    pub stake_time: i64,
+    pub positions_count: u64,
+    pub reward_iou: u64,
 }
 
 impl UserInfo {
     pub const LEN: usize = DESCRIMINATOR_LEN +
         1 + // is initialized
+        8 + // staked amount in all positions
+        8 + // positions count (next position index)
+        8 // unpaid reward IOU
+        ;
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct Position {
+    pub user: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub stake_time: i64,
+    pub claimed_epoch: u64,
+    pub nft_mint: Pubkey,
+    pub reward_per_share_paid: u128,
+    pub lockup_days: u16,
+    pub apr_boost: u16,
+    pub auto_compound: bool,
+}
+
+impl Position {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        32 + // user pk
+        8 + // position index
         8 + // staked amount
-        8 // stake time
+        8 + // stake time
+        8 + // last claimed epoch
+        32 + // position NFT mint pk
+        16 + // reward per share checkpoint
+        2 + // lockup period in days
+        2 + // APR boost in basis points
+        1 // rewards compound into amount
+        ;
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct EpochHistory {
+    pub aprs: Vec<u16>,
+}
+
+impl EpochHistory {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        4 + 2 * EPOCH_HISTORY_MAX_LEN // applied APR in bps of the completed epochs
+        ;
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct AprSchedule {
+    pub aprs: Vec<u16>,
+}
+
+impl AprSchedule {
+    pub const LEN: usize = DESCRIMINATOR_LEN +
+        4 + 2 * APR_SCHEDULE_MAX_LEN // APR in bps by epoch
         ;
 }
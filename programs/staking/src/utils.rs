@@ -1,7 +1,16 @@
 use crate::error::StakingError;
+use crate::state::{
+    LockupOption, PoolMode, RewardAccrual, StakingInfo, APR_SCHEDULE_MAX_LEN, BASE_APR_BOOST,
+    EPOCH_HISTORY_MAX_LEN, LOCKUP_OPTIONS_MAX_LEN, MAX_POSITION_NFT_URI_LEN, POSITION_NFT_NAME,
+    REWARD_ACCRUALS_MAX_LEN,
+};
 use anchor_lang::{prelude::*, solana_program::clock::UnixTimestamp};
 use anchor_spl::token::spl_token::{amount_to_ui_amount, ui_amount_to_amount};
-use std::ops::Mul;
+pub use reward_calc::{
+    calc_compound_growth, calc_grown_amount, mul_wad, DAYS_IN_YEAR, MAX_BPS, WAD,
+};
+
+pub const BPS_IN_PERCENT: u16 = 100;
 
 /// Get the percent value.
 pub fn calc_royalty_amount(royalty: u8, amount: u64) -> Result<u64> {
@@ -28,31 +37,85 @@ pub fn is_withdraw_royalty(
     Ok(true)
 }
 
+/// Checks the position lockup is ended.
+pub fn is_lockup_ended(
+    current_time: UnixTimestamp,
+    stake_time: UnixTimestamp,
+    lockup_days: u16,
+) -> Result<bool> {
+    let lockup_end_time = stake_time
+        .checked_add(lockup_days as i64 * 24 * 60 * 60)
+        .ok_or(StakingError::Overflow)?;
+    Ok(current_time >= lockup_end_time)
+}
+
+/// Checks lockup options table. APR boost can't be less than 1x.
+pub fn is_valid_lockup_options(lockup_options: &[LockupOption]) -> bool {
+    !lockup_options.is_empty()
+        && lockup_options.len() <= LOCKUP_OPTIONS_MAX_LEN
+        && lockup_options
+            .iter()
+            .all(|option| option.apr_boost >= BASE_APR_BOOST)
+}
+
+/// Get the max APR boost of the lockup options and the staked positions.
+pub fn get_max_apr_boost(staking_info: &StakingInfo) -> u16 {
+    staking_info
+        .lockup_options
+        .iter()
+        .map(|option| option.apr_boost)
+        .chain(
+            staking_info
+                .reward_accruals
+                .iter()
+                .map(|accrual| accrual.apr_boost),
+        )
+        .max()
+        .unwrap_or(BASE_APR_BOOST)
+}
+
+/// Checks every APR in basis points with APR boost fits the boosted APR.
+pub fn is_valid_boosted_aprs(aprs: &[u16], apr_boost: u16) -> bool {
+    aprs.iter()
+        .all(|apr| reward_calc::calc_boosted_apr(*apr, apr_boost).is_some())
+}
+
+/// Checks position NFT base URI fits the metadata with the position suffix.
+pub fn is_valid_position_nft_uri(uri: &str) -> bool {
+    !uri.is_empty() && uri.len() <= MAX_POSITION_NFT_URI_LEN
+}
+
+/// Get the position NFT name unique by the position pk prefix.
+pub fn get_position_nft_name(position: &Pubkey) -> String {
+    format!("{} {:.10}", POSITION_NFT_NAME, position.to_string())
+}
+
+/// Get the position NFT metadata URI describing the position.
+pub fn get_position_nft_uri(uri: &str, position: &Pubkey) -> String {
+    format!("{}/{}.json", uri.trim_end_matches('/'), position)
+}
+
+/// Get the amount with APR boost in basis points.
+pub fn calc_boosted_amount(amount: u64, apr_boost: u16) -> Result<u64> {
+    let boosted_amount = amount as u128 * apr_boost as u128 / MAX_BPS;
+    Ok(u64::try_from(boosted_amount).map_err(|_| StakingError::Overflow)?)
+}
+
+/// Get the APR in basis points with APR boost in basis points.
+pub fn calc_boosted_apr(apr: u16, apr_boost: u16) -> Result<u16> {
+    Ok(reward_calc::calc_boosted_apr(apr, apr_boost).ok_or(StakingError::Overflow)?)
+}
+
 /// Get number of epoch.
 pub fn get_epoch_by_time(
     staking_start_time: UnixTimestamp,
     time: UnixTimestamp,
     epoch_period_days: u16,
 ) -> Result<(u64, bool)> {
-    let spent_time = time
-        .checked_sub(staking_start_time)
-        .ok_or(StakingError::Overflow)?;
-    let spent_days = spent_time
-        .checked_div(24 * 60 * 60)
-        .ok_or(StakingError::Overflow)?;
-
-    let epoch_period_days = epoch_period_days as i64;
-    let epoch = spent_days
-        .checked_div(epoch_period_days)
-        .ok_or(StakingError::Overflow)?;
-
-    let is_full_epoch = if (spent_days % epoch_period_days) == 0 {
-        true
-    } else {
-        false
-    };
-
-    Ok((epoch as u64 + 1, is_full_epoch))
+    Ok(
+        reward_calc::get_epoch_by_time(staking_start_time, time, epoch_period_days)
+            .ok_or(StakingError::Overflow)?,
+    )
 }
 
 /// Get the current APR by epoch.
@@ -71,6 +134,348 @@ pub fn get_apr_by_epoch(epoch: u64, start_apr: u8, step_apr: u8, end_apr: u8) ->
     }
 }
 
+/// Build APR schedule in basis points from the linear step-down curve.
+pub fn build_apr_schedule(start_apr: u8, step_apr: u8, end_apr: u8) -> Result<Vec<u16>> {
+    let mut apr_schedule = vec![];
+    for epoch in 1..=APR_SCHEDULE_MAX_LEN as u64 {
+        let apr = get_apr_by_epoch(epoch, start_apr, step_apr, end_apr)?;
+        apr_schedule.push(apr as u16 * BPS_IN_PERCENT);
+        if apr == end_apr {
+            break;
+        }
+    }
+
+    Ok(apr_schedule)
+}
+
+/// Get the scheduled APR in basis points of epoch.
+/// Epochs after the schedule end use the last scheduled APR.
+pub fn get_scheduled_apr(apr_schedule: &[u16], epoch: u64) -> Result<u16> {
+    let index = epoch.checked_sub(1).ok_or(StakingError::Overflow)? as usize;
+    let apr = apr_schedule
+        .get(index)
+        .or(apr_schedule.last())
+        .ok_or(StakingError::InvalidAPRSchedule)?;
+
+    Ok(*apr)
+}
+
+/// Set APRs in basis points starting from the epoch.
+/// Gap after the current schedule end is filled with the last scheduled APR.
+pub fn set_scheduled_aprs(
+    apr_schedule: &mut Vec<u16>,
+    current_epoch: u64,
+    from_epoch: u64,
+    aprs: &[u16],
+) -> Result<()> {
+    require!(
+        from_epoch > current_epoch,
+        StakingError::InvalidAPRScheduleEpoch
+    );
+    require!(!aprs.is_empty(), StakingError::InvalidAPRSchedule);
+    let from_index = (from_epoch - 1) as usize;
+    let end_index = from_index
+        .checked_add(aprs.len())
+        .ok_or(StakingError::Overflow)?;
+    require!(
+        end_index <= APR_SCHEDULE_MAX_LEN,
+        StakingError::InvalidAPRSchedule
+    );
+
+    let last_apr = *apr_schedule
+        .last()
+        .ok_or(StakingError::InvalidAPRSchedule)?;
+    if apr_schedule.len() < from_index {
+        apr_schedule.resize(from_index, last_apr);
+    }
+    for (i, apr) in aprs.iter().enumerate() {
+        let index = from_index + i;
+        if index < apr_schedule.len() {
+            apr_schedule[index] = *apr;
+        } else {
+            apr_schedule.push(*apr);
+        }
+    }
+
+    Ok(())
+}
+
+/// Record the applied APR of every completed epoch and move staking to the current epoch.
+pub fn advance_epochs(
+    staking_info: &mut StakingInfo,
+    apr_schedule: &[u16],
+    epoch_aprs: &mut Vec<u16>,
+    current_time: UnixTimestamp,
+) -> Result<u64> {
+    let (current_epoch, _) = get_epoch_by_time(
+        staking_info.start_time,
+        current_time,
+        staking_info.epoch_period_days,
+    )?;
+
+    let mut advanced = 0;
+    while staking_info.epoch < current_epoch {
+        let apr = get_scheduled_apr(apr_schedule, staking_info.epoch)?;
+        if epoch_aprs.len() < EPOCH_HISTORY_MAX_LEN {
+            epoch_aprs.push(apr);
+        }
+        if staking_info.pool_mode == PoolMode::FixedApr {
+            // Positions are rewarded by the recorded APR
+            let apr = get_recorded_apr(epoch_aprs, staking_info.epoch)?;
+            for accrual in staking_info.reward_accruals.iter_mut() {
+                let epoch_reward = calc_max_epoch_reward_amount(
+                    accrual.amount,
+                    calc_boosted_apr(apr, accrual.apr_boost)?,
+                    staking_info.epoch_period_days,
+                )?;
+                staking_info.reward_liability = staking_info
+                    .reward_liability
+                    .checked_add(epoch_reward)
+                    .ok_or(StakingError::Overflow)?;
+                accrual.amount = accrual
+                    .amount
+                    .checked_add(epoch_reward)
+                    .and_then(|amount| amount.checked_add(accrual.pending_amount))
+                    .ok_or(StakingError::Overflow)?;
+                accrual.pending_amount = 0;
+            }
+        }
+        staking_info.epoch += 1;
+        advanced += 1;
+    }
+
+    Ok(advanced)
+}
+
+/// Get the first rewarded epoch of the position.
+/// Position staked after the first day of the epoch is rewarded from the next one.
+pub fn get_position_start_epoch(
+    staking_start_time: UnixTimestamp,
+    stake_time: UnixTimestamp,
+    epoch_period_days: u16,
+) -> Result<u64> {
+    Ok(
+        reward_calc::get_position_start_epoch(staking_start_time, stake_time, epoch_period_days)
+            .ok_or(StakingError::Overflow)?,
+    )
+}
+
+/// Add the new position starting at the epoch to the reward accrual of its APR boost.
+pub fn add_reward_accrual(
+    staking_info: &mut StakingInfo,
+    apr_boost: u16,
+    amount: u64,
+    start_epoch: u64,
+) -> Result<()> {
+    let is_pending = start_epoch > staking_info.epoch;
+    let accruals = &mut staking_info.reward_accruals;
+    let index = match accruals
+        .iter()
+        .position(|accrual| accrual.apr_boost == apr_boost)
+    {
+        Some(index) => index,
+        None => {
+            require_gt!(
+                REWARD_ACCRUALS_MAX_LEN,
+                accruals.len(),
+                StakingError::TooManyRewardAccruals
+            );
+            accruals.push(RewardAccrual {
+                apr_boost,
+                ..Default::default()
+            });
+            accruals.len() - 1
+        }
+    };
+
+    let accrual = &mut accruals[index];
+    if is_pending {
+        accrual.pending_amount = accrual
+            .pending_amount
+            .checked_add(amount)
+            .ok_or(StakingError::Overflow)?;
+    } else {
+        accrual.amount = accrual
+            .amount
+            .checked_add(amount)
+            .ok_or(StakingError::Overflow)?;
+    }
+    accrual.positions_count = accrual
+        .positions_count
+        .checked_add(1)
+        .ok_or(StakingError::Overflow)?;
+
+    Ok(())
+}
+
+/// Release the paid position reward from the reward liability and the reward accrual.
+/// Compounded reward stays in the accrual as the staked amount.
+pub fn release_accrued_reward(
+    staking_info: &mut StakingInfo,
+    apr_boost: u16,
+    reward: u64,
+    is_compounded: bool,
+) -> Result<()> {
+    staking_info.reward_liability = staking_info
+        .reward_liability
+        .checked_sub(reward)
+        .ok_or(StakingError::InvalidRewardLiability)?;
+    if is_compounded {
+        return Ok(());
+    }
+
+    let accrual = staking_info
+        .reward_accruals
+        .iter_mut()
+        .find(|accrual| accrual.apr_boost == apr_boost)
+        .ok_or(StakingError::InvalidRewardLiability)?;
+    accrual.amount = accrual
+        .amount
+        .checked_sub(reward)
+        .ok_or(StakingError::InvalidRewardLiability)?;
+
+    Ok(())
+}
+
+/// Remove the withdrawn position starting at the epoch from the reward accrual.
+/// Rounding margin of the accrual without positions is released from the reward liability.
+pub fn remove_reward_accrual(
+    staking_info: &mut StakingInfo,
+    apr_boost: u16,
+    amount: u64,
+    start_epoch: u64,
+) -> Result<()> {
+    let is_pending = start_epoch > staking_info.epoch;
+    let index = staking_info
+        .reward_accruals
+        .iter()
+        .position(|accrual| accrual.apr_boost == apr_boost)
+        .ok_or(StakingError::InvalidRewardLiability)?;
+
+    let accrual = &mut staking_info.reward_accruals[index];
+    if is_pending {
+        accrual.pending_amount = accrual
+            .pending_amount
+            .checked_sub(amount)
+            .ok_or(StakingError::InvalidRewardLiability)?;
+    } else {
+        accrual.amount = accrual
+            .amount
+            .checked_sub(amount)
+            .ok_or(StakingError::InvalidRewardLiability)?;
+    }
+    accrual.positions_count = accrual
+        .positions_count
+        .checked_sub(1)
+        .ok_or(StakingError::InvalidRewardLiability)?;
+
+    if accrual.positions_count == 0 {
+        let margin = accrual.amount;
+        staking_info.reward_accruals.remove(index);
+        staking_info.reward_liability = staking_info
+            .reward_liability
+            .checked_sub(margin)
+            .ok_or(StakingError::InvalidRewardLiability)?;
+    }
+
+    Ok(())
+}
+
+/// Spread new staking fund deposits over the staked amount with APR boost.
+/// Deposits stay undistributed while nothing is staked.
+pub fn update_reward_per_share(
+    staking_info: &mut StakingInfo,
+    staking_fund_amount: u64,
+) -> Result<()> {
+    let new_rewards = staking_fund_amount.saturating_sub(staking_info.reward_reserve);
+    if new_rewards == 0 || staking_info.total_boosted_staked == 0 {
+        return Ok(());
+    }
+
+    let increase = (new_rewards as u128)
+        .checked_mul(WAD)
+        .ok_or(StakingError::Overflow)?
+        / staking_info.total_boosted_staked as u128;
+    staking_info.reward_per_share = staking_info
+        .reward_per_share
+        .checked_add(increase)
+        .ok_or(StakingError::Overflow)?;
+    staking_info.reward_reserve = staking_fund_amount;
+
+    Ok(())
+}
+
+/// Get the reward of amount accrued since the reward per share checkpoint.
+pub fn calc_pending_reward(amount: u64, reward_per_share: u128, checkpoint: u128) -> Result<u64> {
+    let reward = reward_per_share
+        .checked_sub(checkpoint)
+        .and_then(|delta| mul_wad(delta, amount as u128))
+        .ok_or(StakingError::Overflow)?;
+
+    Ok(u64::try_from(reward).map_err(|_| StakingError::Overflow)?)
+}
+
+/// Calc reward amount for one full epoch with APR.
+pub fn calc_epoch_reward_amount(amount: u64, apr: u16, epoch_period_days: u16) -> Result<u64> {
+    let new_amount = calc_grown_amount(amount, calc_compound_growth(apr, epoch_period_days));
+    Ok(new_amount
+        .checked_sub(amount)
+        .ok_or(StakingError::Overflow)?)
+}
+
+/// Calc epoch reward amount rounded up by the growth error bound (see WAD),
+/// so it's never below the exact reward of any part of the amount.
+pub fn calc_max_epoch_reward_amount(amount: u64, apr: u16, epoch_period_days: u16) -> Result<u64> {
+    if amount == 0 {
+        return Ok(0);
+    }
+    let reward = calc_epoch_reward_amount(amount, apr, epoch_period_days)?;
+    let error_bound = (amount as u128 + reward as u128)
+        .checked_mul(2 * epoch_period_days as u128 + 17)
+        .and_then(|error| error.checked_add(WAD - 1))
+        .ok_or(StakingError::Overflow)?
+        / WAD
+        + 2;
+
+    Ok(u64::try_from(reward as u128 + error_bound).map_err(|_| StakingError::Overflow)?)
+}
+
+/// Checks staking fund can cover liabilities and the next epoch reward of total staked.
+pub fn is_staking_fund_covered(
+    staking_fund_amount: u64,
+    staking_info: &StakingInfo,
+    apr_schedule: &[u16],
+    total_boosted_staked: u64,
+) -> Result<bool> {
+    let current_apr = get_scheduled_apr(apr_schedule, staking_info.epoch)?;
+    let next_epoch_reward = calc_epoch_reward_amount(
+        total_boosted_staked,
+        current_apr,
+        staking_info.epoch_period_days,
+    )?;
+    let required_amount = staking_info
+        .reward_liability
+        .checked_add(staking_info.total_iou)
+        .ok_or(StakingError::Overflow)?
+        .checked_add(next_epoch_reward)
+        .ok_or(StakingError::Overflow)?;
+
+    Ok(staking_fund_amount >= required_amount)
+}
+
+/// Get the recorded APR of completed epoch.
+/// Epochs after the full history use the last recorded APR.
+pub fn get_recorded_apr(epoch_aprs: &[u16], epoch: u64) -> Result<u16> {
+    let index = epoch.checked_sub(1).ok_or(StakingError::Overflow)? as usize;
+    let apr = match epoch_aprs.get(index) {
+        Some(apr) => Some(*apr),
+        None if epoch_aprs.len() >= EPOCH_HISTORY_MAX_LEN => epoch_aprs.last().copied(),
+        None => None,
+    };
+
+    Ok(apr.ok_or(StakingError::EpochNotRecorded)?)
+}
+
 /// Get the vector of epoch past since start time
 pub fn calc_user_past_epochs(
     staking_start_time: UnixTimestamp,
@@ -79,15 +484,11 @@ pub fn calc_user_past_epochs(
     epoch_period_days: u16,
 ) -> Result<Vec<u64>> {
     let mut epochs = vec![];
-    let (mut user_start_epoch, is_user_start_epoch_full) =
-        get_epoch_by_time(staking_start_time, user_stake_time, epoch_period_days)?;
+    let user_start_epoch =
+        get_position_start_epoch(staking_start_time, user_stake_time, epoch_period_days)?;
     let (user_end_epoch, _) =
         get_epoch_by_time(staking_start_time, current_time, epoch_period_days)?;
 
-    if !is_user_start_epoch_full {
-        user_start_epoch += 1;
-    }
-
     msg!("User start epoch: {}", user_start_epoch);
     msg!("User end epoch: {}", user_end_epoch);
     for epoch in user_start_epoch..user_end_epoch {
@@ -101,129 +502,156 @@ pub fn calc_user_past_epochs(
 pub fn calc_user_reward_amount(
     epoch_period_days: u16,
     staking_start_time: UnixTimestamp,
-    staking_start_apr: u8,
-    staking_step_apr: u8,
-    staking_end_apr: u8,
+    epoch_aprs: &[u16],
     user_staked_amount: u64,
     user_stake_time: UnixTimestamp,
     current_time: UnixTimestamp,
 ) -> Result<u64> {
-    let epochs = calc_user_past_epochs(
+    let (user_reward, _) = calc_user_unclaimed_reward_amount(
+        epoch_period_days,
+        staking_start_time,
+        epoch_aprs,
+        user_staked_amount,
+        user_stake_time,
+        0,
+        BASE_APR_BOOST,
+        current_time,
+    )?;
+
+    Ok(user_reward)
+}
+
+/// Calc user reward amount for the past epochs after the last claimed epoch.
+/// Returns the reward amount and the last rewarded epoch.
+pub fn calc_user_unclaimed_reward_amount(
+    epoch_period_days: u16,
+    staking_start_time: UnixTimestamp,
+    epoch_aprs: &[u16],
+    user_staked_amount: u64,
+    user_stake_time: UnixTimestamp,
+    claimed_epoch: u64,
+    apr_boost: u16,
+    current_time: UnixTimestamp,
+) -> Result<(u64, u64)> {
+    let mut epochs = calc_user_past_epochs(
         staking_start_time,
         user_stake_time,
         current_time,
         epoch_period_days,
     )?;
+    epochs.retain(|epoch| *epoch > claimed_epoch);
     msg!("User epochs: {:?}", epochs);
+    let last_epoch = epochs.last().copied().unwrap_or(claimed_epoch);
 
-    let mut user_new_amount = amount_to_ui_amount(user_staked_amount, 9);
-    println!("User start amount ui: {}", user_new_amount);
+    let mut growth = Some(WAD);
     for epoch in epochs {
-        let current_apr =
-            get_apr_by_epoch(epoch, staking_start_apr, staking_step_apr, staking_end_apr)? as f64;
-        println!("Current epoch: {}", epoch);
-        println!("Current apr %: {}", current_apr);
-        let current_apr = current_apr / 100.0;
-        println!("Current apr: {}", current_apr);
-        user_new_amount =
-            user_new_amount.mul((1.0 + current_apr / 365.0).powi(epoch_period_days as i32));
-        println!("Current new amount ui: {}", user_new_amount);
-    }
-
-    println!("User new amount ui: {}", user_new_amount);
-    let user_new_amount = ui_amount_to_amount(user_new_amount, 9);
-    println!("User new amount: {}", user_new_amount);
+        let current_apr = calc_boosted_apr(get_recorded_apr(epoch_aprs, epoch)?, apr_boost)?;
+        growth = growth.and_then(|growth| {
+            mul_wad(
+                growth,
+                calc_compound_growth(current_apr, epoch_period_days)?,
+            )
+        });
+    }
+
+    let user_new_amount = calc_grown_amount(user_staked_amount, growth);
     let user_reward = user_new_amount
         .checked_sub(user_staked_amount)
         .ok_or(StakingError::Overflow)?;
 
-    println!("User reward amount: {}", user_reward);
-    println!(
-        "User reward amount ui: {}",
-        amount_to_ui_amount(user_reward, 9)
-    );
-
-    Ok(user_reward)
+    Ok((user_reward, last_epoch))
 }
 
 #[cfg(test)]
 #[allow(non_upper_case_globals)]
 mod tests {
     use super::*;
+    use crate::metadata::{MAX_NAME_LENGTH, MAX_URI_LENGTH};
+    use num_bigint::BigUint;
+    use std::str::FromStr;
 
     const time: i64 = 1660032700;
     const day: i64 = 24 * 60 * 60;
 
+    fn aprs(start_apr: u8, step_apr: u8, end_apr: u8) -> Vec<u16> {
+        (1..=10000)
+            .map(|epoch| {
+                get_apr_by_epoch(epoch, start_apr, step_apr, end_apr).unwrap() as u16
+                    * BPS_IN_PERCENT
+            })
+            .collect()
+    }
+
     #[test]
     pub fn test_calc_user_reward_amount() {
         let amount = 10_000_000_000;
         // User rewards for first epoch
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time, time + 10 * day),
+            calc_user_reward_amount(10, time, &aprs(10, 1, 5), amount, time, time + 10 * day),
             Ok(27431062) // 0.027431062
         );
         // User stake in half epoch
         let amount = 10_000_000_000;
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 15 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 15 * day),
             Ok(123973918)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 83, 1, 5, amount, time, time + 15 * day),
+            calc_user_reward_amount(10, time, &aprs(83, 1, 5), amount, time, time + 15 * day),
             Ok(229738355)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 99, 1, 5, amount, time, time + 15 * day),
+            calc_user_reward_amount(10, time, &aprs(99, 1, 5), amount, time, time + 15 * day),
             Ok(274567462)
         );
 
         // Amounts less than 1 GGWP
         let amount = 500_000_000; // 0.5
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 20 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 20 * day),
             Ok(12334025)
         );
 
         let amount = 800; // 0.0000008
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 20 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 20 * day),
             Ok(19)
         );
 
         // User stake in next epoch
         let amount = 19_000_000_000;
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 20 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 20 * day),
             Ok(468692977)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 30 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 30 * day),
             Ok(699269917)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 40 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 40 * day),
             Ok(927123806)
         );
 
         // User stake in half next epoch
         let amount = 1299_500_000_000;
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 25 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 25 * day),
             Ok(32056132852)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 35 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 35 * day),
             Ok(47826381988)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 45 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 45 * day),
             Ok(63410388776)
         );
 
         // Check min apr limit
         let amount = 5_000_000_000;
         assert_eq!(
-            calc_user_reward_amount(10, time, 6, 1, 5, amount, time, time + 50 * day),
+            calc_user_reward_amount(10, time, &aprs(6, 1, 5), amount, time, time + 50 * day),
             Ok(35741023)
         );
 
@@ -231,15 +659,15 @@ mod tests {
         println!("-----");
         let amount = 100000_000_000_000;
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 100 * day),
-            Ok(11727991191434)
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 100 * day),
+            Ok(11727991191433) // f64: 11727991191434
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 1000 * day),
-            Ok(43551067839644)
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 1000 * day),
+            Ok(43551067839653) // f64: 43551067839644
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 45, 1, 5, amount, time, time + 100000 * day),
+            calc_user_reward_amount(10, time, &aprs(45, 1, 5), amount, time, time + 100000 * day),
             Ok(18446644073709551615)
         );
     }
@@ -248,31 +676,73 @@ mod tests {
     pub fn test_calc_user_reward_amount_zero_epochs() {
         let amount = 100_000_000_000;
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time, time + 5 * day),
+            calc_user_reward_amount(10, time, &aprs(10, 1, 5), amount, time, time + 5 * day),
             Ok(0)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time + 5 * day, time + 5 * day),
+            calc_user_reward_amount(
+                10,
+                time,
+                &aprs(10, 1, 5),
+                amount,
+                time + 5 * day,
+                time + 5 * day
+            ),
             Ok(0)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time + 5 * day, time + 10 * day),
+            calc_user_reward_amount(
+                10,
+                time,
+                &aprs(10, 1, 5),
+                amount,
+                time + 5 * day,
+                time + 10 * day
+            ),
             Ok(0)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time + 5 * day, time + 15 * day),
+            calc_user_reward_amount(
+                10,
+                time,
+                &aprs(10, 1, 5),
+                amount,
+                time + 5 * day,
+                time + 15 * day
+            ),
             Ok(0)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time + 10 * day, time + 10 * day),
+            calc_user_reward_amount(
+                10,
+                time,
+                &aprs(10, 1, 5),
+                amount,
+                time + 10 * day,
+                time + 10 * day
+            ),
             Ok(0)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time + 15 * day, time + 15 * day),
+            calc_user_reward_amount(
+                10,
+                time,
+                &aprs(10, 1, 5),
+                amount,
+                time + 15 * day,
+                time + 15 * day
+            ),
             Ok(0)
         );
         assert_eq!(
-            calc_user_reward_amount(10, time, 10, 1, 5, amount, time + 15 * day, time + 20 * day),
+            calc_user_reward_amount(
+                10,
+                time,
+                &aprs(10, 1, 5),
+                amount,
+                time + 15 * day,
+                time + 20 * day
+            ),
             Ok(0)
         );
     }
@@ -487,4 +957,889 @@ mod tests {
         assert_eq!(get_apr_by_epoch(43, 45, 1, 5), Ok(5));
         assert_eq!(get_apr_by_epoch(44, 45, 1, 5), Ok(5));
     }
+
+    #[test]
+    pub fn test_advance_epochs() {
+        let mut staking_info = StakingInfo {
+            start_time: time,
+            epoch: 1,
+            epoch_period_days: 10,
+            ..Default::default()
+        };
+        let mut apr_schedule = build_apr_schedule(45, 1, 5).unwrap();
+        let mut epoch_aprs = vec![];
+
+        assert_eq!(
+            advance_epochs(&mut staking_info, &apr_schedule, &mut epoch_aprs, time),
+            Ok(0)
+        );
+        assert_eq!(
+            advance_epochs(
+                &mut staking_info,
+                &apr_schedule,
+                &mut epoch_aprs,
+                time + 9 * day
+            ),
+            Ok(0)
+        );
+        assert_eq!(staking_info.epoch, 1);
+        assert_eq!(
+            advance_epochs(
+                &mut staking_info,
+                &apr_schedule,
+                &mut epoch_aprs,
+                time + 10 * day
+            ),
+            Ok(1)
+        );
+        assert_eq!(staking_info.epoch, 2);
+        assert_eq!(epoch_aprs, vec![4500]);
+
+        // Recorded APR is kept after schedule change
+        apr_schedule[0] = 2000;
+        apr_schedule[1] = 1950;
+        assert_eq!(
+            advance_epochs(
+                &mut staking_info,
+                &apr_schedule,
+                &mut epoch_aprs,
+                time + 35 * day
+            ),
+            Ok(2)
+        );
+        assert_eq!(staking_info.epoch, 4);
+        assert_eq!(epoch_aprs, vec![4500, 1950, 4300]);
+        assert_eq!(
+            advance_epochs(
+                &mut staking_info,
+                &apr_schedule,
+                &mut epoch_aprs,
+                time + 39 * day
+            ),
+            Ok(0)
+        );
+
+        // History is limited by max len
+        let mut epoch_aprs = vec![500; EPOCH_HISTORY_MAX_LEN];
+        assert_eq!(
+            advance_epochs(
+                &mut staking_info,
+                &apr_schedule,
+                &mut epoch_aprs,
+                time + 50 * day
+            ),
+            Ok(2)
+        );
+        assert_eq!(epoch_aprs.len(), EPOCH_HISTORY_MAX_LEN);
+    }
+
+    #[test]
+    pub fn test_build_apr_schedule() {
+        assert_eq!(
+            build_apr_schedule(10, 1, 5),
+            Ok(vec![1000, 900, 800, 700, 600, 500])
+        );
+        assert_eq!(
+            build_apr_schedule(45, 10, 5),
+            Ok(vec![4500, 3500, 2500, 1500, 500])
+        );
+        assert_eq!(build_apr_schedule(5, 1, 5), Ok(vec![500]));
+        assert_eq!(build_apr_schedule(5, 1, 10), Ok(vec![1000]));
+        let apr_schedule = build_apr_schedule(45, 1, 5).unwrap();
+        for epoch in 1..100 {
+            assert_eq!(
+                get_scheduled_apr(&apr_schedule, epoch),
+                Ok(get_apr_by_epoch(epoch, 45, 1, 5).unwrap() as u16 * BPS_IN_PERCENT)
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_get_scheduled_apr() {
+        assert_eq!(get_scheduled_apr(&[4500, 4450], 1), Ok(4500));
+        assert_eq!(get_scheduled_apr(&[4500, 4450], 2), Ok(4450));
+        assert_eq!(get_scheduled_apr(&[4500, 4450], 100), Ok(4450));
+        assert_eq!(
+            get_scheduled_apr(&[], 1),
+            Err(StakingError::InvalidAPRSchedule.into())
+        );
+        assert_eq!(
+            get_scheduled_apr(&[4500], 0),
+            Err(StakingError::Overflow.into())
+        );
+    }
+
+    #[test]
+    pub fn test_set_scheduled_aprs() {
+        let mut apr_schedule = vec![4500, 4400, 4300];
+        // Only future epochs can be changed
+        assert_eq!(
+            set_scheduled_aprs(&mut apr_schedule, 2, 2, &[1000]),
+            Err(StakingError::InvalidAPRScheduleEpoch.into())
+        );
+        assert_eq!(
+            set_scheduled_aprs(&mut apr_schedule, 2, 1, &[1000]),
+            Err(StakingError::InvalidAPRScheduleEpoch.into())
+        );
+        assert_eq!(
+            set_scheduled_aprs(&mut apr_schedule, 2, 3, &[]),
+            Err(StakingError::InvalidAPRSchedule.into())
+        );
+
+        assert_eq!(
+            set_scheduled_aprs(&mut apr_schedule, 2, 3, &[4250, 4225]),
+            Ok(())
+        );
+        assert_eq!(apr_schedule, vec![4500, 4400, 4250, 4225]);
+
+        // Gap is filled with the last APR
+        assert_eq!(set_scheduled_aprs(&mut apr_schedule, 2, 7, &[100]), Ok(()));
+        assert_eq!(apr_schedule, vec![4500, 4400, 4250, 4225, 4225, 4225, 100]);
+
+        // Schedule is limited by max len
+        let max_epoch = APR_SCHEDULE_MAX_LEN as u64;
+        assert_eq!(
+            set_scheduled_aprs(&mut apr_schedule, 2, max_epoch, &[100, 100]),
+            Err(StakingError::InvalidAPRSchedule.into())
+        );
+        assert_eq!(
+            set_scheduled_aprs(&mut apr_schedule, 2, max_epoch, &[50]),
+            Ok(())
+        );
+        assert_eq!(apr_schedule.len(), APR_SCHEDULE_MAX_LEN);
+        assert_eq!(get_scheduled_apr(&apr_schedule, 5000), Ok(50));
+    }
+
+    #[test]
+    pub fn test_get_recorded_apr() {
+        assert_eq!(get_recorded_apr(&[45, 44, 43], 1), Ok(45));
+        assert_eq!(get_recorded_apr(&[45, 44, 43], 3), Ok(43));
+        assert_eq!(
+            get_recorded_apr(&[45, 44, 43], 4),
+            Err(StakingError::EpochNotRecorded.into())
+        );
+        assert_eq!(
+            get_recorded_apr(&[], 1),
+            Err(StakingError::EpochNotRecorded.into())
+        );
+        assert_eq!(
+            get_recorded_apr(&[45, 44, 43], 0),
+            Err(StakingError::Overflow.into())
+        );
+
+        let mut epoch_aprs = vec![1000; EPOCH_HISTORY_MAX_LEN];
+        epoch_aprs[EPOCH_HISTORY_MAX_LEN - 1] = 500;
+        assert_eq!(
+            get_recorded_apr(&epoch_aprs, EPOCH_HISTORY_MAX_LEN as u64 + 10),
+            Ok(500)
+        );
+    }
+
+    #[test]
+    pub fn test_calc_user_reward_amount_recorded_aprs() {
+        // Rewards use the recorded APR instead of the current params
+        let amount = 10_000_000_000;
+        assert_eq!(
+            calc_user_reward_amount(10, time, &[1000], amount, time, time + 10 * day),
+            Ok(27431062)
+        );
+        assert_eq!(
+            calc_user_reward_amount(10, time, &[1000], amount, time, time + 20 * day),
+            Err(StakingError::EpochNotRecorded.into())
+        );
+    }
+
+    #[test]
+    pub fn test_calc_user_unclaimed_reward_amount() {
+        let amount = 19_000_000_000;
+        let epoch_aprs = aprs(45, 1, 5);
+        assert_eq!(
+            calc_user_unclaimed_reward_amount(
+                10,
+                time,
+                &epoch_aprs,
+                amount,
+                time,
+                0,
+                BASE_APR_BOOST,
+                time + 5 * day
+            ),
+            Ok((0, 0))
+        );
+        assert_eq!(
+            calc_user_unclaimed_reward_amount(
+                10,
+                time,
+                &epoch_aprs,
+                amount,
+                time,
+                0,
+                BASE_APR_BOOST,
+                time + 20 * day
+            ),
+            Ok((468692977, 2))
+        );
+
+        // Claimed epochs are skipped
+        let (first_reward, _) = calc_user_unclaimed_reward_amount(
+            10,
+            time,
+            &epoch_aprs,
+            amount,
+            time,
+            0,
+            BASE_APR_BOOST,
+            time + 10 * day,
+        )
+        .unwrap();
+        let (second_reward, last_epoch) = calc_user_unclaimed_reward_amount(
+            10,
+            time,
+            &epoch_aprs,
+            amount,
+            time,
+            1,
+            BASE_APR_BOOST,
+            time + 20 * day,
+        )
+        .unwrap();
+        assert_eq!(last_epoch, 2);
+        // Rewards are not compounded after claim
+        assert!(first_reward + second_reward < 468692977);
+        assert_eq!(
+            calc_user_unclaimed_reward_amount(
+                10,
+                time,
+                &epoch_aprs,
+                amount,
+                time,
+                2,
+                BASE_APR_BOOST,
+                time + 25 * day
+            ),
+            Ok((0, 2))
+        );
+
+        // Boosted APR is applied to recorded APR
+        let boosted_epoch_aprs: Vec<u16> = epoch_aprs.iter().map(|apr| apr * 2).collect();
+        assert_eq!(
+            calc_user_unclaimed_reward_amount(
+                10,
+                time,
+                &epoch_aprs,
+                amount,
+                time,
+                0,
+                2 * BASE_APR_BOOST,
+                time + 20 * day
+            ),
+            calc_user_unclaimed_reward_amount(
+                10,
+                time,
+                &boosted_epoch_aprs,
+                amount,
+                time,
+                0,
+                BASE_APR_BOOST,
+                time + 20 * day
+            )
+        );
+    }
+
+    #[test]
+    pub fn test_calc_user_unclaimed_reward_amount_compounded() {
+        let amount = 19_000_000_000;
+        let epoch_aprs = aprs(45, 1, 5);
+        let (total_reward, _) = calc_user_unclaimed_reward_amount(
+            10,
+            time,
+            &epoch_aprs,
+            amount,
+            time,
+            0,
+            BASE_APR_BOOST,
+            time + 50 * day,
+        )
+        .unwrap();
+
+        // Rewards compounded into amount at each epoch boundary
+        let mut compounded_amount = amount;
+        for epoch in 1..=5 {
+            let (reward, last_epoch) = calc_user_unclaimed_reward_amount(
+                10,
+                time,
+                &epoch_aprs,
+                compounded_amount,
+                time,
+                epoch - 1,
+                BASE_APR_BOOST,
+                time + epoch as i64 * 10 * day,
+            )
+            .unwrap();
+            assert_eq!(last_epoch, epoch);
+            compounded_amount += reward;
+        }
+        assert!(amount + total_reward - compounded_amount <= 5);
+    }
+
+    #[test]
+    pub fn test_is_lockup_ended() {
+        assert_eq!(is_lockup_ended(time, time, 0), Ok(true));
+        assert_eq!(is_lockup_ended(time + 89 * day, time, 90), Ok(false));
+        assert_eq!(is_lockup_ended(time + 90 * day - 1, time, 90), Ok(false));
+        assert_eq!(is_lockup_ended(time + 90 * day, time, 90), Ok(true));
+        assert_eq!(is_lockup_ended(time + 400 * day, time, 365), Ok(true));
+    }
+
+    #[test]
+    pub fn test_is_valid_lockup_options() {
+        let option = |lockup_days, apr_boost| LockupOption {
+            lockup_days,
+            apr_boost,
+        };
+        assert!(is_valid_lockup_options(&[option(0, BASE_APR_BOOST)]));
+        assert!(is_valid_lockup_options(&[
+            option(0, BASE_APR_BOOST),
+            option(90, 12_500),
+            option(365, 20_000),
+        ]));
+        assert!(!is_valid_lockup_options(&[]));
+        assert!(!is_valid_lockup_options(&[option(0, 9_999)]));
+        assert!(!is_valid_lockup_options(
+            &[option(0, BASE_APR_BOOST); LOCKUP_OPTIONS_MAX_LEN + 1]
+        ));
+    }
+
+    #[test]
+    pub fn test_is_valid_boosted_aprs() {
+        let mut staking_info = StakingInfo {
+            lockup_options: vec![LockupOption {
+                lockup_days: 0,
+                apr_boost: BASE_APR_BOOST,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(get_max_apr_boost(&staking_info), BASE_APR_BOOST);
+        assert!(is_valid_boosted_aprs(&[4500, u16::MAX], BASE_APR_BOOST));
+
+        // Staked positions keep the boost of the removed lockup option
+        staking_info.reward_accruals.push(RewardAccrual {
+            apr_boost: 2 * BASE_APR_BOOST,
+            ..Default::default()
+        });
+        assert_eq!(get_max_apr_boost(&staking_info), 2 * BASE_APR_BOOST);
+        assert!(is_valid_boosted_aprs(&[4500, 32_767], 2 * BASE_APR_BOOST));
+        // 350% APR with 2x boost
+        assert!(!is_valid_boosted_aprs(&[4500, 35_000], 2 * BASE_APR_BOOST));
+        assert!(!is_valid_boosted_aprs(&[u16::MAX], u16::MAX));
+        assert!(is_valid_boosted_aprs(&[], u16::MAX));
+    }
+
+    #[test]
+    pub fn test_position_nft_metadata() {
+        assert!(is_valid_position_nft_uri("https://a.b"));
+        assert!(is_valid_position_nft_uri(
+            &"a".repeat(MAX_POSITION_NFT_URI_LEN)
+        ));
+        assert!(!is_valid_position_nft_uri(""));
+        assert!(!is_valid_position_nft_uri(
+            &"a".repeat(MAX_POSITION_NFT_URI_LEN + 1)
+        ));
+
+        let position = Pubkey::from_str("5ihGT7nkjxfo1M43NZrPbbDBG4Js215ftJp6uksnNCEP").unwrap();
+        assert_eq!(
+            get_position_nft_name(&position),
+            "GGWP Staking Position 5ihGT7nkjx"
+        );
+        assert!(get_position_nft_name(&position).len() <= MAX_NAME_LENGTH);
+        let other_position =
+            Pubkey::from_str("79GShMQgEBcfpiiwkBxv3yBxHqCN8J2E8DhivatqpfYC").unwrap();
+        assert_ne!(
+            get_position_nft_name(&position),
+            get_position_nft_name(&other_position)
+        );
+
+        assert_eq!(
+            get_position_nft_uri("https://a.b/positions/", &position),
+            "https://a.b/positions/5ihGT7nkjxfo1M43NZrPbbDBG4Js215ftJp6uksnNCEP.json"
+        );
+        let uri = "a".repeat(MAX_POSITION_NFT_URI_LEN);
+        assert!(get_position_nft_uri(&uri, &position).len() <= MAX_URI_LENGTH);
+    }
+
+    #[test]
+    pub fn test_calc_boosted_amount() {
+        assert_eq!(calc_boosted_amount(1_000, BASE_APR_BOOST), Ok(1_000));
+        assert_eq!(calc_boosted_amount(1_000, 12_500), Ok(1_250));
+        assert_eq!(calc_boosted_amount(999, 15_000), Ok(1_498));
+        assert!(calc_boosted_amount(u64::MAX, 20_000).is_err());
+    }
+
+    #[test]
+    pub fn test_calc_boosted_apr() {
+        assert_eq!(calc_boosted_apr(4500, BASE_APR_BOOST), Ok(4500));
+        assert_eq!(calc_boosted_apr(4500, 12_500), Ok(5625));
+        assert_eq!(calc_boosted_apr(4500, 20_000), Ok(9000));
+        assert!(calc_boosted_apr(50_000, 20_000).is_err());
+    }
+
+    #[test]
+    pub fn test_calc_epoch_reward_amount() {
+        assert_eq!(calc_epoch_reward_amount(0, 4500, 10), Ok(0));
+        assert_eq!(
+            calc_epoch_reward_amount(10_000_000_000, 1000, 10),
+            Ok(27431062)
+        );
+        assert_eq!(
+            calc_epoch_reward_amount(19_000_000_000, 4500, 10),
+            calc_user_reward_amount(
+                10,
+                time,
+                &aprs(45, 1, 5),
+                19_000_000_000,
+                time,
+                time + 10 * day
+            )
+        );
+    }
+
+    #[test]
+    pub fn test_advance_epochs_reward_liability() {
+        let mut staking_info = StakingInfo {
+            start_time: time,
+            epoch: 1,
+            epoch_period_days: 10,
+            ..Default::default()
+        };
+        let apr_schedule = build_apr_schedule(10, 1, 5).unwrap();
+        let mut epoch_aprs = vec![];
+
+        // Stake in the first day accrues the epoch, the later stake starts from the next one
+        let stake_time = time + day / 2;
+        let late_stake_time = time + 3 * day;
+        let start_epoch = get_position_start_epoch(time, stake_time, 10).unwrap();
+        let late_start_epoch = get_position_start_epoch(time, late_stake_time, 10).unwrap();
+        assert_eq!((start_epoch, late_start_epoch), (1, 2));
+        add_reward_accrual(
+            &mut staking_info,
+            BASE_APR_BOOST,
+            10_000_000_000,
+            start_epoch,
+        )
+        .unwrap();
+        add_reward_accrual(
+            &mut staking_info,
+            BASE_APR_BOOST,
+            5_000_000_000,
+            late_start_epoch,
+        )
+        .unwrap();
+        add_reward_accrual(&mut staking_info, 20_000, 2_000_000_000, start_epoch).unwrap();
+        assert_eq!(
+            staking_info.reward_accruals,
+            vec![
+                RewardAccrual {
+                    apr_boost: BASE_APR_BOOST,
+                    amount: 10_000_000_000,
+                    pending_amount: 5_000_000_000,
+                    positions_count: 2,
+                },
+                RewardAccrual {
+                    apr_boost: 20_000,
+                    amount: 2_000_000_000,
+                    pending_amount: 0,
+                    positions_count: 1,
+                },
+            ]
+        );
+
+        advance_epochs(
+            &mut staking_info,
+            &apr_schedule,
+            &mut epoch_aprs,
+            time + 10 * day,
+        )
+        .unwrap();
+        let epoch_reward = calc_max_epoch_reward_amount(10_000_000_000, 1000, 10).unwrap();
+        assert_eq!(
+            staking_info.reward_liability,
+            epoch_reward + calc_max_epoch_reward_amount(2_000_000_000, 2000, 10).unwrap()
+        );
+        assert_eq!(
+            staking_info.reward_accruals[0].amount,
+            15_000_000_000 + epoch_reward
+        );
+        assert_eq!(staking_info.reward_accruals[0].pending_amount, 0);
+
+        advance_epochs(
+            &mut staking_info,
+            &apr_schedule,
+            &mut epoch_aprs,
+            time + 30 * day,
+        )
+        .unwrap();
+        assert_eq!(epoch_aprs, vec![1000, 900, 800]);
+
+        // Liability covers the exact compounded rewards from the position start epochs
+        let exact_reward = reference_reward_amount(10_000_000_000, 10, &[1000, 900, 800])
+            + reference_reward_amount(5_000_000_000, 10, &[900, 800])
+            + reference_reward_amount(2_000_000_000, 10, &[2000, 1800, 1600]);
+        assert!(staking_info.reward_liability >= exact_reward);
+        assert!(staking_info.reward_liability - exact_reward <= 20);
+
+        let calc_reward = |amount: u64, stake_time: i64, apr_boost: u16| {
+            calc_user_unclaimed_reward_amount(
+                10,
+                time,
+                &epoch_aprs,
+                amount,
+                stake_time,
+                0,
+                apr_boost,
+                time + 30 * day,
+            )
+            .unwrap()
+            .0
+        };
+        let reward = calc_reward(10_000_000_000, stake_time, BASE_APR_BOOST);
+        let late_reward = calc_reward(5_000_000_000, late_stake_time, BASE_APR_BOOST);
+        let boosted_reward = calc_reward(2_000_000_000, stake_time, 20_000);
+        assert!(reward + late_reward + boosted_reward <= exact_reward);
+
+        // Compounded reward stays in the accrual, claimed reward leaves it
+        release_accrued_reward(&mut staking_info, BASE_APR_BOOST, reward, true).unwrap();
+        release_accrued_reward(&mut staking_info, 20_000, boosted_reward, false).unwrap();
+
+        // Withdrawn positions leave with the stake and the reward
+        release_accrued_reward(&mut staking_info, BASE_APR_BOOST, late_reward, false).unwrap();
+        remove_reward_accrual(
+            &mut staking_info,
+            BASE_APR_BOOST,
+            5_000_000_000,
+            late_start_epoch,
+        )
+        .unwrap();
+        remove_reward_accrual(&mut staking_info, 20_000, 2_000_000_000, start_epoch).unwrap();
+
+        // Only the rounding margin stays over the remaining stake with the compounded reward
+        assert_eq!(staking_info.reward_accruals.len(), 1);
+        let accrual = staking_info.reward_accruals[0];
+        assert_eq!(accrual.positions_count, 1);
+        assert!(accrual.amount >= 10_000_000_000 + reward);
+        assert!(accrual.amount - 10_000_000_000 - reward <= 20);
+        assert_eq!(
+            staking_info.reward_liability,
+            accrual.amount - 10_000_000_000 - reward
+        );
+
+        let liability = staking_info.reward_liability;
+        assert_eq!(
+            release_accrued_reward(&mut staking_info, BASE_APR_BOOST, liability + 1, false),
+            Err(StakingError::InvalidRewardLiability.into())
+        );
+        remove_reward_accrual(
+            &mut staking_info,
+            BASE_APR_BOOST,
+            10_000_000_000 + reward,
+            start_epoch,
+        )
+        .unwrap();
+        assert!(staking_info.reward_accruals.is_empty());
+        assert_eq!(staking_info.reward_liability, 0);
+    }
+
+    #[test]
+    pub fn test_reward_accruals() {
+        let mut staking_info = StakingInfo {
+            epoch: 3,
+            ..Default::default()
+        };
+
+        // Position withdrawn before its start epoch leaves the pending amount
+        add_reward_accrual(&mut staking_info, BASE_APR_BOOST, 1_000, 3).unwrap();
+        add_reward_accrual(&mut staking_info, BASE_APR_BOOST, 500, 4).unwrap();
+        remove_reward_accrual(&mut staking_info, BASE_APR_BOOST, 500, 4).unwrap();
+        assert_eq!(
+            staking_info.reward_accruals,
+            vec![RewardAccrual {
+                apr_boost: BASE_APR_BOOST,
+                amount: 1_000,
+                pending_amount: 0,
+                positions_count: 1,
+            }]
+        );
+        assert_eq!(
+            remove_reward_accrual(&mut staking_info, BASE_APR_BOOST, 1_001, 3),
+            Err(StakingError::InvalidRewardLiability.into())
+        );
+        assert_eq!(
+            remove_reward_accrual(&mut staking_info, 20_000, 1, 3),
+            Err(StakingError::InvalidRewardLiability.into())
+        );
+        remove_reward_accrual(&mut staking_info, BASE_APR_BOOST, 1_000, 3).unwrap();
+        assert!(staking_info.reward_accruals.is_empty());
+
+        for i in 0..REWARD_ACCRUALS_MAX_LEN {
+            add_reward_accrual(&mut staking_info, BASE_APR_BOOST + i as u16, 1, 3).unwrap();
+        }
+        add_reward_accrual(&mut staking_info, BASE_APR_BOOST, 1, 3).unwrap();
+        assert_eq!(
+            add_reward_accrual(&mut staking_info, 1, 1, 3),
+            Err(StakingError::TooManyRewardAccruals.into())
+        );
+    }
+
+    #[test]
+    pub fn test_advance_epochs_reward_per_share_no_liability() {
+        let mut staking_info = StakingInfo {
+            start_time: time,
+            epoch: 1,
+            epoch_period_days: 10,
+            total_boosted_staked: 10_000_000_000,
+            pool_mode: PoolMode::RewardPerShare,
+            ..Default::default()
+        };
+        let apr_schedule = build_apr_schedule(10, 1, 5).unwrap();
+        let mut epoch_aprs = vec![];
+
+        advance_epochs(
+            &mut staking_info,
+            &apr_schedule,
+            &mut epoch_aprs,
+            time + 20 * day,
+        )
+        .unwrap();
+        assert_eq!(staking_info.epoch, 3);
+        assert_eq!(staking_info.reward_liability, 0);
+    }
+
+    #[test]
+    pub fn test_update_reward_per_share() {
+        let mut staking_info = StakingInfo {
+            pool_mode: PoolMode::RewardPerShare,
+            ..Default::default()
+        };
+
+        // Nothing staked, deposits wait for stakers
+        update_reward_per_share(&mut staking_info, 1_000).unwrap();
+        assert_eq!(staking_info.reward_per_share, 0);
+        assert_eq!(staking_info.reward_reserve, 0);
+
+        staking_info.total_boosted_staked = 4_000;
+        update_reward_per_share(&mut staking_info, 1_000).unwrap();
+        assert_eq!(staking_info.reward_per_share, WAD / 4);
+        assert_eq!(staking_info.reward_reserve, 1_000);
+
+        // No new deposits
+        update_reward_per_share(&mut staking_info, 1_000).unwrap();
+        assert_eq!(staking_info.reward_per_share, WAD / 4);
+
+        staking_info.total_boosted_staked = 3_000;
+        update_reward_per_share(&mut staking_info, 2_000).unwrap();
+        assert_eq!(staking_info.reward_per_share, WAD / 4 + WAD / 3);
+        assert_eq!(staking_info.reward_reserve, 2_000);
+
+        // Paid out rewards leave the reserve
+        staking_info.reward_reserve = 500;
+        update_reward_per_share(&mut staking_info, 500).unwrap();
+        assert_eq!(staking_info.reward_per_share, WAD / 4 + WAD / 3);
+    }
+
+    #[test]
+    pub fn test_calc_pending_reward() {
+        assert_eq!(calc_pending_reward(4_000, WAD / 4, 0).unwrap(), 1_000);
+        assert_eq!(calc_pending_reward(4_000, WAD / 4, WAD / 4).unwrap(), 0);
+        assert_eq!(
+            calc_pending_reward(3_000, WAD / 4 + WAD / 3, WAD / 4).unwrap(),
+            999
+        );
+        assert_eq!(calc_pending_reward(0, WAD, 0).unwrap(), 0);
+        assert!(calc_pending_reward(1, 0, 1).is_err());
+        assert!(calc_pending_reward(u64::MAX, 2 * WAD, 0).is_err());
+    }
+
+    #[test]
+    pub fn test_reward_per_share_pro_rata() {
+        let mut staking_info = StakingInfo {
+            pool_mode: PoolMode::RewardPerShare,
+            ..Default::default()
+        };
+        let mut rng = Lcg(7);
+        let mut fund: u64 = 0;
+        let mut paid: u64 = 0;
+        let amounts: Vec<u64> = (0..10)
+            .map(|_| 1 + rng.next(1_000_000_000) * 1_000)
+            .collect();
+        staking_info.total_boosted_staked = amounts.iter().sum();
+        let mut checkpoints = vec![0u128; amounts.len()];
+
+        for _ in 0..100 {
+            fund += rng.next(1_000_000_000) * 10;
+            update_reward_per_share(&mut staking_info, fund).unwrap();
+
+            let index = rng.next(amounts.len() as u64) as usize;
+            let reward = calc_pending_reward(
+                amounts[index],
+                staking_info.reward_per_share,
+                checkpoints[index],
+            )
+            .unwrap();
+            checkpoints[index] = staking_info.reward_per_share;
+            fund -= reward;
+            paid += reward;
+            staking_info.reward_reserve -= reward;
+        }
+
+        // Rewards never exceed deposits
+        let pending: u64 = amounts
+            .iter()
+            .zip(checkpoints.iter())
+            .map(|(amount, checkpoint)| {
+                calc_pending_reward(*amount, staking_info.reward_per_share, *checkpoint).unwrap()
+            })
+            .sum();
+        assert!(pending <= fund);
+        assert!(fund - pending <= amounts.len() as u64 * 100);
+        assert!(paid > 0);
+    }
+
+    #[test]
+    pub fn test_is_staking_fund_covered() {
+        let mut staking_info = StakingInfo {
+            epoch: 1,
+            epoch_period_days: 10,
+            ..Default::default()
+        };
+        let apr_schedule = build_apr_schedule(10, 1, 5).unwrap();
+        assert_eq!(
+            is_staking_fund_covered(0, &staking_info, &apr_schedule, 0),
+            Ok(true)
+        );
+        assert_eq!(
+            is_staking_fund_covered(27431062, &staking_info, &apr_schedule, 10_000_000_000),
+            Ok(true)
+        );
+        assert_eq!(
+            is_staking_fund_covered(27431061, &staking_info, &apr_schedule, 10_000_000_000),
+            Ok(false)
+        );
+
+        staking_info.reward_liability = 100;
+        staking_info.total_iou = 50;
+        assert_eq!(
+            is_staking_fund_covered(27431062 + 150, &staking_info, &apr_schedule, 10_000_000_000),
+            Ok(true)
+        );
+        assert_eq!(
+            is_staking_fund_covered(27431062 + 149, &staking_info, &apr_schedule, 10_000_000_000),
+            Ok(false)
+        );
+    }
+
+    /// Exact reward by rational daily compounding: amount * ((3650000 + apr) / 3650000)^days.
+    fn reference_reward_amount(amount: u64, epoch_period_days: u16, epoch_aprs: &[u16]) -> u64 {
+        let mut numerator = BigUint::from(amount);
+        let mut denominator = BigUint::from(1u8);
+        for apr in epoch_aprs {
+            numerator *= BigUint::from(3650000u32 + *apr as u32).pow(epoch_period_days as u32);
+            denominator *= BigUint::from(3650000u32).pow(epoch_period_days as u32);
+        }
+        let new_amount = numerator / denominator;
+        u64::try_from(new_amount).unwrap_or(u64::MAX) - amount
+    }
+
+    /// Simple deterministic pseudo random generator for property tests.
+    struct Lcg(u64);
+
+    impl Lcg {
+        /// Get 32 high bits of the next state, the low bits of LCG are not random.
+        fn next_u32(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0 >> 32
+        }
+
+        fn next(&mut self, max: u64) -> u64 {
+            ((self.next_u32() << 32) | self.next_u32()) % max
+        }
+    }
+
+    fn assert_reward_within_tolerance(amount: u64, epoch_period_days: u16, epoch_aprs: &[u16]) {
+        let expected = reference_reward_amount(amount, epoch_period_days, epoch_aprs);
+        let epochs = epoch_aprs.len() as u64;
+        let reward = calc_user_reward_amount(
+            epoch_period_days,
+            time,
+            epoch_aprs,
+            amount,
+            time,
+            time + epochs as i64 * epoch_period_days as i64 * day,
+        )
+        .unwrap();
+
+        // Never above the exact value, below by 1 lamport + (2D + 17E) * 1e-18 of new amount at most
+        let new_amount = amount.saturating_add(expected) as u128;
+        let compounded_days = epochs as u128 * epoch_period_days as u128;
+        let truncations = 2 * compounded_days + 17 * epochs as u128;
+        let tolerance = 1 + (new_amount * truncations).div_ceil(WAD);
+        assert!(
+            reward <= expected && (expected - reward) as u128 <= tolerance,
+            "amount {}, days {}, aprs {:?}: reward {}, expected {}",
+            amount,
+            epoch_period_days,
+            epoch_aprs,
+            reward,
+            expected
+        );
+    }
+
+    #[test]
+    pub fn test_calc_user_reward_amount_reference() {
+        let mut rng = Lcg(1660032700);
+        for _ in 0..300 {
+            let amount = match rng.next(3) {
+                0 => rng.next(1_000_000_000),
+                1 => rng.next(100_000_000_000_000_000),
+                _ => rng.next(u64::MAX),
+            };
+            let epoch_period_days = 1 + rng.next(60) as u16;
+            let epochs = rng.next(40) as usize;
+            let epoch_aprs: Vec<u16> = (0..epochs).map(|_| 1 + rng.next(25500) as u16).collect();
+            assert_reward_within_tolerance(amount, epoch_period_days, &epoch_aprs);
+        }
+    }
+
+    #[test]
+    pub fn test_calc_user_reward_amount_reference_full_epoch_range() {
+        let mut rng = Lcg(45);
+        for epoch_period_days in [2, 10, 45] {
+            let apr_schedule = build_apr_schedule(45, 1, 5).unwrap();
+            let epoch_aprs: Vec<u16> = (1..=EPOCH_HISTORY_MAX_LEN as u64)
+                .map(|epoch| get_scheduled_apr(&apr_schedule, epoch).unwrap())
+                .collect();
+            for epochs in [1, 2, 10, 40, 100, 500, EPOCH_HISTORY_MAX_LEN] {
+                let amount = 1 + rng.next(10_000_000_000_000);
+                assert_reward_within_tolerance(amount, epoch_period_days, &epoch_aprs[..epochs]);
+                assert_reward_within_tolerance(
+                    10_000_000_000_000,
+                    epoch_period_days,
+                    &epoch_aprs[..epochs],
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_calc_compound_growth() {
+        assert_eq!(calc_compound_growth(0, 45), Some(WAD));
+        assert_eq!(calc_compound_growth(45, 0), Some(WAD));
+        assert_eq!(calc_compound_growth(7300, 1), Some(WAD + WAD / 500));
+        assert_eq!(calc_compound_growth(25500, u16::MAX), None);
+        assert_eq!(calc_grown_amount(0, None), 0);
+        assert_eq!(calc_grown_amount(1, None), u64::MAX);
+        assert_eq!(calc_grown_amount(u64::MAX, Some(2 * WAD)), u64::MAX);
+        assert_eq!(calc_grown_amount(1000, Some(WAD + WAD / 2)), 1500);
+    }
 }
@@ -1,42 +1,42 @@
 import * as anchor from "@project-serum/anchor";
 import { AnchorError, Program } from "@project-serum/anchor";
-import { Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import { Distribution } from "../../target/types/distribution";
 import * as assert from "assert";
 import * as utils from "../utils";
-import { DistributionTestFixture, prepareDistributionTestFixture } from "./fixture";
+import {
+  DistributionTestFixture,
+  prepareDistributionTestFixture,
+  defaultRecipients,
+  recipientAccounts,
+  Recipient,
+} from "./fixture";
 
 describe("Distribution authority tests", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
   const program = anchor.workspace.Distribution as Program<Distribution>;
 
-  const playToEarnFundShare: number = 45;
-  const stakingFundShare: number = 40;
-  const companyFundShare: number = 5;
-  const teamFundShare: number = 10;
-
   let fixture: DistributionTestFixture = null;
+  let recipients: Recipient[] = null;
   before(async () => {
     fixture = await prepareDistributionTestFixture(program);
+    recipients = defaultRecipients(fixture);
     await program.methods.initialize(
       fixture.updateAuth.publicKey,
-      playToEarnFundShare,
-      stakingFundShare,
-      companyFundShare,
-      teamFundShare,
+      recipients,
+      fixture.teamFund,
+      new anchor.BN(3),
     )
       .accounts({
         admin: fixture.admin.publicKey,
         distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
         ggwpToken: fixture.ggwpToken,
         accumulativeFund: fixture.accumulativeFund,
         accumulativeFundAuth: fixture.accumulativeFundAuth,
-        playToEarnFund: fixture.playToEarnFund,
-        stakingFund: fixture.stakingFund,
-        companyFund: fixture.companyFund,
-        teamFund: fixture.teamFund,
         systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(recipientAccounts(recipients))
       .signers([fixture.admin, fixture.info])
       .rpc();
 
@@ -45,14 +45,9 @@ describe("Distribution authority tests", () => {
     assert.ok(distributionInfoData.updateAuth.equals(fixture.updateAuth.publicKey));
     assert.ok(distributionInfoData.ggwpToken.equals(fixture.ggwpToken));
     assert.ok(distributionInfoData.accumulativeFund.equals(fixture.accumulativeFund));
-    assert.ok(distributionInfoData.playToEarnFund.equals(fixture.playToEarnFund));
-    assert.equal(distributionInfoData.playToEarnFundShare, playToEarnFundShare);
-    assert.ok(distributionInfoData.stakingFund.equals(fixture.stakingFund));
-    assert.equal(distributionInfoData.stakingFundShare, stakingFundShare);
-    assert.ok(distributionInfoData.companyFund.equals(fixture.companyFund));
-    assert.equal(distributionInfoData.companyFundShare, companyFundShare);
-    assert.ok(distributionInfoData.teamFund.equals(fixture.teamFund));
-    assert.equal(distributionInfoData.teamFundShare, teamFundShare);
+    assert.deepEqual(distributionInfoData.recipients, recipients);
+    assert.ok(distributionInfoData.dustRecipient.equals(fixture.teamFund));
+    assert.equal(distributionInfoData.recipientsTimelock.toNumber(), 3);
   });
 
   const newAdmin = Keypair.generate();
@@ -113,6 +108,7 @@ describe("Distribution authority tests", () => {
   });
 
   it("Set update authority", async () => {
+    await utils.airdropSol(program.provider.connection, newUpdateAuth.publicKey, 1 * LAMPORTS_PER_SOL);
     await program.methods
       .setUpdateAuthority(newUpdateAuth.publicKey)
       .accounts({
@@ -127,14 +123,17 @@ describe("Distribution authority tests", () => {
     assert.ok(distributionInfoData.updateAuth.equals(newUpdateAuth.publicKey));
   });
 
-  it("Update shares with invalid authority", async () => {
+  it("Propose recipients with invalid authority", async () => {
     await assert.rejects(program.methods
-      .updateShares(45, 40, 5, 10)
+      .proposeRecipients(recipients, fixture.teamFund)
       .accounts({
         authority: fixture.updateAuth.publicKey,
         distributionInfo:
-          fixture.info.publicKey
+          fixture.info.publicKey,
+        pendingRecipients: fixture.pendingRecipients,
+        systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(recipientAccounts(recipients))
       .signers([fixture.updateAuth])
       .rpc(),
       (e: AnchorError) => {
@@ -146,60 +145,304 @@ describe("Distribution authority tests", () => {
       });
   });
 
-  it("Update shares with invalid shares values", async () => {
+  it("Propose recipients with invalid recipients", async () => {
+    const invalidRecipientsList = [
+      // Shares sum is not 100%
+      defaultRecipients(fixture).map((recipient) => ({ ...recipient, share: recipient.share + 1 })),
+      // Zero share
+      [...defaultRecipients(fixture), { fund: Keypair.generate().publicKey, share: 0, vestingEscrow: PublicKey.default }],
+      // Duplicated fund
+      [{ fund: fixture.teamFund, share: 5000, vestingEscrow: PublicKey.default }, { fund: fixture.teamFund, share: 5000, vestingEscrow: PublicKey.default }],
+      // Dust recipient is not in the list
+      [{ fund: fixture.playToEarnFund, share: 10000, vestingEscrow: PublicKey.default }],
+    ];
+    for (const invalidRecipients of invalidRecipientsList) {
+      await assert.rejects(program.methods
+        .proposeRecipients(invalidRecipients, fixture.teamFund)
+        .accounts({
+          authority: newUpdateAuth.publicKey,
+          distributionInfo:
+            fixture.info.publicKey,
+          pendingRecipients: fixture.pendingRecipients,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(recipientAccounts(invalidRecipients))
+        .signers([newUpdateAuth])
+        .rpc(),
+        (e: AnchorError) => {
+          assert.ok(e.error !== undefined);
+          assert.strictEqual(e.error.errorCode.code, "InvalidRecipients");
+          assert.strictEqual(e.error.errorCode.number, 6011);
+          assert.strictEqual(e.error.errorMessage, "Invalid recipients");
+          return true;
+        });
+    }
+  });
+
+  it("Propose recipients with invalid recipient accounts", async () => {
     await assert.rejects(program.methods
-      .updateShares(101, 40, 5, 10)
+      .proposeRecipients(recipients, fixture.teamFund)
       .accounts({
         authority: newUpdateAuth.publicKey,
         distributionInfo:
-          fixture.info.publicKey
+          fixture.info.publicKey,
+        pendingRecipients: fixture.pendingRecipients,
+        systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(recipientAccounts(recipients.slice(1)))
       .signers([newUpdateAuth])
       .rpc(),
       (e: AnchorError) => {
         assert.ok(e.error !== undefined);
-        assert.strictEqual(e.error.errorCode.code, "InvalidShare");
-        assert.strictEqual(e.error.errorCode.number, 6008);
-        assert.strictEqual(e.error.errorMessage, "Invalid share percent value");
+        assert.strictEqual(e.error.errorCode.code, "InvalidRecipientAccounts");
+        assert.strictEqual(e.error.errorCode.number, 6012);
+        assert.strictEqual(e.error.errorMessage, "Invalid recipient accounts");
         return true;
       });
 
     await assert.rejects(program.methods
-      .updateShares(45, 101, 5, 10)
+      .proposeRecipients(recipients, fixture.teamFund)
       .accounts({
         authority: newUpdateAuth.publicKey,
         distributionInfo:
-          fixture.info.publicKey
+          fixture.info.publicKey,
+        pendingRecipients: fixture.pendingRecipients,
+        systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(recipientAccounts([...recipients].reverse()))
       .signers([newUpdateAuth])
       .rpc(),
       (e: AnchorError) => {
         assert.ok(e.error !== undefined);
-        assert.strictEqual(e.error.errorCode.code, "InvalidShare");
-        assert.strictEqual(e.error.errorCode.number, 6008);
-        assert.strictEqual(e.error.errorMessage, "Invalid share percent value");
+        assert.strictEqual(e.error.errorCode.code, "InvalidFundPublicKey");
+        assert.strictEqual(e.error.errorCode.number, 6009);
+        assert.strictEqual(e.error.errorMessage, "Invalid fund PK");
         return true;
       });
 
+    const otherToken = await utils.createMint(fixture.admin.publicKey, 9);
+    const otherTokenFund = await utils.createTokenWallet(otherToken, Keypair.generate().publicKey);
+    const otherTokenRecipients = [...recipients.slice(0, 3), { fund: otherTokenFund, share: 1000, vestingEscrow: PublicKey.default }];
     await assert.rejects(program.methods
-      .updateShares(45, 40, 101, 10)
+      .proposeRecipients(otherTokenRecipients, otherTokenFund)
       .accounts({
         authority: newUpdateAuth.publicKey,
         distributionInfo:
-          fixture.info.publicKey
+          fixture.info.publicKey,
+        pendingRecipients: fixture.pendingRecipients,
+        systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(recipientAccounts(otherTokenRecipients))
       .signers([newUpdateAuth])
       .rpc(),
       (e: AnchorError) => {
         assert.ok(e.error !== undefined);
-        assert.strictEqual(e.error.errorCode.code, "InvalidShare");
-        assert.strictEqual(e.error.errorCode.number, 6008);
-        assert.strictEqual(e.error.errorMessage, "Invalid share percent value");
+        assert.strictEqual(e.error.errorCode.code, "InvalidRecipientFundMint");
+        assert.strictEqual(e.error.errorCode.number, 6013);
+        assert.strictEqual(e.error.errorMessage, "Invalid recipient fund mint");
+        return true;
+      });
+  });
+
+  const proposeRecipients = (recipients: Recipient[], dustRecipient: anchor.web3.PublicKey) => program.methods
+    .proposeRecipients(recipients, dustRecipient)
+    .accounts({
+      authority: newUpdateAuth.publicKey,
+      distributionInfo: fixture.info.publicKey,
+      pendingRecipients: fixture.pendingRecipients,
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(recipientAccounts(recipients))
+    .signers([newUpdateAuth])
+    .rpc();
+  const executeRecipients = () => program.methods
+    .executeRecipients()
+    .accounts({
+      distributionInfo: fixture.info.publicKey,
+      pendingRecipients: fixture.pendingRecipients,
+      proposer: newUpdateAuth.publicKey,
+    })
+    .rpc();
+  const cancelRecipients = () => program.methods
+    .cancelRecipients()
+    .accounts({
+      admin: newAdmin.publicKey,
+      distributionInfo: fixture.info.publicKey,
+      pendingRecipients: fixture.pendingRecipients,
+      proposer: newUpdateAuth.publicKey,
+    })
+    .signers([newAdmin])
+    .rpc();
+  const updateRecipientsTimelock = (authority: Keypair, recipientsTimelock: number) => program.methods
+    .updateRecipientsTimelock(new anchor.BN(recipientsTimelock))
+    .accounts({
+      authority: authority.publicKey,
+      distributionInfo: fixture.info.publicKey,
+    })
+    .signers([authority])
+    .rpc();
+  const executeRecipientsTimelock = () => program.methods
+    .executeRecipientsTimelock()
+    .accounts({
+      distributionInfo: fixture.info.publicKey,
+    })
+    .rpc();
+  const assertTimelockNotPassed = (e: AnchorError) => {
+    assert.ok(e.error !== undefined);
+    assert.strictEqual(e.error.errorCode.code, "RecipientsTimelockNotPassed");
+    assert.strictEqual(e.error.errorCode.number, 6027);
+    assert.strictEqual(e.error.errorMessage, "Recipients timelock is not passed");
+    return true;
+  };
+
+  it("Propose, cancel and execute recipients", async () => {
+    const newRecipients = [
+      { fund: fixture.playToEarnFund, share: 5000, vestingEscrow: PublicKey.default },
+      { fund: fixture.stakingFund, share: 5000, vestingEscrow: PublicKey.default },
+    ];
+    await proposeRecipients(newRecipients, fixture.stakingFund);
+
+    let pendingRecipientsData = await program.account.pendingRecipients.fetch(fixture.pendingRecipients);
+    assert.ok(pendingRecipientsData.proposer.equals(newUpdateAuth.publicKey));
+    assert.deepEqual(pendingRecipientsData.recipients, newRecipients);
+    assert.equal(
+      pendingRecipientsData.executableAt.toNumber() - pendingRecipientsData.proposedAt.toNumber(),
+      3,
+    );
+
+    // Only one change can be pending
+    await assert.rejects(proposeRecipients(newRecipients, fixture.stakingFund));
+
+    await assert.rejects(executeRecipients(), assertTimelockNotPassed);
+
+    await assert.rejects(program.methods
+      .cancelRecipients()
+      .accounts({
+        admin: newUpdateAuth.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        pendingRecipients: fixture.pendingRecipients,
+        proposer: newUpdateAuth.publicKey,
+      })
+      .signers([newUpdateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        return true;
+      });
+
+    await cancelRecipients();
+    assert.equal(await program.provider.connection.getAccountInfo(fixture.pendingRecipients), null);
+
+    // Execute after the timelock
+    await proposeRecipients(newRecipients, fixture.stakingFund);
+    await utils.sleep(4);
+    await executeRecipients();
+    assert.equal(await program.provider.connection.getAccountInfo(fixture.pendingRecipients), null);
+
+    const distributionInfoData = await program.account.distributionInfo.fetch(fixture.info.publicKey);
+    assert.deepEqual(distributionInfoData.recipients, newRecipients);
+    assert.ok(distributionInfoData.dustRecipient.equals(fixture.stakingFund));
+  });
+
+  it("Update recipients timelock", async () => {
+    await assert.rejects(updateRecipientsTimelock(newUpdateAuth, 3600),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
         return true;
       });
 
+    for (const invalidTimelock of [-1, 0]) {
+      await assert.rejects(updateRecipientsTimelock(newAdmin, invalidTimelock),
+        (e: AnchorError) => {
+          assert.ok(e.error !== undefined);
+          assert.strictEqual(e.error.errorCode.code, "InvalidRecipientsTimelock");
+          assert.strictEqual(e.error.errorCode.number, 6026);
+          return true;
+        });
+    }
+
+    // Increase applies at once
+    await updateRecipientsTimelock(newAdmin, 3600);
+    const distributionInfoData = await program.account.distributionInfo.fetch(fixture.info.publicKey);
+    assert.equal(distributionInfoData.recipientsTimelock.toNumber(), 3600);
+    assert.equal(distributionInfoData.pendingRecipientsTimelock.toNumber(), 0);
+  });
+
+  it("Lower recipients timelock can't execute the pending recipients early", async () => {
+    const newRecipients = [
+      { fund: fixture.playToEarnFund, share: 4000, vestingEscrow: PublicKey.default },
+      { fund: fixture.stakingFund, share: 6000, vestingEscrow: PublicKey.default },
+    ];
+    await proposeRecipients(newRecipients, fixture.stakingFund);
+
+    // Reduction is queued behind the current timelock
+    await updateRecipientsTimelock(newAdmin, 1);
+    let distributionInfoData = await program.account.distributionInfo.fetch(fixture.info.publicKey);
+    assert.equal(distributionInfoData.recipientsTimelock.toNumber(), 3600);
+    assert.equal(distributionInfoData.pendingRecipientsTimelock.toNumber(), 1);
+    assert.ok(distributionInfoData.pendingRecipientsTimelockAt.toNumber() >= utils.currentTimestamp() + 3500);
+
+    await utils.sleep(2);
+    await assert.rejects(executeRecipientsTimelock(), assertTimelockNotPassed);
+    await assert.rejects(executeRecipients(), assertTimelockNotPassed);
+
+    // New proposal still waits the current timelock
+    await cancelRecipients();
+    await proposeRecipients(newRecipients, fixture.stakingFund);
+    const pendingRecipientsData = await program.account.pendingRecipients.fetch(fixture.pendingRecipients);
+    assert.equal(
+      pendingRecipientsData.executableAt.toNumber() - pendingRecipientsData.proposedAt.toNumber(),
+      3600,
+    );
+    await utils.sleep(2);
+    await assert.rejects(executeRecipients(), assertTimelockNotPassed);
+    await cancelRecipients();
+
+    // Admin drops the reduction with the current timelock
+    await updateRecipientsTimelock(newAdmin, 3600);
+    distributionInfoData = await program.account.distributionInfo.fetch(fixture.info.publicKey);
+    assert.equal(distributionInfoData.pendingRecipientsTimelock.toNumber(), 0);
+    await assert.rejects(executeRecipientsTimelock(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NoPendingRecipientsTimelock");
+        assert.strictEqual(e.error.errorCode.number, 6029);
+        assert.strictEqual(e.error.errorMessage, "No pending recipients timelock");
+        return true;
+      });
+  });
+
+  it("Update distribution limits with invalid authority", async () => {
+    const calls = [
+      program.methods.updateMinDistributionInterval(new anchor.BN(3600)),
+      program.methods.updateMinDistributionAmount(new anchor.BN(1000_000_000_000)),
+      program.methods.updateKeeperRewardShare(100),
+    ];
+    for (const call of calls) {
+      await assert.rejects(call
+        .accounts({
+          authority: fixture.updateAuth.publicKey,
+          distributionInfo:
+            fixture.info.publicKey
+        })
+        .signers([fixture.updateAuth])
+        .rpc(),
+        (e: AnchorError) => {
+          assert.ok(e.error !== undefined);
+          assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+          assert.strictEqual(e.error.errorCode.number, 6000);
+          return true;
+        });
+    }
+  });
+
+  it("Update distribution limits with invalid values", async () => {
     await assert.rejects(program.methods
-      .updateShares(45, 40, 5, 101)
+      .updateMinDistributionInterval(new anchor.BN(-1))
       .accounts({
         authority: newUpdateAuth.publicKey,
         distributionInfo:
@@ -209,14 +452,13 @@ describe("Distribution authority tests", () => {
       .rpc(),
       (e: AnchorError) => {
         assert.ok(e.error !== undefined);
-        assert.strictEqual(e.error.errorCode.code, "InvalidShare");
-        assert.strictEqual(e.error.errorCode.number, 6008);
-        assert.strictEqual(e.error.errorMessage, "Invalid share percent value");
+        assert.strictEqual(e.error.errorCode.code, "InvalidMinDistributionInterval");
+        assert.strictEqual(e.error.errorCode.number, 6017);
         return true;
       });
 
     await assert.rejects(program.methods
-      .updateShares(45, 40, 6, 10)
+      .updateKeeperRewardShare(501)
       .accounts({
         authority: newUpdateAuth.publicKey,
         distributionInfo:
@@ -226,10 +468,32 @@ describe("Distribution authority tests", () => {
       .rpc(),
       (e: AnchorError) => {
         assert.ok(e.error !== undefined);
-        assert.strictEqual(e.error.errorCode.code, "InvalidShare");
-        assert.strictEqual(e.error.errorCode.number, 6008);
-        assert.strictEqual(e.error.errorMessage, "Invalid share percent value");
+        assert.strictEqual(e.error.errorCode.code, "InvalidKeeperRewardShare");
+        assert.strictEqual(e.error.errorCode.number, 6016);
+        assert.strictEqual(e.error.errorMessage, "Invalid keeper reward share value");
         return true;
       });
   });
+
+  it("Update distribution limits", async () => {
+    for (const call of [
+      program.methods.updateMinDistributionInterval(new anchor.BN(3600)),
+      program.methods.updateMinDistributionAmount(new anchor.BN(1000_000_000_000)),
+      program.methods.updateKeeperRewardShare(500),
+    ]) {
+      await call
+        .accounts({
+          authority: newUpdateAuth.publicKey,
+          distributionInfo:
+            fixture.info.publicKey
+        })
+        .signers([newUpdateAuth])
+        .rpc();
+    }
+
+    const distributionInfoData = await program.account.distributionInfo.fetch(fixture.info.publicKey);
+    assert.equal(distributionInfoData.minDistributionInterval.toNumber(), 3600);
+    assert.equal(distributionInfoData.minDistributionAmount.toNumber(), 1000_000_000_000);
+    assert.equal(distributionInfoData.keeperRewardShare, 500);
+  });
 });
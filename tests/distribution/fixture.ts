@@ -1,7 +1,7 @@
 import { Program } from "@project-serum/anchor";
 import { findProgramAddressSync } from "@project-serum/anchor/dist/cjs/utils/pubkey";
 import { utf8 } from "@project-serum/anchor/dist/cjs/utils/bytes";
-import { Keypair, PublicKey } from "@solana/web3.js";
+import { AccountMeta, Keypair, PublicKey } from "@solana/web3.js";
 import { Distribution } from "../../target/types/distribution";
 import * as utils from "../utils";
 
@@ -11,13 +11,18 @@ export class DistributionTestFixture {
   info: Keypair;
   ggwpToken: PublicKey;
 
+  history: PublicKey;
+  pendingRecipients: PublicKey;
   accumulativeFund: PublicKey;
   accumulativeFundAuth: PublicKey;
+  callerGgwpWallet: PublicKey;
 
   playToEarnFund: PublicKey;
   stakingFund: PublicKey;
   companyFund: PublicKey;
+  companyFundAuth: Keypair;
   teamFund: PublicKey;
+  teamFundAuth: Keypair;
 }
 
 export async function prepareDistributionTestFixture(distribution: Program<Distribution>): Promise<DistributionTestFixture> {
@@ -37,9 +42,25 @@ export async function prepareDistributionTestFixture(distribution: Program<Distr
     ],
     distribution.programId,
   )[0];
+  const history = findProgramAddressSync(
+    [
+      utf8.encode(utils.DISTRIBUTION_HISTORY_SEED),
+      distributionInfo.publicKey.toBytes(),
+    ],
+    distribution.programId,
+  )[0];
+  const pendingRecipients = findProgramAddressSync(
+    [
+      utf8.encode(utils.PENDING_RECIPIENTS_SEED),
+      distributionInfo.publicKey.toBytes(),
+    ],
+    distribution.programId,
+  )[0];
   const accumulativeFund = await utils.createTokenWallet(ggwpToken, accumulativeFundAuth);
   await utils.mintTokens(ggwpToken, admin, accumulativeFund, 10000_000_000_000);
 
+  const callerGgwpWallet = await utils.createTokenWallet(ggwpToken, distribution.provider.publicKey);
+
   // Fund authorities just for test
   const playToEarnFundAuth = Keypair.generate();
   const playToEarnFund = await utils.createTokenWallet(ggwpToken, playToEarnFundAuth.publicKey);
@@ -56,12 +77,70 @@ export async function prepareDistributionTestFixture(distribution: Program<Distr
     info: distributionInfo,
     ggwpToken: ggwpToken,
 
+    history: history,
+    pendingRecipients: pendingRecipients,
     accumulativeFund: accumulativeFund,
     accumulativeFundAuth: accumulativeFundAuth,
+    callerGgwpWallet: callerGgwpWallet,
 
     playToEarnFund: playToEarnFund,
     stakingFund: stakingFund,
     companyFund: companyFund,
+    companyFundAuth: companyFundAuth,
     teamFund: teamFund,
+    teamFundAuth: teamFundAuth,
   }
 }
+
+export type Recipient = {
+  fund: PublicKey;
+  share: number;
+  vestingEscrow: PublicKey;
+}
+
+// Default split between funds in basis points, team fund gets rounding dust
+export function defaultRecipients(fixture: DistributionTestFixture): Recipient[] {
+  return [
+    { fund: fixture.playToEarnFund, share: 4500, vestingEscrow: PublicKey.default },
+    { fund: fixture.stakingFund, share: 4000, vestingEscrow: PublicKey.default },
+    { fund: fixture.companyFund, share: 500, vestingEscrow: PublicKey.default },
+    { fund: fixture.teamFund, share: 1000, vestingEscrow: PublicKey.default },
+  ];
+}
+
+export function recipientAccounts(recipients: Recipient[]): AccountMeta[] {
+  return recipients.map((recipient) => ({ pubkey: recipient.fund, isWritable: true, isSigner: false }));
+}
+
+// Distribute takes the recipient funds followed by the vesting escrows of the escrow recipients
+export function distributeAccounts(recipients: Recipient[]): AccountMeta[] {
+  return [
+    ...recipientAccounts(recipients),
+    ...recipients
+      .filter((recipient) => !recipient.vestingEscrow.equals(PublicKey.default))
+      .map((recipient) => ({ pubkey: recipient.vestingEscrow, isWritable: true, isSigner: false })),
+  ];
+}
+
+export function findVestingEscrowPKs(
+  distribution: Program<Distribution>,
+  distributionInfo: PublicKey,
+  beneficiaryWallet: PublicKey,
+): { vestingEscrow: PublicKey, escrowAuth: PublicKey } {
+  const vestingEscrow = findProgramAddressSync(
+    [
+      utf8.encode(utils.VESTING_ESCROW_SEED),
+      distributionInfo.toBytes(),
+      beneficiaryWallet.toBytes(),
+    ],
+    distribution.programId,
+  )[0];
+  const escrowAuth = findProgramAddressSync(
+    [
+      utf8.encode(utils.VESTING_ESCROW_AUTH_SEED),
+      vestingEscrow.toBytes(),
+    ],
+    distribution.programId,
+  )[0];
+  return { vestingEscrow, escrowAuth };
+}
@@ -1,43 +1,44 @@
 import * as anchor from "@project-serum/anchor";
 import { AnchorError, Program } from "@project-serum/anchor";
-import { SystemProgram } from "@solana/web3.js";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import { Distribution } from "../../target/types/distribution";
 import * as assert from "assert";
 import * as utils from "../utils";
-import { DistributionTestFixture, prepareDistributionTestFixture } from "./fixture";
+import {
+  DistributionTestFixture,
+  prepareDistributionTestFixture,
+  defaultRecipients,
+  recipientAccounts,
+  Recipient,
+} from "./fixture";
 import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
 
 describe("Distribution functional tests", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
   const program = anchor.workspace.Distribution as Program<Distribution>;
 
-  const playToEarnFundShare: number = 45;
-  const stakingFundShare: number = 40;
-  const companyFundShare: number = 5;
-  const teamFundShare: number = 10;
-
   let fixture: DistributionTestFixture = null;
+  let recipients: Recipient[] = null;
   before(async () => {
     fixture = await prepareDistributionTestFixture(program);
+    recipients = defaultRecipients(fixture);
     await program.methods.initialize(
       fixture.updateAuth.publicKey,
-      playToEarnFundShare,
-      stakingFundShare,
-      companyFundShare,
-      teamFundShare,
+      recipients,
+      fixture.teamFund,
+      new anchor.BN(1),
     )
       .accounts({
         admin: fixture.admin.publicKey,
         distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
         ggwpToken: fixture.ggwpToken,
         accumulativeFund: fixture.accumulativeFund,
         accumulativeFundAuth: fixture.accumulativeFundAuth,
-        playToEarnFund: fixture.playToEarnFund,
-        stakingFund: fixture.stakingFund,
-        companyFund: fixture.companyFund,
-        teamFund: fixture.teamFund,
         systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(recipientAccounts(recipients))
       .signers([fixture.admin, fixture.info])
       .rpc();
 
@@ -46,29 +47,30 @@ describe("Distribution functional tests", () => {
     assert.ok(distributionInfoData.updateAuth.equals(fixture.updateAuth.publicKey));
     assert.ok(distributionInfoData.ggwpToken.equals(fixture.ggwpToken));
     assert.ok(distributionInfoData.accumulativeFund.equals(fixture.accumulativeFund));
-    assert.ok(distributionInfoData.playToEarnFund.equals(fixture.playToEarnFund));
-    assert.equal(distributionInfoData.playToEarnFundShare, playToEarnFundShare);
-    assert.ok(distributionInfoData.stakingFund.equals(fixture.stakingFund));
-    assert.equal(distributionInfoData.stakingFundShare, stakingFundShare);
-    assert.ok(distributionInfoData.companyFund.equals(fixture.companyFund));
-    assert.equal(distributionInfoData.companyFundShare, companyFundShare);
-    assert.ok(distributionInfoData.teamFund.equals(fixture.teamFund));
-    assert.equal(distributionInfoData.teamFundShare, teamFundShare);
+    assert.deepEqual(distributionInfoData.recipients, recipients);
+    assert.ok(distributionInfoData.dustRecipient.equals(fixture.teamFund));
+    assert.equal(distributionInfoData.minDistributionInterval.toNumber(), 0);
+    assert.equal(distributionInfoData.minDistributionAmount.toNumber(), 0);
+    assert.equal(distributionInfoData.keeperRewardShare, 0);
+    assert.equal(distributionInfoData.burnShare, 0);
+    assert.equal(distributionInfoData.totalBurned.toNumber(), 0);
+    assert.equal(distributionInfoData.recipientsTimelock.toNumber(), 1);
   });
 
   it("Distribute GGWP tokens", async () => {
     await program.methods
       .distribute()
       .accounts({
+        caller: program.provider.publicKey,
         distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
         accumulativeFund: fixture.accumulativeFund,
         accumulativeFundAuth: fixture.accumulativeFundAuth,
-        playToEarnFund: fixture.playToEarnFund,
-        stakingFund: fixture.stakingFund,
-        companyFund: fixture.companyFund,
-        teamFund: fixture.teamFund,
+        callerGgwpWallet: fixture.callerGgwpWallet,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
+      .remainingAccounts(recipientAccounts(recipients))
       .rpc();
 
     assert.equal(await utils.getTokenBalance(fixture.accumulativeFund), 0);
@@ -76,21 +78,38 @@ describe("Distribution functional tests", () => {
     assert.equal(await utils.getTokenBalance(fixture.stakingFund), 4000_000_000_000);
     assert.equal(await utils.getTokenBalance(fixture.companyFund), 500_000_000_000);
     assert.equal(await utils.getTokenBalance(fixture.teamFund), 1000_000_000_000);
+
+    const historyData = await program.account.distributionHistory.fetch(fixture.history);
+    assert.equal(historyData.totalDistributions.toNumber(), 1);
+    assert.equal(historyData.records.length, 1);
+    const record = historyData.records[0];
+    assert.equal(record.amount.toNumber(), 10000_000_000_000);
+    assert.ok(record.caller.equals(program.provider.publicKey));
+    assert.deepEqual(
+      record.fundAmounts.map((fundAmount) => [fundAmount.fund.toBase58(), fundAmount.amount.toNumber()]),
+      [
+        [fixture.playToEarnFund.toBase58(), 4500_000_000_000],
+        [fixture.stakingFund.toBase58(), 4000_000_000_000],
+        [fixture.companyFund.toBase58(), 500_000_000_000],
+        [fixture.teamFund.toBase58(), 1000_000_000_000],
+      ],
+    );
   });
 
   it("Distribute GGWP tokens with empty accumulative fund", async () => {
     await assert.rejects(program.methods
       .distribute()
       .accounts({
+        caller: program.provider.publicKey,
         distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
         accumulativeFund: fixture.accumulativeFund,
         accumulativeFundAuth: fixture.accumulativeFundAuth,
-        playToEarnFund: fixture.playToEarnFund,
-        stakingFund: fixture.stakingFund,
-        companyFund: fixture.companyFund,
-        teamFund: fixture.teamFund,
+        callerGgwpWallet: fixture.callerGgwpWallet,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
+      .remainingAccounts(recipientAccounts(recipients))
       .rpc(),
       (e: AnchorError) => {
         assert.ok(e.error !== undefined);
@@ -106,15 +125,16 @@ describe("Distribution functional tests", () => {
     await program.methods
       .distribute()
       .accounts({
+        caller: program.provider.publicKey,
         distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
         accumulativeFund: fixture.accumulativeFund,
         accumulativeFundAuth: fixture.accumulativeFundAuth,
-        playToEarnFund: fixture.playToEarnFund,
-        stakingFund: fixture.stakingFund,
-        companyFund: fixture.companyFund,
-        teamFund: fixture.teamFund,
+        callerGgwpWallet: fixture.callerGgwpWallet,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
+      .remainingAccounts(recipientAccounts(recipients))
       .rpc();
 
     assert.equal(await utils.getTokenBalance(fixture.accumulativeFund), 0);
@@ -122,5 +142,183 @@ describe("Distribution functional tests", () => {
     assert.equal(await utils.getTokenBalance(fixture.stakingFund), 4000_000_000_000 + 2800_400_000_000);
     assert.equal(await utils.getTokenBalance(fixture.companyFund), 500_000_000_000 + 350_050_000_000);
     assert.equal(await utils.getTokenBalance(fixture.teamFund), 1000_000_000_000 + 700_100_000_001);
+
+    const historyData = await program.account.distributionHistory.fetch(fixture.history);
+    assert.equal(historyData.totalDistributions.toNumber(), 2);
+    assert.equal(historyData.records[1].amount.toNumber(), 7001_000_000_001);
+    assert.equal(historyData.records[1].fundAmounts[3].amount.toNumber(), 700_100_000_001);
+  });
+
+  it("Distribute GGWP tokens to the new marketing fund", async () => {
+    const marketingFund = await utils.createTokenWallet(fixture.ggwpToken, Keypair.generate().publicKey);
+    recipients = [
+      { fund: fixture.playToEarnFund, share: 4000, vestingEscrow: PublicKey.default },
+      { fund: fixture.stakingFund, share: 4000, vestingEscrow: PublicKey.default },
+      { fund: fixture.companyFund, share: 500, vestingEscrow: PublicKey.default },
+      { fund: fixture.teamFund, share: 1000, vestingEscrow: PublicKey.default },
+      { fund: marketingFund, share: 500, vestingEscrow: PublicKey.default },
+    ];
+    await program.methods
+      .proposeRecipients(recipients, marketingFund)
+      .accounts({
+        authority: fixture.updateAuth.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        pendingRecipients: fixture.pendingRecipients,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(recipientAccounts(recipients))
+      .signers([fixture.updateAuth])
+      .rpc();
+    await utils.sleep(2);
+    await program.methods
+      .executeRecipients()
+      .accounts({
+        distributionInfo: fixture.info.publicKey,
+        pendingRecipients: fixture.pendingRecipients,
+        proposer: fixture.updateAuth.publicKey,
+      })
+      .rpc();
+
+    const balancesBefore = await Promise.all(recipients.map((recipient) => utils.getTokenBalance(recipient.fund)));
+    await utils.mintTokens(fixture.ggwpToken, fixture.admin, fixture.accumulativeFund, 1000_000_000_003);
+    await program.methods
+      .distribute()
+      .accounts({
+        caller: program.provider.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
+        accumulativeFund: fixture.accumulativeFund,
+        accumulativeFundAuth: fixture.accumulativeFundAuth,
+        callerGgwpWallet: fixture.callerGgwpWallet,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(recipientAccounts(recipients))
+      .rpc();
+
+    const balancesAfter = await Promise.all(recipients.map((recipient) => utils.getTokenBalance(recipient.fund)));
+    assert.equal(await utils.getTokenBalance(fixture.accumulativeFund), 0);
+    assert.deepEqual(
+      balancesAfter.map((balance, i) => balance - balancesBefore[i]),
+      [400_000_000_001, 400_000_000_001, 50_000_000_000, 100_000_000_000, 50_000_000_001],
+    );
+  });
+
+  it("Distribute GGWP tokens with min amount, min interval and keeper reward", async () => {
+    await program.methods.updateMinDistributionAmount(new anchor.BN(1000_000_000_000))
+      .accounts({ authority: fixture.updateAuth.publicKey, distributionInfo: fixture.info.publicKey })
+      .signers([fixture.updateAuth])
+      .rpc();
+    await program.methods.updateKeeperRewardShare(100)
+      .accounts({ authority: fixture.updateAuth.publicKey, distributionInfo: fixture.info.publicKey })
+      .signers([fixture.updateAuth])
+      .rpc();
+
+    const distribute = () => program.methods
+      .distribute()
+      .accounts({
+        caller: program.provider.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
+        accumulativeFund: fixture.accumulativeFund,
+        accumulativeFundAuth: fixture.accumulativeFundAuth,
+        callerGgwpWallet: fixture.callerGgwpWallet,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(recipientAccounts(recipients))
+      .rpc();
+
+    await utils.mintTokens(fixture.ggwpToken, fixture.admin, fixture.accumulativeFund, 999_000_000_000);
+    await assert.rejects(distribute(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "LessThanMinDistributionAmount");
+        assert.strictEqual(e.error.errorCode.number, 6015);
+        return true;
+      });
+
+    await utils.mintTokens(fixture.ggwpToken, fixture.admin, fixture.accumulativeFund, 1_000_000_000);
+    const callerBalanceBefore = await utils.getTokenBalance(fixture.callerGgwpWallet);
+    await distribute();
+    assert.equal(await utils.getTokenBalance(fixture.callerGgwpWallet) - callerBalanceBefore, 10_000_000_000);
+    assert.equal(await utils.getTokenBalance(fixture.accumulativeFund), 0);
+
+    const historyData = await program.account.distributionHistory.fetch(fixture.history);
+    const record = historyData.records[historyData.records.length - 1];
+    assert.equal(record.amount.toNumber(), 1000_000_000_000);
+    assert.equal(record.keeperReward.toNumber(), 10_000_000_000);
+    assert.equal(
+      record.fundAmounts.reduce((sum, fundAmount) => sum + fundAmount.amount.toNumber(), 0),
+      990_000_000_000,
+    );
+
+    await program.methods.updateMinDistributionInterval(new anchor.BN(24 * 60 * 60))
+      .accounts({ authority: fixture.updateAuth.publicKey, distributionInfo: fixture.info.publicKey })
+      .signers([fixture.updateAuth])
+      .rpc();
+    await utils.mintTokens(fixture.ggwpToken, fixture.admin, fixture.accumulativeFund, 1000_000_000_000);
+    await assert.rejects(distribute(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "DistributionIntervalNotPassed");
+        assert.strictEqual(e.error.errorCode.number, 6014);
+        return true;
+      });
+  });
+
+  it("Distribute GGWP tokens with burn share", async () => {
+    await program.methods.updateMinDistributionInterval(new anchor.BN(0))
+      .accounts({ authority: fixture.updateAuth.publicKey, distributionInfo: fixture.info.publicKey })
+      .signers([fixture.updateAuth])
+      .rpc();
+    await assert.rejects(program.methods.updateBurnShare(10001)
+      .accounts({ authority: fixture.updateAuth.publicKey, distributionInfo: fixture.info.publicKey })
+      .signers([fixture.updateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidBurnShare");
+        assert.strictEqual(e.error.errorCode.number, 6024);
+        return true;
+      });
+    await program.methods.updateBurnShare(2000)
+      .accounts({ authority: fixture.updateAuth.publicKey, distributionInfo: fixture.info.publicKey })
+      .signers([fixture.updateAuth])
+      .rpc();
+
+    const getSupply = async () => Number((await program.provider.connection.getTokenSupply(fixture.ggwpToken)).value.amount);
+    const supplyBefore = await getSupply();
+    await program.methods
+      .distribute()
+      .accounts({
+        caller: program.provider.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
+        accumulativeFund: fixture.accumulativeFund,
+        accumulativeFundAuth: fixture.accumulativeFundAuth,
+        callerGgwpWallet: fixture.callerGgwpWallet,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(recipientAccounts(recipients))
+      .rpc();
+
+    // 1% keeper reward, 20% of the rest is burned
+    assert.equal(await utils.getTokenBalance(fixture.accumulativeFund), 0);
+    assert.equal(supplyBefore - await getSupply(), 198_000_000_000);
+
+    const distributionInfoData = await program.account.distributionInfo.fetch(fixture.info.publicKey);
+    assert.equal(distributionInfoData.burnShare, 2000);
+    assert.equal(distributionInfoData.totalBurned.toNumber(), 198_000_000_000);
+
+    const historyData = await program.account.distributionHistory.fetch(fixture.history);
+    const record = historyData.records[historyData.records.length - 1];
+    assert.equal(record.keeperReward.toNumber(), 10_000_000_000);
+    assert.equal(record.burned.toNumber(), 198_000_000_000);
+    assert.equal(
+      record.fundAmounts.reduce((sum, fundAmount) => sum + fundAmount.amount.toNumber(), 0),
+      792_000_000_000,
+    );
   });
 });
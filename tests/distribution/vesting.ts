@@ -0,0 +1,229 @@
+import * as anchor from "@project-serum/anchor";
+import { AnchorError, Program } from "@project-serum/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { Distribution } from "../../target/types/distribution";
+import * as assert from "assert";
+import * as utils from "../utils";
+import {
+  DistributionTestFixture,
+  prepareDistributionTestFixture,
+  findVestingEscrowPKs,
+  recipientAccounts,
+  distributeAccounts,
+  Recipient,
+} from "./fixture";
+import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
+
+describe("Distribution vesting tests", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.Distribution as Program<Distribution>;
+
+  let fixture: DistributionTestFixture = null;
+  let recipients: Recipient[] = null;
+  let team = null;
+  let company = null;
+  before(async () => {
+    fixture = await prepareDistributionTestFixture(program);
+    team = findVestingEscrowPKs(program, fixture.info.publicKey, fixture.teamFund);
+    team.escrowWallet = await utils.createTokenWallet(fixture.ggwpToken, team.escrowAuth);
+    company = findVestingEscrowPKs(program, fixture.info.publicKey, fixture.companyFund);
+    company.escrowWallet = await utils.createTokenWallet(fixture.ggwpToken, company.escrowAuth);
+
+    // Team and company shares go to the escrow wallets
+    recipients = [
+      { fund: fixture.playToEarnFund, share: 4500, vestingEscrow: PublicKey.default },
+      { fund: fixture.stakingFund, share: 4000, vestingEscrow: PublicKey.default },
+      { fund: company.escrowWallet, share: 500, vestingEscrow: company.vestingEscrow },
+      { fund: team.escrowWallet, share: 1000, vestingEscrow: team.vestingEscrow },
+    ];
+    await program.methods.initialize(
+      fixture.updateAuth.publicKey,
+      recipients,
+      team.escrowWallet,
+      new anchor.BN(1),
+    )
+      .accounts({
+        admin: fixture.admin.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
+        accumulativeFund: fixture.accumulativeFund,
+        accumulativeFundAuth: fixture.accumulativeFundAuth,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(recipientAccounts(recipients))
+      .signers([fixture.admin, fixture.info])
+      .rpc();
+  });
+
+  const createVestingEscrow = (admin: Keypair, escrow: any, beneficiaryWallet: PublicKey, startTime: number, cliffPeriod: number, vestingPeriod: number) =>
+    program.methods.createVestingEscrow(new anchor.BN(startTime), new anchor.BN(cliffPeriod), new anchor.BN(vestingPeriod))
+      .accounts({
+        admin: admin.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        vestingEscrow: escrow.vestingEscrow,
+        escrowAuth: escrow.escrowAuth,
+        escrowWallet: escrow.escrowWallet,
+        beneficiaryWallet: beneficiaryWallet,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+  const claimVested = (authority: Keypair, escrow: any, beneficiaryWallet: PublicKey) =>
+    program.methods.claimVested()
+      .accounts({
+        authority: authority.publicKey,
+        vestingEscrow: escrow.vestingEscrow,
+        escrowAuth: escrow.escrowAuth,
+        escrowWallet: escrow.escrowWallet,
+        beneficiaryWallet: beneficiaryWallet,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+
+  const distribute = (remainingAccounts: anchor.web3.AccountMeta[]) =>
+    program.methods
+      .distribute()
+      .accounts({
+        caller: program.provider.publicKey,
+        distributionInfo: fixture.info.publicKey,
+        distributionHistory: fixture.history,
+        ggwpToken: fixture.ggwpToken,
+        accumulativeFund: fixture.accumulativeFund,
+        accumulativeFundAuth: fixture.accumulativeFundAuth,
+        callerGgwpWallet: fixture.callerGgwpWallet,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+
+  it("Create vesting escrow with invalid admin", async () => {
+    await assert.rejects(createVestingEscrow(fixture.updateAuth, team, fixture.teamFund, utils.currentTimestamp(), 0, 0),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        return true;
+      });
+  });
+
+  it("Create vesting escrow with invalid schedule", async () => {
+    await assert.rejects(createVestingEscrow(fixture.admin, team, fixture.teamFund, utils.currentTimestamp(), 100, 99),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidVestingSchedule");
+        assert.strictEqual(e.error.errorCode.number, 6019);
+        assert.strictEqual(e.error.errorMessage, "Invalid vesting schedule");
+        return true;
+      });
+  });
+
+  it("Create vesting escrows", async () => {
+    const startTime = utils.currentTimestamp();
+    // Team share is locked for a year
+    await createVestingEscrow(fixture.admin, team, fixture.teamFund, startTime, 365 * 24 * 60 * 60, 3 * 365 * 24 * 60 * 60);
+    // Company schedule is over already, but every share vests from its arrival
+    await createVestingEscrow(fixture.admin, company, fixture.companyFund, startTime - 100, 3, 3);
+
+    const vestingEscrowData = await program.account.vestingEscrow.fetch(team.vestingEscrow);
+    assert.ok(vestingEscrowData.distributionInfo.equals(fixture.info.publicKey));
+    assert.ok(vestingEscrowData.beneficiaryWallet.equals(fixture.teamFund));
+    assert.ok(vestingEscrowData.escrowWallet.equals(team.escrowWallet));
+    assert.equal(vestingEscrowData.startTime.toNumber(), startTime);
+    assert.equal(vestingEscrowData.cliffPeriod.toNumber(), 365 * 24 * 60 * 60);
+    assert.equal(vestingEscrowData.vestingPeriod.toNumber(), 3 * 365 * 24 * 60 * 60);
+    assert.equal(vestingEscrowData.totalClaimed.toNumber(), 0);
+    assert.equal(vestingEscrowData.releasedAmount.toNumber(), 0);
+    assert.equal(vestingEscrowData.tranches.length, 0);
+  });
+
+  it("Distribute GGWP tokens without vesting escrows", async () => {
+    await assert.rejects(distribute(recipientAccounts(recipients)),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidRecipientAccounts");
+        return true;
+      });
+  });
+
+  it("Distribute GGWP tokens with invalid vesting escrows", async () => {
+    await assert.rejects(distribute([...recipientAccounts(recipients), ...distributeAccounts(recipients).slice(recipients.length).reverse()]),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidVestingEscrow");
+        assert.strictEqual(e.error.errorCode.number, 6030);
+        assert.strictEqual(e.error.errorMessage, "Invalid vesting escrow");
+        return true;
+      });
+  });
+
+  it("Distribute GGWP tokens into escrows", async () => {
+    await distribute(distributeAccounts(recipients));
+
+    assert.equal(await utils.getTokenBalance(company.escrowWallet), 500_000_000_000);
+    assert.equal(await utils.getTokenBalance(team.escrowWallet), 1000_000_000_000);
+    const vestingEscrowData = await program.account.vestingEscrow.fetch(company.vestingEscrow);
+    assert.equal(vestingEscrowData.tranches.length, 1);
+    assert.equal(vestingEscrowData.tranches[0].amount.toNumber(), 500_000_000_000);
+  });
+
+  it("Claim vested GGWP before the cliff", async () => {
+    await assert.rejects(claimVested(fixture.teamFundAuth, team, fixture.teamFund),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        assert.strictEqual(e.error.errorCode.number, 6023);
+        assert.strictEqual(e.error.errorMessage, "Nothing to claim");
+        return true;
+      });
+  });
+
+  it("Claim vested GGWP with invalid authority", async () => {
+    await assert.rejects(claimVested(fixture.teamFundAuth, company, fixture.companyFund),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        return true;
+      });
+  });
+
+  it("Claim vested GGWP", async () => {
+    await utils.sleep(4);
+    await claimVested(fixture.companyFundAuth, company, fixture.companyFund);
+
+    assert.equal(await utils.getTokenBalance(company.escrowWallet), 0);
+    assert.equal(await utils.getTokenBalance(fixture.companyFund), 500_000_000_000);
+    const vestingEscrowData = await program.account.vestingEscrow.fetch(company.vestingEscrow);
+    assert.equal(vestingEscrowData.totalClaimed.toNumber(), 500_000_000_000);
+
+    await assert.rejects(claimVested(fixture.companyFundAuth, company, fixture.companyFund),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        return true;
+      });
+  });
+
+  it("Later inflow into the escrow vests from its arrival", async () => {
+    await utils.mintTokens(fixture.ggwpToken, fixture.admin, fixture.accumulativeFund, 1000_000_000_000);
+    await distribute(distributeAccounts(recipients));
+    assert.equal(await utils.getTokenBalance(company.escrowWallet), 50_000_000_000);
+
+    // Cliff of the new share is not passed, though the escrow schedule is over
+    await assert.rejects(claimVested(fixture.companyFundAuth, company, fixture.companyFund),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        return true;
+      });
+
+    await utils.sleep(4);
+    await claimVested(fixture.companyFundAuth, company, fixture.companyFund);
+    assert.equal(await utils.getTokenBalance(fixture.companyFund), 550_000_000_000);
+    const vestingEscrowData = await program.account.vestingEscrow.fetch(company.vestingEscrow);
+    assert.equal(vestingEscrowData.totalClaimed.toNumber(), 550_000_000_000);
+  });
+});
@@ -229,6 +229,23 @@ describe("Fighting authority tests", () => {
     );
   });
 
+  it("update royalty over 100 percent", async () => {
+    await assert.rejects(fighting.methods.updateRoyalty(101)
+      .accounts({
+        authority: newUpdAuthority.publicKey,
+        fightingSettings: fixture.fighting.settings.publicKey,
+      })
+      .signers([newUpdAuthority])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidRoyaltyValue");
+        assert.strictEqual(e.error.errorCode.number, 6003);
+        return true;
+      }
+    );
+  });
+
   it("update royalty", async () => {
     const newRoyalty = 87;
     await fighting.methods.updateRoyalty(newRoyalty)
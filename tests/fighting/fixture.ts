@@ -27,6 +27,7 @@ export class FightingTestFixture {
     gpassBurnAuth: PublicKey;
     gpassMintAuth: PublicKey;
     transferAuth: PublicKey;
+    transferAllowance: PublicKey;
     ggwpToken: PublicKey;
     accumulativeFund: PublicKey;
     playToEarnFund: PublicKey;
@@ -39,6 +40,8 @@ export class FightingTestFixture {
     freezingInfo: PublicKey;
     gpassWallet: PublicKey;
     ggwpWallet: PublicKey;
+    vestingEscrow: PublicKey;
+    vestingEscrowWallet: PublicKey;
   }
 }
 
@@ -116,6 +119,32 @@ export async function prepareFightingTestFixture(
     fighting.programId
   )[0];
 
+  const transferAllowance = findProgramAddressSync(
+    [
+      utf8.encode(utils.TRANSFER_ALLOWANCE_SEED),
+      rewardDistributionInfo.publicKey.toBytes(),
+      transferAuth.toBytes(),
+    ],
+    rewardDistribution.programId
+  )[0];
+
+  const userVestingEscrow = findProgramAddressSync(
+    [
+      utf8.encode(utils.VESTING_ESCROW_SEED),
+      rewardDistributionInfo.publicKey.toBytes(),
+      user.publicKey.toBytes(),
+    ],
+    rewardDistribution.programId
+  )[0];
+
+  const userVestingEscrowWallet = findProgramAddressSync(
+    [
+      utf8.encode(utils.VESTING_ESCROW_WALLET_SEED),
+      userVestingEscrow.toBytes(),
+    ],
+    rewardDistribution.programId
+  )[0];
+
   let burnPeriod = gpassBurnPeriod ? gpassBurnPeriod : 30 * 60;
   await gpass.methods.initialize(
     new anchor.BN(burnPeriod),
@@ -179,6 +208,16 @@ export async function prepareFightingTestFixture(
     })
     .signers([admin, rewardDistributionInfo])
     .rpc();
+  await rewardDistribution.methods.createTransferAllowance(new anchor.BN(100_000_000_000), new anchor.BN(24 * 60 * 60))
+    .accounts({
+      admin: admin.publicKey,
+      rewardDistributionInfo: rewardDistributionInfo.publicKey,
+      authority: transferAuth,
+      transferAllowance: transferAllowance,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([admin])
+    .rpc();
 
   const rewardTable = [
     {
@@ -234,6 +273,7 @@ export async function prepareFightingTestFixture(
       gpassBurnAuth: gpassBurnAuth,
       gpassMintAuth: gpassMintAuth,
       transferAuth: transferAuth,
+      transferAllowance: transferAllowance,
       ggwpToken: ggwpToken,
       accumulativeFund: accumulativeFund,
       playToEarnFund: playToEarnFund,
@@ -245,6 +285,8 @@ export async function prepareFightingTestFixture(
       freezingInfo: userFreezingInfo,
       ggwpWallet: userGgwpTokenWallet,
       gpassWallet: userGpassWallet,
+      vestingEscrow: userVestingEscrow,
+      vestingEscrowWallet: userVestingEscrowWallet,
     }
   }
 }
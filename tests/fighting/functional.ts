@@ -1,6 +1,6 @@
 import * as anchor from "@project-serum/anchor";
 import { Program, AnchorError } from "@project-serum/anchor";
-import { SystemProgram } from "@solana/web3.js";
+import { SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
 import { Fighting } from "../../target/types/fighting";
 import { Gpass } from "../../target/types/gpass";
 import { Freezing } from "../../target/types/freezing";
@@ -89,7 +89,7 @@ describe("Fighting functional tests", () => {
 
   it("User freezing ggwp to get GPASS", async () => {
     const userFreezeAmount = 10_870_000_000; // 10 GGWP + royalty percent
-    await freezing.methods.freeze(new anchor.BN(userFreezeAmount))
+    await freezing.methods.freeze(new anchor.BN(userFreezeAmount), null)
       .accounts({
         user: fixture.user.kp.publicKey,
         accumulativeFund: fixture.fighting.accumulativeFund,
@@ -194,11 +194,16 @@ describe("Fighting functional tests", () => {
         playToEarnFundAuth: fixture.fighting.playToEarnFundAuth,
         rewardDistributionInfo: fixture.fighting.rewardDistributionInfo.publicKey,
         rewardTransferAuth: fixture.fighting.transferAuth,
+        rewardTransferAllowance: fixture.fighting.transferAllowance,
+        userVestingEscrow: fixture.user.vestingEscrow,
+        userVestingEscrowWallet: fixture.user.vestingEscrowWallet,
+        ggwpToken: fixture.fighting.ggwpToken,
         freezingInfo: fixture.fighting.freezingInfo.publicKey,
         fightingSettings: fixture.fighting.settings.publicKey,
         systemProgram: SystemProgram.programId,
         rewardDistributionProgram: rewardDistribution.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([fixture.user.kp, fixture.admin])
       .rpc(),
@@ -224,11 +229,16 @@ describe("Fighting functional tests", () => {
         playToEarnFundAuth: fixture.fighting.playToEarnFundAuth,
         rewardDistributionInfo: fixture.fighting.rewardDistributionInfo.publicKey,
         rewardTransferAuth: fixture.fighting.transferAuth,
+        rewardTransferAllowance: fixture.fighting.transferAllowance,
+        userVestingEscrow: fixture.user.vestingEscrow,
+        userVestingEscrowWallet: fixture.user.vestingEscrowWallet,
+        ggwpToken: fixture.fighting.ggwpToken,
         freezingInfo: fixture.fighting.freezingInfo.publicKey,
         fightingSettings: fixture.fighting.settings.publicKey,
         systemProgram: SystemProgram.programId,
         rewardDistributionProgram: rewardDistribution.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([fixture.user.kp, fixture.fighting.validator])
       .rpc(),
@@ -276,11 +286,16 @@ describe("Fighting functional tests", () => {
         playToEarnFundAuth: fixture.fighting.playToEarnFundAuth,
         rewardDistributionInfo: fixture.fighting.rewardDistributionInfo.publicKey,
         rewardTransferAuth: fixture.fighting.transferAuth,
+        rewardTransferAllowance: fixture.fighting.transferAllowance,
+        userVestingEscrow: fixture.user.vestingEscrow,
+        userVestingEscrowWallet: fixture.user.vestingEscrowWallet,
+        ggwpToken: fixture.fighting.ggwpToken,
         freezingInfo: fixture.fighting.freezingInfo.publicKey,
         fightingSettings: fixture.fighting.settings.publicKey,
         systemProgram: SystemProgram.programId,
         rewardDistributionProgram: rewardDistribution.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([fixture.user.kp, fixture.fighting.validator])
       .rpc(),
@@ -310,11 +325,16 @@ describe("Fighting functional tests", () => {
         playToEarnFundAuth: fixture.fighting.playToEarnFundAuth,
         rewardDistributionInfo: fixture.fighting.rewardDistributionInfo.publicKey,
         rewardTransferAuth: fixture.fighting.transferAuth,
+        rewardTransferAllowance: fixture.fighting.transferAllowance,
+        userVestingEscrow: fixture.user.vestingEscrow,
+        userVestingEscrowWallet: fixture.user.vestingEscrowWallet,
+        ggwpToken: fixture.fighting.ggwpToken,
         freezingInfo: fixture.fighting.freezingInfo.publicKey,
         fightingSettings: fixture.fighting.settings.publicKey,
         systemProgram: SystemProgram.programId,
         rewardDistributionProgram: rewardDistribution.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([fixture.user.kp, fixture.fighting.validator])
       .rpc(),
@@ -349,11 +369,16 @@ describe("Fighting functional tests", () => {
         playToEarnFundAuth: fixture.fighting.playToEarnFundAuth,
         rewardDistributionInfo: fixture.fighting.rewardDistributionInfo.publicKey,
         rewardTransferAuth: fixture.fighting.transferAuth,
+        rewardTransferAllowance: fixture.fighting.transferAllowance,
+        userVestingEscrow: fixture.user.vestingEscrow,
+        userVestingEscrowWallet: fixture.user.vestingEscrowWallet,
+        ggwpToken: fixture.fighting.ggwpToken,
         freezingInfo: fixture.fighting.freezingInfo.publicKey,
         fightingSettings: fixture.fighting.settings.publicKey,
         systemProgram: SystemProgram.programId,
         rewardDistributionProgram: rewardDistribution.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([fixture.user.kp, fixture.fighting.validator])
       .rpc();
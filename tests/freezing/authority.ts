@@ -514,4 +514,39 @@ describe("Freezing authority tests", () => {
     const freezingInfoData = await freezingProgram.account.freezingInfo.fetch(fixture.freezing.info.publicKey);
     assert.equal(freezingInfoData.unfreezeLockPeriod.toNumber(), newPeriod);
   });
+
+  it("Migrate freezing info with invalid admin", async () => {
+    await assert.rejects(freezingProgram.methods
+      .migrateFreezingInfo()
+      .accounts({
+        admin: fixture.admin.publicKey,
+        freezingInfo: fixture.freezing.info.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.admin])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        assert.strictEqual(e.error.errorMessage, "Access denied");
+        return true;
+      });
+  });
+
+  it("Migrate freezing info", async () => {
+    await freezingProgram.methods
+      .migrateFreezingInfo()
+      .accounts({
+        admin: newAdmin.publicKey,
+        freezingInfo: fixture.freezing.info.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([newAdmin])
+      .rpc();
+
+    const freezingInfoData = await freezingProgram.account.freezingInfo.fetch(fixture.freezing.info.publicKey);
+    assert.ok(freezingInfoData.admin.equals(newAdmin.publicKey));
+    assert.equal(freezingInfoData.referralShare, 0);
+  });
 });
@@ -0,0 +1,151 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Freezing } from "../../target/types/freezing";
+import { Gpass } from "../../target/types/gpass";
+import * as assert from "assert";
+import * as utils from "../utils";
+import { FreezingTestFixture, prepareFreezingTestFixture } from "./fixture";
+import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
+
+describe("Freezing auto claim tests", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const freezingProgram = anchor.workspace.Freezing as Program<Freezing>;
+  const gpassProgram = anchor.workspace.Gpass as Program<Gpass>;
+
+  const rewardPeriod = 3;
+  const royalty = 8;
+  const keeperTip = 1_000_000;
+  const rewardTable = [
+    {
+      ggwpAmount: new anchor.BN(10_000_000_000),
+      gpassAmount: new anchor.BN(5),
+    },
+  ];
+
+  let fixture: FreezingTestFixture;
+  const keeper = Keypair.generate();
+  let keeperGgwpWallet: anchor.web3.PublicKey;
+  before(async () => {
+    fixture = await prepareFreezingTestFixture(freezingProgram, gpassProgram);
+    await freezingProgram.methods.initialize(
+      fixture.updateAuth.publicKey,
+      new anchor.BN(rewardPeriod),
+      royalty,
+      15,
+      new anchor.BN(6),
+      rewardTable,
+    )
+      .accounts({
+        admin: fixture.admin.publicKey,
+        freezingInfo: fixture.freezing.info.publicKey,
+        accumulativeFund: fixture.freezing.accumulativeFund,
+        gpassInfo: fixture.freezing.gpassInfo.publicKey,
+        gpassMintAuth: fixture.freezing.gpassMintAuth,
+        treasury: fixture.freezing.treasury,
+        treasuryAuth: fixture.freezing.treasuryAuth,
+        ggwpToken: fixture.freezing.ggwpToken,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.admin, fixture.freezing.info])
+      .rpc();
+
+    await utils.airdropSol(freezingProgram.provider.connection, keeper.publicKey, 200_000_000_000);
+    keeperGgwpWallet = await utils.createTokenWallet(fixture.freezing.ggwpToken, keeper.publicKey);
+
+    await freezingProgram.methods.freeze(new anchor.BN(10_870_000_000), null)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        userInfo: fixture.user.info,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        userGpassWallet: fixture.user.gpassWallet,
+        freezingInfo: fixture.freezing.info.publicKey,
+        gpassInfo: fixture.freezing.gpassInfo.publicKey,
+        gpassMintAuth: fixture.freezing.gpassMintAuth,
+        accumulativeFund: fixture.freezing.accumulativeFund,
+        treasury: fixture.freezing.treasury,
+        systemProgram: SystemProgram.programId,
+        gpassProgram: gpassProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+  });
+
+  function withdrawGpassFor() {
+    return freezingProgram.methods.withdrawGpassFor()
+      .accounts({
+        keeper: keeper.publicKey,
+        user: fixture.user.kp.publicKey,
+        userInfo: fixture.user.info,
+        freezingInfo: fixture.freezing.info.publicKey,
+        gpassInfo: fixture.freezing.gpassInfo.publicKey,
+        userGpassWallet: fixture.user.gpassWallet,
+        gpassMintAuth: fixture.freezing.gpassMintAuth,
+        keeperGgwpWallet: keeperGgwpWallet,
+        treasury: fixture.freezing.treasury,
+        treasuryAuth: fixture.freezing.treasuryAuth,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        gpassProgram: gpassProgram.programId,
+      })
+      .signers([keeper])
+      .rpc();
+  }
+
+  it("Keeper cannot withdraw GPASS without user auto claim", async () => {
+    await utils.sleep(rewardPeriod);
+    await assert.rejects(withdrawGpassFor(),
+      (e: anchor.AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.ok(e.error.errorCode.code === "AutoClaimDisabled");
+        return true;
+      });
+  });
+
+  it("User enable auto claim and deposit tip budget", async () => {
+    await freezingProgram.methods.updateAutoClaim(true, new anchor.BN(keeperTip))
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        userInfo: fixture.user.info,
+        freezingInfo: fixture.freezing.info.publicKey,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    await freezingProgram.methods.depositTipBudget(new anchor.BN(keeperTip))
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        userInfo: fixture.user.info,
+        freezingInfo: fixture.freezing.info.publicKey,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        treasury: fixture.freezing.treasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    const userInfoData = await freezingProgram.account.userInfo.fetch(fixture.user.info);
+    assert.equal(userInfoData.autoClaim, true);
+    assert.ok(userInfoData.user.equals(fixture.user.kp.publicKey));
+    assert.equal(userInfoData.keeperTip.toNumber(), keeperTip);
+    assert.equal(userInfoData.tipBudget.toNumber(), keeperTip);
+  });
+
+  it("Keeper withdraw GPASS for user and get the tip", async () => {
+    const userWalletDataBefore = await gpassProgram.account.wallet.fetch(fixture.user.gpassWallet);
+    await withdrawGpassFor();
+
+    const userWalletData = await gpassProgram.account.wallet.fetch(fixture.user.gpassWallet);
+    assert.equal(userWalletData.amount.toNumber(), userWalletDataBefore.amount.toNumber() + 5);
+    assert.equal(await utils.getTokenBalance(keeperGgwpWallet), keeperTip);
+    const userInfoData = await freezingProgram.account.userInfo.fetch(fixture.user.info);
+    assert.equal(userInfoData.tipBudget.toNumber(), 0);
+  });
+
+  it("Keeper withdraw GPASS without tip when budget is empty", async () => {
+    await utils.sleep(rewardPeriod);
+    await withdrawGpassFor();
+    assert.equal(await utils.getTokenBalance(keeperGgwpWallet), keeperTip);
+  });
+});
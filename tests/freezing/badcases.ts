@@ -60,7 +60,7 @@ describe("Freezing bad cases tests", () => {
   });
 
   it("User freeze zero amount", async () => {
-    await assert.rejects(freezingProgram.methods.freeze(new anchor.BN(0))
+    await assert.rejects(freezingProgram.methods.freeze(new anchor.BN(0), null)
       .accounts({
         user: fixture.user.kp.publicKey,
         userInfo: fixture.user.info,
@@ -140,7 +140,7 @@ describe("Freezing bad cases tests", () => {
 
   const userFreezeAmount = 10_870_000_000; // 10 GGWP + royalty percent
   it("User freeze amount of GGWP", async () => {
-    await freezingProgram.methods.freeze(new anchor.BN(userFreezeAmount))
+    await freezingProgram.methods.freeze(new anchor.BN(userFreezeAmount), null)
       .accounts({
         user: fixture.user.kp.publicKey,
         userInfo: fixture.user.info,
@@ -194,7 +194,7 @@ describe("Freezing bad cases tests", () => {
   });
 
   it("Additional freeze not avaliable", async () => {
-    await assert.rejects(freezingProgram.methods.freeze(new anchor.BN(userFreezeAmount))
+    await assert.rejects(freezingProgram.methods.freeze(new anchor.BN(userFreezeAmount), null)
       .accounts({
         user: fixture.user.kp.publicKey,
         userInfo: fixture.user.info,
@@ -62,7 +62,7 @@ describe("Freezing functional tests", () => {
 
   const userFreezeAmount = 10_870_000_000; // 10 GGWP + royalty percent
   it("User freeze amount of GGWP", async () => {
-    await freezingProgram.methods.freeze(new anchor.BN(userFreezeAmount))
+    await freezingProgram.methods.freeze(new anchor.BN(userFreezeAmount), null)
       .accounts({
         user: fixture.user.kp.publicKey,
         userInfo: fixture.user.info,
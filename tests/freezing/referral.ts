@@ -0,0 +1,205 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { findProgramAddressSync } from "@project-serum/anchor/dist/cjs/utils/pubkey";
+import { utf8 } from "@project-serum/anchor/dist/cjs/utils/bytes";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Freezing } from "../../target/types/freezing";
+import { Gpass } from "../../target/types/gpass";
+import * as assert from "assert";
+import * as utils from "../utils";
+import { FreezingTestFixture, prepareFreezingTestFixture } from "./fixture";
+import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
+
+describe("Freezing referral tests", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const freezingProgram = anchor.workspace.Freezing as Program<Freezing>;
+  const gpassProgram = anchor.workspace.Gpass as Program<Gpass>;
+
+  const royalty = 8;
+  const referralShare = 25;
+  const rewardTable = [
+    {
+      ggwpAmount: new anchor.BN(10_000_000_000),
+      gpassAmount: new anchor.BN(5),
+    },
+  ];
+
+  let fixture: FreezingTestFixture;
+  const referrer = Keypair.generate();
+  let referrerInfo: anchor.web3.PublicKey;
+  let referrerGgwpWallet: anchor.web3.PublicKey;
+  before(async () => {
+    fixture = await prepareFreezingTestFixture(freezingProgram, gpassProgram);
+    await freezingProgram.methods.initialize(
+      fixture.updateAuth.publicKey,
+      new anchor.BN(3),
+      royalty,
+      15,
+      new anchor.BN(6),
+      rewardTable,
+    )
+      .accounts({
+        admin: fixture.admin.publicKey,
+        freezingInfo: fixture.freezing.info.publicKey,
+        accumulativeFund: fixture.freezing.accumulativeFund,
+        gpassInfo: fixture.freezing.gpassInfo.publicKey,
+        gpassMintAuth: fixture.freezing.gpassMintAuth,
+        treasury: fixture.freezing.treasury,
+        treasuryAuth: fixture.freezing.treasuryAuth,
+        ggwpToken: fixture.freezing.ggwpToken,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.admin, fixture.freezing.info])
+      .rpc();
+
+    await utils.airdropSol(freezingProgram.provider.connection, referrer.publicKey, 200_000_000_000);
+    referrerGgwpWallet = await utils.createTokenWallet(fixture.freezing.ggwpToken, referrer.publicKey);
+    referrerInfo = findProgramAddressSync(
+      [
+        utf8.encode(utils.REFERRER_INFO_SEED),
+        fixture.freezing.info.publicKey.toBytes(),
+        referrer.publicKey.toBytes(),
+      ],
+      freezingProgram.programId,
+    )[0];
+  });
+
+  it("Update authority set the referral share", async () => {
+    await freezingProgram.methods.updateReferralShare(referralShare)
+      .accounts({
+        authority: fixture.updateAuth.publicKey,
+        freezingInfo: fixture.freezing.info.publicKey,
+      })
+      .signers([fixture.updateAuth])
+      .rpc();
+
+    const freezingInfoData = await freezingProgram.account.freezingInfo.fetch(fixture.freezing.info.publicKey);
+    assert.equal(freezingInfoData.referralShare, referralShare);
+  });
+
+  it("Referrer register the referral code", async () => {
+    await freezingProgram.methods.registerReferrer()
+      .accounts({
+        referrer: referrer.publicKey,
+        referrerInfo: referrerInfo,
+        freezingInfo: fixture.freezing.info.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([referrer])
+      .rpc();
+
+    const referrerInfoData = await freezingProgram.account.referrerInfo.fetch(referrerInfo);
+    assert.ok(referrerInfoData.referrer.equals(referrer.publicKey));
+    assert.equal(referrerInfoData.referrals.toNumber(), 0);
+    assert.equal(referrerInfoData.totalEarned.toNumber(), 0);
+    assert.equal(referrerInfoData.unclaimedAmount.toNumber(), 0);
+  });
+
+  it("User cannot freeze with referrer without referrer accounts", async () => {
+    await assert.rejects(freezingProgram.methods.freeze(new anchor.BN(10_870_000_000), referrer.publicKey)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        userInfo: fixture.user.info,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        userGpassWallet: fixture.user.gpassWallet,
+        freezingInfo: fixture.freezing.info.publicKey,
+        gpassInfo: fixture.freezing.gpassInfo.publicKey,
+        gpassMintAuth: fixture.freezing.gpassMintAuth,
+        accumulativeFund: fixture.freezing.accumulativeFund,
+        treasury: fixture.freezing.treasury,
+        systemProgram: SystemProgram.programId,
+        gpassProgram: gpassProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc(),
+      (e: anchor.AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.ok(e.error.errorCode.code === "MissingReferrerAccounts");
+        return true;
+      });
+  });
+
+  const userFreezeAmount = 10_870_000_000;
+  it("User freeze GGWP with referrer", async () => {
+    await freezingProgram.methods.freeze(new anchor.BN(userFreezeAmount), referrer.publicKey)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        userInfo: fixture.user.info,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        userGpassWallet: fixture.user.gpassWallet,
+        freezingInfo: fixture.freezing.info.publicKey,
+        gpassInfo: fixture.freezing.gpassInfo.publicKey,
+        gpassMintAuth: fixture.freezing.gpassMintAuth,
+        accumulativeFund: fixture.freezing.accumulativeFund,
+        treasury: fixture.freezing.treasury,
+        systemProgram: SystemProgram.programId,
+        gpassProgram: gpassProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: referrerInfo, isSigner: false, isWritable: true },
+      ])
+      .signers([fixture.user.kp])
+      .rpc();
+
+    const royaltyAmount = utils.calcRoyaltyAmount(userFreezeAmount, royalty);
+    const referralAmount = utils.calcRoyaltyAmount(royaltyAmount, referralShare);
+
+    const userInfoData = await freezingProgram.account.userInfo.fetch(fixture.user.info);
+    assert.ok(userInfoData.referrer.equals(referrer.publicKey));
+    const referrerInfoData = await freezingProgram.account.referrerInfo.fetch(referrerInfo);
+    assert.equal(referrerInfoData.referrals.toNumber(), 1);
+    assert.ok(utils.assertWithPrecission(referrerInfoData.totalEarned.toNumber(), referralAmount, 1));
+    assert.ok(utils.assertWithPrecission(referrerInfoData.unclaimedAmount.toNumber(), referralAmount, 1));
+    assert.equal(await utils.getTokenBalance(referrerGgwpWallet), 0);
+    assert.ok(utils.assertWithPrecission(await utils.getTokenBalance(fixture.freezing.accumulativeFund), royaltyAmount - referralAmount, 1));
+    assert.ok(utils.assertWithPrecission(await utils.getTokenBalance(fixture.freezing.treasury), userFreezeAmount - royaltyAmount + referralAmount, 1));
+  });
+
+  it("Referrer claim referral rewards", async () => {
+    const referrerInfoDataBefore = await freezingProgram.account.referrerInfo.fetch(referrerInfo);
+    const treasuryBalanceBefore = await utils.getTokenBalance(fixture.freezing.treasury);
+    await freezingProgram.methods.claimReferralRewards()
+      .accounts({
+        referrer: referrer.publicKey,
+        referrerInfo: referrerInfo,
+        freezingInfo: fixture.freezing.info.publicKey,
+        referrerGgwpWallet: referrerGgwpWallet,
+        treasury: fixture.freezing.treasury,
+        treasuryAuth: fixture.freezing.treasuryAuth,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([referrer])
+      .rpc();
+
+    const unclaimedAmount = referrerInfoDataBefore.unclaimedAmount.toNumber();
+    const referrerInfoData = await freezingProgram.account.referrerInfo.fetch(referrerInfo);
+    assert.equal(referrerInfoData.unclaimedAmount.toNumber(), 0);
+    assert.equal(referrerInfoData.totalEarned.toNumber(), referrerInfoDataBefore.totalEarned.toNumber());
+    assert.equal(await utils.getTokenBalance(referrerGgwpWallet), unclaimedAmount);
+    assert.equal(await utils.getTokenBalance(fixture.freezing.treasury), treasuryBalanceBefore - unclaimedAmount);
+  });
+
+  it("Referrer cannot claim without referral rewards", async () => {
+    await assert.rejects(freezingProgram.methods.claimReferralRewards()
+      .accounts({
+        referrer: referrer.publicKey,
+        referrerInfo: referrerInfo,
+        freezingInfo: fixture.freezing.info.publicKey,
+        referrerGgwpWallet: referrerGgwpWallet,
+        treasury: fixture.freezing.treasury,
+        treasuryAuth: fixture.freezing.treasuryAuth,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([referrer])
+      .rpc(),
+      (e: anchor.AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "ZeroReferralRewards");
+        assert.strictEqual(e.error.errorCode.number, 6023);
+        return true;
+      });
+  });
+});
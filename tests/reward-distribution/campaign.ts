@@ -0,0 +1,133 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, AnchorError } from "@project-serum/anchor";
+import { SystemProgram, Keypair } from "@solana/web3.js";
+import { RewardDistribution } from "../../target/types/reward_distribution";
+import * as assert from "assert";
+import * as utils from "../utils";
+import {
+  RewardDistributionTestFixture,
+  prepareRewardDistributionTestFixture,
+  findTransferAllowance,
+  claimLeaf,
+  merkleRoot,
+  merkleProof,
+} from "./fixture";
+import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
+
+describe("Reward Distribution claim campaign tests", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.RewardDistribution as Program<RewardDistribution>;
+
+  let fixture: RewardDistributionTestFixture = null;
+  const claimCampaign = Keypair.generate();
+  let claims: { user: anchor.web3.PublicKey, amount: number }[] = null;
+  let leaves: Buffer[] = null;
+
+  before(async () => {
+    fixture = await prepareRewardDistributionTestFixture(program);
+    await utils.airdropSol(program.provider.connection, fixture.transferAuth.publicKey, 1_000_000_000);
+    await program.methods.initialize(fixture.updateAuth.publicKey, [fixture.transferAuth.publicKey])
+      .accounts({
+        admin: fixture.admin.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        ggwpToken: fixture.distribution.ggwpToken,
+        playToEarnFund: fixture.distribution.playToEarnFund,
+        playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.admin, fixture.distribution.info])
+      .rpc();
+    await program.methods.createTransferAllowance(new anchor.BN(50_000_000_000), new anchor.BN(3600))
+      .accounts({
+        admin: fixture.admin.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        authority: fixture.transferAuth.publicKey,
+        transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.transferAuth.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.admin])
+      .rpc();
+
+    claims = [
+      { user: Keypair.generate().publicKey, amount: 1_000_000_000 },
+      { user: fixture.user.kp.publicKey, amount: 2_500_000_000 },
+      { user: Keypair.generate().publicKey, amount: 3_000_000_000 },
+    ];
+    leaves = claims.map((claim, index) => claimLeaf(index, claim.user, claim.amount));
+  });
+
+  it("Create claim campaign", async () => {
+    await program.methods.createClaimCampaign(merkleRoot(leaves), new anchor.BN(6_500_000_000), claims.length)
+      .accounts({
+        authority: fixture.transferAuth.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.transferAuth.publicKey),
+        claimCampaign: claimCampaign.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.transferAuth, claimCampaign])
+      .rpc();
+
+    const claimCampaignData = await program.account.claimCampaign.fetch(claimCampaign.publicKey);
+    assert.ok(claimCampaignData.authority.equals(fixture.transferAuth.publicKey));
+    assert.deepEqual(claimCampaignData.merkleRoot, merkleRoot(leaves));
+    assert.equal(claimCampaignData.maxClaims, 3);
+    assert.equal(claimCampaignData.totalAmount.toNumber(), 6_500_000_000);
+    assert.equal(claimCampaignData.claimedAmount.toNumber(), 0);
+
+    const allowanceData = await program.account.transferAllowance.fetch(
+      findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.transferAuth.publicKey)
+    );
+    assert.equal(allowanceData.used.toNumber(), 6_500_000_000);
+  });
+
+  const claim = (index: number, amount: number, proof: number[][]) => program.methods
+    .claimFromCampaign(index, new anchor.BN(amount), proof)
+    .accounts({
+      user: fixture.user.kp.publicKey,
+      rewardDistributionInfo: fixture.distribution.info.publicKey,
+      claimCampaign: claimCampaign.publicKey,
+      playToEarnFund: fixture.distribution.playToEarnFund,
+      playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
+      userGgwpWallet: fixture.user.ggwpWallet,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([fixture.user.kp])
+    .rpc();
+
+  it("Claim with invalid proof", async () => {
+    const invalidClaims = [
+      claim(1, 3_000_000_000, merkleProof(leaves, 1)),
+      claim(0, 1_000_000_000, merkleProof(leaves, 0)),
+      claim(1, 2_500_000_000, merkleProof(leaves, 2)),
+    ];
+    for (const invalidClaim of invalidClaims) {
+      await assert.rejects(invalidClaim,
+        (e: AnchorError) => {
+          assert.ok(e.error !== undefined);
+          assert.strictEqual(e.error.errorCode.code, "InvalidMerkleProof");
+          assert.strictEqual(e.error.errorCode.number, 6010);
+          assert.strictEqual(e.error.errorMessage, "Invalid Merkle proof");
+          return true;
+        });
+    }
+  });
+
+  it("Claim", async () => {
+    await claim(1, 2_500_000_000, merkleProof(leaves, 1));
+
+    assert.equal(await utils.getTokenBalance(fixture.user.ggwpWallet), 2_500_000_000);
+    const claimCampaignData = await program.account.claimCampaign.fetch(claimCampaign.publicKey);
+    assert.equal(claimCampaignData.claimedAmount.toNumber(), 2_500_000_000);
+    assert.deepEqual([...claimCampaignData.claimedBitmap], [0b10]);
+
+    await assert.rejects(claim(1, 2_500_000_000, merkleProof(leaves, 1)),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AlreadyClaimed");
+        assert.strictEqual(e.error.errorCode.number, 6011);
+        assert.strictEqual(e.error.errorMessage, "Already claimed");
+        return true;
+      });
+  });
+});
@@ -2,6 +2,8 @@ import { Program } from "@project-serum/anchor";
 import { findProgramAddressSync } from "@project-serum/anchor/dist/cjs/utils/pubkey";
 import { utf8 } from "@project-serum/anchor/dist/cjs/utils/bytes";
 import { Keypair, PublicKey } from "@solana/web3.js";
+import * as anchor from "@project-serum/anchor";
+import { keccak_256 } from "js-sha3";
 import { RewardDistribution } from "../../target/types/reward_distribution";
 import * as utils from "../utils";
 
@@ -68,3 +70,78 @@ export async function prepareRewardDistributionTestFixture(program: Program<Rewa
     }
   }
 }
+
+export function findTransferAllowance(program: Program<RewardDistribution>, rewardDistributionInfo: PublicKey, authority: PublicKey): PublicKey {
+  return findProgramAddressSync(
+    [
+      utf8.encode(utils.TRANSFER_ALLOWANCE_SEED),
+      rewardDistributionInfo.toBytes(),
+      authority.toBytes(),
+    ],
+    program.programId
+  )[0];
+}
+
+export function findVestingEscrow(program: Program<RewardDistribution>, rewardDistributionInfo: PublicKey, user: PublicKey): PublicKey {
+  return findProgramAddressSync(
+    [
+      utf8.encode(utils.VESTING_ESCROW_SEED),
+      rewardDistributionInfo.toBytes(),
+      user.toBytes(),
+    ],
+    program.programId
+  )[0];
+}
+
+export function findVestingEscrowWallet(program: Program<RewardDistribution>, vestingEscrow: PublicKey): PublicKey {
+  return findProgramAddressSync(
+    [
+      utf8.encode(utils.VESTING_ESCROW_WALLET_SEED),
+      vestingEscrow.toBytes(),
+    ],
+    program.programId
+  )[0];
+}
+
+export function claimLeaf(index: number, user: PublicKey, amount: number): Buffer {
+  return Buffer.from(keccak_256.arrayBuffer(Buffer.concat([
+    new anchor.BN(index).toArrayLike(Buffer, "le", 4),
+    user.toBuffer(),
+    new anchor.BN(amount).toArrayLike(Buffer, "le", 8),
+  ])));
+}
+
+function hashPair(a: Buffer, b: Buffer): Buffer {
+  const pair = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return Buffer.from(keccak_256.arrayBuffer(Buffer.concat(pair)));
+}
+
+function merkleLevels(leaves: Buffer[]): Buffer[][] {
+  const levels = [leaves];
+  while (levels[levels.length - 1].length > 1) {
+    const level = levels[levels.length - 1];
+    const nextLevel = [];
+    for (let i = 0; i < level.length; i += 2) {
+      nextLevel.push(i + 1 < level.length ? hashPair(level[i], level[i + 1]) : level[i]);
+    }
+    levels.push(nextLevel);
+  }
+  return levels;
+}
+
+export function merkleRoot(leaves: Buffer[]): number[] {
+  const levels = merkleLevels(leaves);
+  return [...levels[levels.length - 1][0]];
+}
+
+export function merkleProof(leaves: Buffer[], index: number): number[][] {
+  const proof = [];
+  for (const level of merkleLevels(leaves)) {
+    const sibling = index ^ 1;
+    if (sibling < level.length) {
+      proof.push([...level[sibling]]);
+    }
+    index = Math.floor(index / 2);
+  }
+  return proof;
+}
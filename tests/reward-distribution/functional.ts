@@ -4,7 +4,7 @@ import { SystemProgram, Keypair } from "@solana/web3.js";
 import { RewardDistribution } from "../../target/types/reward_distribution";
 import * as assert from "assert";
 import * as utils from "../utils";
-import { RewardDistributionTestFixture, prepareRewardDistributionTestFixture } from "./fixture";
+import { RewardDistributionTestFixture, prepareRewardDistributionTestFixture, findTransferAllowance } from "./fixture";
 import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
 
 describe("Reward Distribution functional tests", () => {
@@ -12,6 +12,17 @@ describe("Reward Distribution functional tests", () => {
   const program = anchor.workspace.RewardDistribution as Program<RewardDistribution>;
 
   let fixture: RewardDistributionTestFixture = null;
+  const createTransferAllowance = (authority: anchor.web3.PublicKey, limit: number, window: number) => program.methods
+    .createTransferAllowance(new anchor.BN(limit), new anchor.BN(window))
+    .accounts({
+      admin: fixture.admin.publicKey,
+      rewardDistributionInfo: fixture.distribution.info.publicKey,
+      authority: authority,
+      transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, authority),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([fixture.admin])
+    .rpc();
 
   before(async () => {
     fixture = await prepareRewardDistributionTestFixture(program);
@@ -36,10 +47,12 @@ describe("Reward Distribution functional tests", () => {
   it("Transfer tokens from fund with invalid authority", async () => {
     const invalidAuth = Keypair.generate();
     await utils.airdropSol(program.provider.connection, invalidAuth.publicKey, 1_000_000_000);
+    await createTransferAllowance(invalidAuth.publicKey, 100, 3600);
     await assert.rejects(program.methods.transfer(new anchor.BN(100))
       .accounts({
         authority: invalidAuth.publicKey,
         rewardDistributionInfo: fixture.distribution.info.publicKey,
+        transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, invalidAuth.publicKey),
         playToEarnFund: fixture.distribution.playToEarnFund,
         playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
         to: fixture.user.ggwpWallet,
@@ -56,12 +69,53 @@ describe("Reward Distribution functional tests", () => {
       });
   });
 
+  it("Create transfer allowance with invalid params", async () => {
+    await assert.rejects(program.methods
+      .createTransferAllowance(new anchor.BN(20_000_000_000), new anchor.BN(3600))
+      .accounts({
+        admin: fixture.updateAuth.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        authority: fixture.updateAuth.publicKey,
+        transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.updateAuth.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.updateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        return true;
+      });
+
+    await assert.rejects(createTransferAllowance(fixture.updateAuth.publicKey, 20_000_000_000, 0),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidAllowanceWindow");
+        assert.strictEqual(e.error.errorCode.number, 6006);
+        assert.strictEqual(e.error.errorMessage, "Invalid allowance window");
+        return true;
+      });
+  });
+
+  it("Create transfer allowance", async () => {
+    await createTransferAllowance(fixture.updateAuth.publicKey, 20_000_000_000, 3600);
+
+    const allowance = findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.updateAuth.publicKey);
+    const allowanceData = await program.account.transferAllowance.fetch(allowance);
+    assert.ok(allowanceData.authority.equals(fixture.updateAuth.publicKey));
+    assert.equal(allowanceData.limit.toNumber(), 20_000_000_000);
+    assert.equal(allowanceData.window.toNumber(), 3600);
+    assert.equal(allowanceData.used.toNumber(), 0);
+  });
+
   it("Transfer amount of tokens to user", async () => {
     const amount = 15_000_000_000;
     await program.methods.transfer(new anchor.BN(amount))
       .accounts({
         authority: fixture.updateAuth.publicKey,
         rewardDistributionInfo: fixture.distribution.info.publicKey,
+        transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.updateAuth.publicKey),
         playToEarnFund: fixture.distribution.playToEarnFund,
         playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
         to: fixture.user.ggwpWallet,
@@ -72,4 +126,113 @@ describe("Reward Distribution functional tests", () => {
 
     assert.equal(await utils.getTokenBalance(fixture.user.ggwpWallet), amount);
   });
+
+  it("Transfer amount of tokens over allowance", async () => {
+    const allowance = findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.updateAuth.publicKey);
+    const transfer = (amount: number) => program.methods.transfer(new anchor.BN(amount))
+      .accounts({
+        authority: fixture.updateAuth.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        transferAllowance: allowance,
+        playToEarnFund: fixture.distribution.playToEarnFund,
+        playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
+        to: fixture.user.ggwpWallet,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.updateAuth])
+      .rpc();
+
+    await assert.rejects(transfer(5_000_000_001),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AllowanceExceeded");
+        assert.strictEqual(e.error.errorCode.number, 6007);
+        assert.strictEqual(e.error.errorMessage, "Transfer allowance exceeded");
+        return true;
+      });
+
+    await transfer(5_000_000_000);
+    let allowanceData = await program.account.transferAllowance.fetch(allowance);
+    assert.equal(allowanceData.used.toNumber(), 20_000_000_000);
+
+    await program.methods
+      .updateTransferAllowance(new anchor.BN(30_000_000_000), new anchor.BN(3600))
+      .accounts({
+        admin: fixture.admin.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        transferAllowance: allowance,
+      })
+      .signers([fixture.admin])
+      .rpc();
+    await transfer(10_000_000_000);
+    allowanceData = await program.account.transferAllowance.fetch(allowance);
+    assert.equal(allowanceData.limit.toNumber(), 30_000_000_000);
+    assert.equal(allowanceData.used.toNumber(), 30_000_000_000);
+    assert.equal(await utils.getTokenBalance(fixture.user.ggwpWallet), 30_000_000_000);
+  });
+
+  it("Batch transfer tokens to recipients", async () => {
+    const allowance = findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.updateAuth.publicKey);
+    const recipients = [
+      await utils.createTokenWallet(fixture.distribution.ggwpToken, Keypair.generate().publicKey),
+      await utils.createTokenWallet(fixture.distribution.ggwpToken, Keypair.generate().publicKey),
+    ];
+    const batchTransfer = (amounts: number[], recipients: anchor.web3.PublicKey[]) => program.methods
+      .batchTransfer(amounts.map(amount => new anchor.BN(amount)))
+      .accounts({
+        authority: fixture.updateAuth.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        transferAllowance: allowance,
+        playToEarnFund: fixture.distribution.playToEarnFund,
+        playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(recipients.map(pubkey => ({ pubkey, isWritable: true, isSigner: false })))
+      .signers([fixture.updateAuth])
+      .rpc();
+
+    await program.methods
+      .updateTransferAllowance(new anchor.BN(40_000_000_000), new anchor.BN(3600))
+      .accounts({
+        admin: fixture.admin.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        transferAllowance: allowance,
+      })
+      .signers([fixture.admin])
+      .rpc();
+
+    await assert.rejects(batchTransfer([1_000_000_000], recipients),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidRecipientAccounts");
+        assert.strictEqual(e.error.errorCode.number, 6014);
+        assert.strictEqual(e.error.errorMessage, "Invalid recipient accounts");
+        return true;
+      });
+
+    const otherMint = await utils.createMint(fixture.admin.publicKey, 9);
+    const otherWallet = await utils.createTokenWallet(otherMint, Keypair.generate().publicKey);
+    await assert.rejects(batchTransfer([1_000_000_000, 1_000_000_000], [recipients[0], otherWallet]),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidRecipientMint");
+        assert.strictEqual(e.error.errorCode.number, 6015);
+        assert.strictEqual(e.error.errorMessage, "Invalid recipient mint");
+        return true;
+      });
+
+    await assert.rejects(batchTransfer([6_000_000_000, 4_000_000_001], recipients),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AllowanceExceeded");
+        assert.strictEqual(e.error.errorCode.number, 6007);
+        return true;
+      });
+
+    await batchTransfer([6_000_000_000, 4_000_000_000], recipients);
+    const allowanceData = await program.account.transferAllowance.fetch(allowance);
+    assert.equal(allowanceData.used.toNumber(), 40_000_000_000);
+    assert.equal(await utils.getTokenBalance(recipients[0]), 6_000_000_000);
+    assert.equal(await utils.getTokenBalance(recipients[1]), 4_000_000_000);
+  });
 });
@@ -0,0 +1,183 @@
+import * as anchor from "@project-serum/anchor";
+import { Program, AnchorError } from "@project-serum/anchor";
+import { SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { RewardDistribution } from "../../target/types/reward_distribution";
+import * as assert from "assert";
+import * as utils from "../utils";
+import {
+  RewardDistributionTestFixture,
+  prepareRewardDistributionTestFixture,
+  findTransferAllowance,
+  findVestingEscrow,
+  findVestingEscrowWallet,
+} from "./fixture";
+import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
+
+describe("Reward Distribution vesting tests", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.RewardDistribution as Program<RewardDistribution>;
+
+  let fixture: RewardDistributionTestFixture = null;
+  let vestingEscrow: anchor.web3.PublicKey = null;
+  let vestingEscrowWallet: anchor.web3.PublicKey = null;
+
+  const transferVested = (amount: number) => program.methods.transferVested(new anchor.BN(amount))
+    .accounts({
+      payer: fixture.transferAuth.publicKey,
+      authority: fixture.transferAuth.publicKey,
+      rewardDistributionInfo: fixture.distribution.info.publicKey,
+      transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.transferAuth.publicKey),
+      user: fixture.user.kp.publicKey,
+      vestingEscrow: vestingEscrow,
+      vestingEscrowWallet: vestingEscrowWallet,
+      ggwpToken: fixture.distribution.ggwpToken,
+      playToEarnFund: fixture.distribution.playToEarnFund,
+      playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: SYSVAR_RENT_PUBKEY,
+    })
+    .signers([fixture.transferAuth])
+    .rpc();
+
+  const claim = () => program.methods.claim()
+    .accounts({
+      user: fixture.user.kp.publicKey,
+      rewardDistributionInfo: fixture.distribution.info.publicKey,
+      vestingEscrow: vestingEscrow,
+      vestingEscrowWallet: vestingEscrowWallet,
+      userGgwpWallet: fixture.user.ggwpWallet,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([fixture.user.kp])
+    .rpc();
+
+  const updateVestingDuration = (authority: anchor.web3.Keypair, vestingDuration: number) => program.methods
+    .updateVestingDuration(new anchor.BN(vestingDuration))
+    .accounts({
+      authority: authority.publicKey,
+      rewardDistributionInfo: fixture.distribution.info.publicKey,
+    })
+    .signers([authority])
+    .rpc();
+
+  before(async () => {
+    fixture = await prepareRewardDistributionTestFixture(program);
+    await utils.airdropSol(program.provider.connection, fixture.transferAuth.publicKey, 1_000_000_000);
+    await program.methods.initialize(fixture.updateAuth.publicKey, [fixture.transferAuth.publicKey])
+      .accounts({
+        admin: fixture.admin.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        ggwpToken: fixture.distribution.ggwpToken,
+        playToEarnFund: fixture.distribution.playToEarnFund,
+        playToEarnFundAuth: fixture.distribution.playToEarnFundAuth,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.admin, fixture.distribution.info])
+      .rpc();
+    await program.methods.createTransferAllowance(new anchor.BN(50_000_000_000), new anchor.BN(3600))
+      .accounts({
+        admin: fixture.admin.publicKey,
+        rewardDistributionInfo: fixture.distribution.info.publicKey,
+        authority: fixture.transferAuth.publicKey,
+        transferAllowance: findTransferAllowance(program, fixture.distribution.info.publicKey, fixture.transferAuth.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.admin])
+      .rpc();
+
+    vestingEscrow = findVestingEscrow(program, fixture.distribution.info.publicKey, fixture.user.kp.publicKey);
+    vestingEscrowWallet = findVestingEscrowWallet(program, vestingEscrow);
+  });
+
+  it("Transfer vested with disabled vesting", async () => {
+    await assert.rejects(transferVested(1_000_000_000),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "VestingDisabled");
+        assert.strictEqual(e.error.errorCode.number, 6017);
+        assert.strictEqual(e.error.errorMessage, "Vesting is disabled");
+        return true;
+      });
+  });
+
+  it("Update vesting duration", async () => {
+    await assert.rejects(updateVestingDuration(fixture.admin, 4),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        return true;
+      });
+
+    await assert.rejects(updateVestingDuration(fixture.updateAuth, -1),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidVestingDuration");
+        assert.strictEqual(e.error.errorCode.number, 6016);
+        assert.strictEqual(e.error.errorMessage, "Invalid vesting duration");
+        return true;
+      });
+
+    await updateVestingDuration(fixture.updateAuth, 4);
+    const rewardDistributionInfoData = await program.account.rewardDistributionInfo.fetch(fixture.distribution.info.publicKey);
+    assert.equal(rewardDistributionInfoData.vestingDuration.toNumber(), 4);
+  });
+
+  it("Transfer vested and claim", async () => {
+    const amount = 2_000_000_000;
+    const userBalanceBefore = await utils.getTokenBalance(fixture.user.ggwpWallet);
+    await transferVested(amount);
+
+    let escrowData = await program.account.vestingEscrow.fetch(vestingEscrow);
+    assert.ok(escrowData.user.equals(fixture.user.kp.publicKey));
+    assert.equal(escrowData.totalAmount.toNumber(), amount);
+    assert.equal(escrowData.claimedAmount.toNumber(), 0);
+    assert.equal(escrowData.releasedAmount.toNumber(), 0);
+    assert.equal(escrowData.tranches.length, 1);
+    assert.equal(escrowData.tranches[0].amount.toNumber(), amount);
+    assert.equal(escrowData.tranches[0].endTime.toNumber() - escrowData.tranches[0].startTime.toNumber(), 4);
+    assert.equal(await utils.getTokenBalance(vestingEscrowWallet), amount);
+    assert.equal(await utils.getTokenBalance(fixture.user.ggwpWallet), userBalanceBefore);
+
+    await utils.sleep(5);
+    await claim();
+    escrowData = await program.account.vestingEscrow.fetch(vestingEscrow);
+    assert.equal(escrowData.claimedAmount.toNumber(), amount);
+    assert.equal(await utils.getTokenBalance(vestingEscrowWallet), 0);
+    assert.equal(await utils.getTokenBalance(fixture.user.ggwpWallet), userBalanceBefore + amount);
+
+    await assert.rejects(claim(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        assert.strictEqual(e.error.errorCode.number, 6018);
+        assert.strictEqual(e.error.errorMessage, "Nothing to claim");
+        return true;
+      });
+  });
+
+  it("Transfer vested keeps the schedule of the earlier transfers", async () => {
+    const amount = 1_000_000_000;
+    await transferVested(amount);
+    await utils.sleep(2);
+    await transferVested(amount);
+
+    const escrowData = await program.account.vestingEscrow.fetch(vestingEscrow);
+    assert.equal(escrowData.totalAmount.toNumber(), 4_000_000_000);
+    assert.equal(escrowData.releasedAmount.toNumber(), 2_000_000_000);
+    assert.equal(escrowData.tranches.length, 2);
+    const [first, second] = escrowData.tranches;
+    assert.equal(first.amount.toNumber(), amount);
+    assert.equal(second.amount.toNumber(), amount);
+    assert.ok(second.startTime.toNumber() > first.startTime.toNumber());
+    assert.equal(first.endTime.toNumber() - first.startTime.toNumber(), 4);
+    assert.equal(second.endTime.toNumber() - second.startTime.toNumber(), 4);
+
+    // First transfer is fully claimable at its original end
+    await utils.sleep(3);
+    const userBalanceBefore = await utils.getTokenBalance(fixture.user.ggwpWallet);
+    await claim();
+    assert.ok(await utils.getTokenBalance(fixture.user.ggwpWallet) >= userBalanceBefore + amount);
+  });
+});
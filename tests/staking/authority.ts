@@ -18,6 +18,7 @@ describe("Staking authority tests", () => {
   const aprStart = 50;
   const aprStep = 1;
   const aprEnd = 5;
+  const positionNftUri = "https://example.com/positions";
 
   let fixture: StakingTestFixture = null;
   before(async () => {
@@ -32,10 +33,14 @@ describe("Staking authority tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -437,4 +442,182 @@ describe("Staking authority tests", () => {
     const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
     assert.equal(stakingInfoData.holdPeriodDays, newHoldPeriod);
   });
+
+  it("Update APR schedule with invalid update auth", async () => {
+    await assert.rejects(program.methods
+      .updateAprSchedule(new anchor.BN(2), [4550])
+      .accounts({
+        authority: fixture.updateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([fixture.updateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        assert.strictEqual(e.error.errorMessage, "Access denied");
+        return true;
+      });
+  });
+
+  it("Update APR schedule of the current epoch", async () => {
+    await assert.rejects(program.methods
+      .updateAprSchedule(new anchor.BN(1), [4550])
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidAPRScheduleEpoch");
+        assert.strictEqual(e.error.errorCode.number, 6025);
+        assert.strictEqual(e.error.errorMessage, "APR can be scheduled for future epochs only");
+        return true;
+      });
+  });
+
+  it("Update APR schedule with zero APR", async () => {
+    await assert.rejects(program.methods
+      .updateAprSchedule(new anchor.BN(2), [4550, 0])
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidAPR");
+        assert.strictEqual(e.error.errorCode.number, 6007);
+        return true;
+      });
+  });
+
+  it("Update APR schedule of the future epochs", async () => {
+    await program.methods
+      .updateAprSchedule(new anchor.BN(2), [4550, 4425])
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc();
+
+    const aprScheduleData = await program.account.aprSchedule.fetch(fixture.staking.aprSchedule);
+    assert.equal(aprScheduleData.aprs[0], aprStart * 100);
+    assert.equal(aprScheduleData.aprs[1], 4550);
+    assert.equal(aprScheduleData.aprs[2], 4425);
+    assert.equal(aprScheduleData.aprs[3], (aprStart - 3 * aprStep) * 100);
+  });
+
+  it("Update lockup options with invalid update auth", async () => {
+    await assert.rejects(program.methods
+      .updateLockupOptions([{ lockupDays: 0, aprBoost: 10000 }])
+      .accounts({
+        authority: fixture.updateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([fixture.updateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "AccessDenied");
+        assert.strictEqual(e.error.errorCode.number, 6000);
+        return true;
+      });
+  });
+
+  it("Update lockup options with boost less than base", async () => {
+    await assert.rejects(program.methods
+      .updateLockupOptions([{ lockupDays: 0, aprBoost: 10000 }, { lockupDays: 90, aprBoost: 9000 }])
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidLockupOptions");
+        assert.strictEqual(e.error.errorCode.number, 6029);
+        return true;
+      });
+  });
+
+  it("Update lockup options", async () => {
+    const lockupOptions = [
+      { lockupDays: 0, aprBoost: 10000 },
+      { lockupDays: 90, aprBoost: 12500 },
+      { lockupDays: 365, aprBoost: 20000 },
+    ];
+    await program.methods
+      .updateLockupOptions(lockupOptions)
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc();
+
+    const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    assert.deepEqual(stakingInfoData.lockupOptions, lockupOptions);
+  });
+
+  it("Update APR schedule overflowing the boosted APR", async () => {
+    // 350% APR with 2x lockup boost
+    await assert.rejects(program.methods
+      .updateAprSchedule(new anchor.BN(2), [35000])
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidAPR");
+        assert.strictEqual(e.error.errorCode.number, 6007);
+        return true;
+      });
+  });
+
+  it("Update lockup options overflowing the boosted APR", async () => {
+    await program.methods
+      .updateAprSchedule(new anchor.BN(2), [20000])
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc();
+
+    // 200% APR with 4x lockup boost
+    await assert.rejects(program.methods
+      .updateLockupOptions([{ lockupDays: 0, aprBoost: 10000 }, { lockupDays: 365, aprBoost: 40000 }])
+      .accounts({
+        authority: newUpdateAuth.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .signers([newUpdateAuth])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidLockupOptions");
+        assert.strictEqual(e.error.errorCode.number, 6029);
+        return true;
+      });
+  });
 });
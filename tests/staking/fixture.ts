@@ -1,7 +1,9 @@
+import * as anchor from "@project-serum/anchor";
 import { Program } from "@project-serum/anchor";
 import { findProgramAddressSync } from "@project-serum/anchor/dist/cjs/utils/pubkey";
 import { utf8 } from "@project-serum/anchor/dist/cjs/utils/bytes";
 import { Keypair, PublicKey } from "@solana/web3.js";
+import { associatedAddress } from "@project-serum/anchor/dist/cjs/utils/token";
 import { Staking } from "../../target/types/staking";
 import * as utils from "../utils";
 
@@ -14,6 +16,8 @@ export class StakingTestFixture {
         ggwpToken: PublicKey;
         stakingFund: PublicKey;
         stakingFundAuth: PublicKey;
+        epochHistory: PublicKey;
+        aprSchedule: PublicKey;
         accumulativeFund: PublicKey;
         treasury: PublicKey;
         treasuryAuth: PublicKey,
@@ -55,6 +59,20 @@ export async function prepareStakingTestFixture(staking: Program<Staking>): Prom
         staking.programId,
     )[0];
     const stakingFund = await utils.createTokenWallet(ggwpToken, stakingFundAuth);
+    const epochHistory = findProgramAddressSync(
+        [
+            utf8.encode(utils.EPOCH_HISTORY_SEED),
+            stakingInfo.publicKey.toBytes(),
+        ],
+        staking.programId,
+    )[0];
+    const aprSchedule = findProgramAddressSync(
+        [
+            utf8.encode(utils.APR_SCHEDULE_SEED),
+            stakingInfo.publicKey.toBytes(),
+        ],
+        staking.programId,
+    )[0];
     await utils.mintTokens(ggwpToken, admin, stakingFund, 10000_000_000_000);
 
     const userGgwpTokenWallet = await utils.createTokenWallet(ggwpToken, user.publicKey);
@@ -78,6 +96,8 @@ export async function prepareStakingTestFixture(staking: Program<Staking>): Prom
             ggwpToken: ggwpToken,
             stakingFund: stakingFund,
             stakingFundAuth: stakingFundAuth,
+            epochHistory: epochHistory,
+            aprSchedule: aprSchedule,
             accumulativeFund: accumulativeFund,
             treasury: treasury,
             treasuryAuth: treasuryAuth,
@@ -89,3 +109,70 @@ export async function prepareStakingTestFixture(staking: Program<Staking>): Prom
         }
     }
 }
+
+export function findUserInfoPK(staking: Program<Staking>, stakingInfo: PublicKey, user: PublicKey): PublicKey {
+    return findProgramAddressSync(
+        [
+            utf8.encode(utils.USER_INFO_SEED),
+            stakingInfo.toBytes(),
+            user.toBytes(),
+        ],
+        staking.programId,
+    )[0];
+}
+
+export function findPositionPK(staking: Program<Staking>, stakingInfo: PublicKey, user: PublicKey, index: number): PublicKey {
+    return findProgramAddressSync(
+        [
+            utf8.encode(utils.POSITION_SEED),
+            stakingInfo.toBytes(),
+            user.toBytes(),
+            new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        ],
+        staking.programId,
+    )[0];
+}
+
+export const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+export function findPositionNFTAccounts(staking: Program<Staking>, stakingInfo: PublicKey, position: PublicKey, holder: PublicKey) {
+    const positionNftMint = findProgramAddressSync(
+        [
+            utf8.encode(utils.POSITION_NFT_MINT_SEED),
+            position.toBytes(),
+        ],
+        staking.programId,
+    )[0];
+    const positionNftAuth = findProgramAddressSync(
+        [
+            utf8.encode(utils.POSITION_NFT_AUTH_SEED),
+            stakingInfo.toBytes(),
+        ],
+        staking.programId,
+    )[0];
+    const positionNftMetadata = findProgramAddressSync(
+        [
+            utf8.encode("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBytes(),
+            positionNftMint.toBytes(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID,
+    )[0];
+    const positionNftEdition = findProgramAddressSync(
+        [
+            utf8.encode("metadata"),
+            TOKEN_METADATA_PROGRAM_ID.toBytes(),
+            positionNftMint.toBytes(),
+            utf8.encode("edition"),
+        ],
+        TOKEN_METADATA_PROGRAM_ID,
+    )[0];
+
+    return {
+        positionNftMint: positionNftMint,
+        positionNftAuth: positionNftAuth,
+        userPositionNftWallet: associatedAddress({ mint: positionNftMint, owner: holder }),
+        positionNftMetadata: positionNftMetadata,
+        positionNftEdition: positionNftEdition,
+    };
+}
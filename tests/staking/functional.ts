@@ -1,11 +1,18 @@
 import * as anchor from "@project-serum/anchor";
 import { AnchorError, Program } from "@project-serum/anchor";
-import { SystemProgram } from "@solana/web3.js";
+import { Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
 import { Staking } from "../../target/types/staking";
 import * as assert from "assert";
 import * as utils from "../utils";
-import { StakingTestFixture, prepareStakingTestFixture } from "./fixture";
-import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
+import {
+  StakingTestFixture,
+  prepareStakingTestFixture,
+  findPositionPK,
+  findPositionNFTAccounts,
+  findUserInfoPK,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./fixture";
+import { ASSOCIATED_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
 
 describe("Staking functional tests", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
@@ -19,6 +26,7 @@ describe("Staking functional tests", () => {
   const aprStart = 50;
   const aprStep = 1;
   const aprEnd = 5;
+  const positionNftUri = "https://example.com/positions";
 
   let fixture: StakingTestFixture = null;
   before(async () => {
@@ -33,10 +41,14 @@ describe("Staking functional tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -63,20 +75,31 @@ describe("Staking functional tests", () => {
     assert.equal(stakingInfoData.aprStep, aprStep);
     assert.equal(stakingInfoData.aprEnd, aprEnd);
     assert.equal(stakingInfoData.totalStaked, 0);
+    assert.equal(stakingInfoData.rewardLiability.toNumber(), 0);
+    assert.equal(stakingInfoData.totalIou.toNumber(), 0);
   });
 
   it("User stake amount of GGWP less than min stake amount", async () => {
     const stakeAmount = 5_000_000_000;
-    await assert.rejects(program.methods.stake(new anchor.BN(stakeAmount))
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await assert.rejects(program.methods.stake(new anchor.BN(stakeAmount), 0, false)
       .accounts({
         user: fixture.user.kp.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         userInfo: fixture.user.info,
+        position: position,
+        ...findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey),
         userGgwpWallet: fixture.user.ggwpWallet,
         accumulativeFund: fixture.staking.accumulativeFund,
         treasury: fixture.staking.treasury,
+        stakingFund: fixture.staking.stakingFund,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([fixture.user.kp])
       .rpc(),
@@ -90,27 +113,361 @@ describe("Staking functional tests", () => {
     );
   });
 
+  it("User stake amount of GGWP with invalid lockup option", async () => {
+    const stakeAmount = 5000_000_000_000;
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await assert.rejects(program.methods.stake(new anchor.BN(stakeAmount), 1, false)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: fixture.user.info,
+        position: position,
+        ...findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey),
+        userGgwpWallet: fixture.user.ggwpWallet,
+        accumulativeFund: fixture.staking.accumulativeFund,
+        treasury: fixture.staking.treasury,
+        stakingFund: fixture.staking.stakingFund,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([fixture.user.kp])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidLockupOption");
+        assert.strictEqual(e.error.errorCode.number, 6030);
+        return true;
+      }
+    );
+  });
+
   it("User stake amount of GGWP", async () => {
     const userTokenBalanceBefore = await utils.getTokenBalance(fixture.user.ggwpWallet);
     const stakeAmount = 5000_000_000_000;
-    await program.methods.stake(new anchor.BN(stakeAmount))
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await program.methods.stake(new anchor.BN(stakeAmount), 0, false)
       .accounts({
         user: fixture.user.kp.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         userInfo: fixture.user.info,
+        position: position,
+        ...findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey),
         userGgwpWallet: fixture.user.ggwpWallet,
         accumulativeFund: fixture.staking.accumulativeFund,
         treasury: fixture.staking.treasury,
+        stakingFund: fixture.staking.stakingFund,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
       })
       .signers([fixture.user.kp])
       .rpc();
 
     const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
     assert.equal(stakingInfoData.totalStaked.toNumber(), stakeAmount - utils.calcRoyaltyAmount(stakeAmount, stakingInfoData.royalty));
+    assert.equal(stakingInfoData.rewardAccruals.length, 1);
+    assert.equal(stakingInfoData.rewardAccruals[0].aprBoost, 10000);
+    assert.equal(stakingInfoData.rewardAccruals[0].amount.toNumber(), stakingInfoData.totalStaked.toNumber());
+    assert.equal(stakingInfoData.rewardAccruals[0].positionsCount.toNumber(), 1);
     const userInfoData = await program.account.userInfo.fetch(fixture.user.info);
     assert.equal(userInfoData.amount.toNumber(), stakeAmount - utils.calcRoyaltyAmount(stakeAmount, stakingInfoData.royalty));
+    assert.equal(userInfoData.positionsCount.toNumber(), 1);
+    const positionData = await program.account.position.fetch(position);
+    assert.ok(positionData.user.equals(fixture.user.kp.publicKey));
+    assert.equal(positionData.index.toNumber(), 0);
+    assert.equal(positionData.amount.toNumber(), stakeAmount - utils.calcRoyaltyAmount(stakeAmount, stakingInfoData.royalty));
     assert.equal(await utils.getTokenBalance(fixture.user.ggwpWallet), userTokenBalanceBefore - stakeAmount);
+
+    const positionNft = findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey);
+    assert.ok(positionData.nftMint.equals(positionNft.positionNftMint));
+    assert.equal(await utils.getTokenBalance(positionNft.userPositionNftWallet), 1);
+    const editionInfo = await program.provider.connection.getAccountInfo(positionNft.positionNftEdition);
+    assert.ok(editionInfo.owner.equals(TOKEN_METADATA_PROGRAM_ID));
+  });
+
+  it("User stake additional amount of GGWP in new position", async () => {
+    const stakeAmount = 4000_000_000_000;
+    const stakingInfoBefore = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    const userInfoBefore = await program.account.userInfo.fetch(fixture.user.info);
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 1);
+    await program.methods.stake(new anchor.BN(stakeAmount), 0, false)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: fixture.user.info,
+        position: position,
+        ...findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey),
+        userGgwpWallet: fixture.user.ggwpWallet,
+        accumulativeFund: fixture.staking.accumulativeFund,
+        treasury: fixture.staking.treasury,
+        stakingFund: fixture.staking.stakingFund,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    const stakedAmount = stakeAmount - utils.calcRoyaltyAmount(stakeAmount, stakingInfoBefore.royalty);
+    const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    assert.equal(stakingInfoData.totalStaked.toNumber(), stakingInfoBefore.totalStaked.toNumber() + stakedAmount);
+    const userInfoData = await program.account.userInfo.fetch(fixture.user.info);
+    assert.equal(userInfoData.amount.toNumber(), userInfoBefore.amount.toNumber() + stakedAmount);
+    assert.equal(userInfoData.positionsCount.toNumber(), 2);
+    const positionData = await program.account.position.fetch(position);
+    assert.equal(positionData.index.toNumber(), 1);
+    assert.equal(positionData.amount.toNumber(), stakedAmount);
+  });
+
+  it("Funder stake amount of GGWP for beneficiary", async () => {
+    const stakeAmount = 3000_000_000_000;
+    const beneficiary = Keypair.generate();
+    const beneficiaryInfo = findUserInfoPK(program, fixture.staking.info.publicKey, beneficiary.publicKey);
+    const position = findPositionPK(program, fixture.staking.info.publicKey, beneficiary.publicKey, 0);
+    await utils.mintTokens(fixture.staking.ggwpToken, fixture.admin, fixture.user.ggwpWallet, stakeAmount);
+    const funderTokenBalanceBefore = await utils.getTokenBalance(fixture.user.ggwpWallet);
+    const stakingInfoBefore = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    await program.methods.stakeFor(new anchor.BN(stakeAmount), 0, false)
+      .accounts({
+        funder: fixture.user.kp.publicKey,
+        beneficiary: beneficiary.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: beneficiaryInfo,
+        position: position,
+        ...findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, beneficiary.publicKey),
+        funderGgwpWallet: fixture.user.ggwpWallet,
+        accumulativeFund: fixture.staking.accumulativeFund,
+        treasury: fixture.staking.treasury,
+        stakingFund: fixture.staking.stakingFund,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    const stakedAmount = stakeAmount - utils.calcRoyaltyAmount(stakeAmount, stakingInfoBefore.royalty);
+    const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    assert.equal(stakingInfoData.totalStaked.toNumber(), stakingInfoBefore.totalStaked.toNumber() + stakedAmount);
+    const beneficiaryInfoData = await program.account.userInfo.fetch(beneficiaryInfo);
+    assert.equal(beneficiaryInfoData.amount.toNumber(), stakedAmount);
+    assert.equal(beneficiaryInfoData.positionsCount.toNumber(), 1);
+    const positionData = await program.account.position.fetch(position);
+    assert.ok(positionData.user.equals(beneficiary.publicKey));
+    assert.equal(positionData.amount.toNumber(), stakedAmount);
+    assert.equal(await utils.getTokenBalance(fixture.user.ggwpWallet), funderTokenBalanceBefore - stakeAmount);
+
+    const positionNft = findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, beneficiary.publicKey);
+    assert.equal(await utils.getTokenBalance(positionNft.userPositionNftWallet), 1);
+  });
+
+  it("Advance epoch inside current epoch does nothing", async () => {
+    await program.methods.advanceEpoch()
+      .accounts({
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+      })
+      .rpc();
+
+    const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    assert.equal(stakingInfoData.epoch.toNumber(), 1);
+    const epochHistoryData = await program.account.epochHistory.fetch(fixture.staking.epochHistory);
+    assert.equal(epochHistoryData.aprs.length, 0);
+  });
+
+  it("User can't claim rewards before the epoch is completed", async () => {
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await assert.rejects(program.methods.claimRewards()
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: fixture.user.info,
+        position: position,
+        userPositionNftWallet: findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey).userPositionNftWallet,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        stakingFund: fixture.staking.stakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        assert.strictEqual(e.error.errorCode.number, 6021);
+        assert.strictEqual(e.error.errorMessage, "Nothing to claim");
+        return true;
+      }
+    );
+  });
+
+  it("Anyone can't compound the position without auto compound", async () => {
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await assert.rejects(program.methods.compound()
+      .accounts({
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        stakerInfo: fixture.user.info,
+        position: position,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        stakingFund: fixture.staking.stakingFund,
+        treasury: fixture.staking.treasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NotAutoCompoundPosition");
+        assert.strictEqual(e.error.errorCode.number, 6032);
+        assert.strictEqual(e.error.errorMessage, "Position is not auto-compounding");
+        return true;
+      }
+    );
+  });
+
+  it("User set auto compound of the position", async () => {
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await program.methods.setAutoCompound(true)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        position: position,
+        userPositionNftWallet: findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey).userPositionNftWallet,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    const positionData = await program.account.position.fetch(position);
+    assert.equal(positionData.autoCompound, true);
+  });
+
+  it("Anyone can't compound the position before the epoch is completed", async () => {
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await assert.rejects(program.methods.compound()
+      .accounts({
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        stakerInfo: fixture.user.info,
+        position: position,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        stakingFund: fixture.staking.stakingFund,
+        treasury: fixture.staking.treasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        assert.strictEqual(e.error.errorCode.number, 6021);
+        return true;
+      }
+    );
+  });
+
+  it("User can't claim rewards of the transferred position", async () => {
+    const position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 1);
+    const positionNft = findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey);
+    const holder = Keypair.generate();
+    const holderNftWallet = await utils.createTokenWallet(positionNft.positionNftMint, holder.publicKey);
+    await utils.transferTokens(positionNft.userPositionNftWallet, fixture.user.kp, holderNftWallet, 1);
+    assert.equal(await utils.getTokenBalance(holderNftWallet), 1);
+
+    await assert.rejects(program.methods.claimRewards()
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: fixture.user.info,
+        position: position,
+        userPositionNftWallet: positionNft.userPositionNftWallet,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        stakingFund: fixture.staking.stakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NotPositionHolder");
+        assert.strictEqual(e.error.errorCode.number, 6028);
+        assert.strictEqual(e.error.errorMessage, "Position NFT is not held by user");
+        return true;
+      }
+    );
+  });
+
+  it("User can't claim IOU from another staking fund", async () => {
+    const otherStakingFund = await utils.createTokenWallet(fixture.staking.ggwpToken, fixture.staking.stakingFundAuth);
+
+    await assert.rejects(program.methods.claimIou()
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        userInfo: fixture.user.info,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        stakingFund: otherStakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidStakingFundPK");
+        assert.strictEqual(e.error.errorCode.number, 6022);
+        return true;
+      }
+    );
+  });
+
+  it("User can't claim IOU without unpaid rewards", async () => {
+    const userInfoData = await program.account.userInfo.fetch(fixture.user.info);
+    assert.equal(userInfoData.rewardIou.toNumber(), 0);
+
+    await assert.rejects(program.methods.claimIou()
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        userInfo: fixture.user.info,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        stakingFund: fixture.staking.stakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        assert.strictEqual(e.error.errorCode.number, 6021);
+        return true;
+      }
+    );
   });
 });
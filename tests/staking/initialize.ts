@@ -17,6 +17,7 @@ describe("Staking initialize tests", () => {
   const aprStart = 50;
   const aprStep = 1;
   const aprEnd = 5;
+  const positionNftUri = "https://example.com/positions";
 
   let fixture: StakingTestFixture = null;
   before(async () => {
@@ -34,10 +35,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -67,10 +72,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -100,10 +109,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -133,10 +146,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -166,10 +183,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -199,10 +220,14 @@ describe("Staking initialize tests", () => {
       0,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -232,10 +257,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       0,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -265,10 +294,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       aprStep,
       0,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -289,6 +322,45 @@ describe("Staking initialize tests", () => {
     );
   });
 
+  it("Initialize with invalid position NFT URI", async () => {
+    await assert.rejects(program.methods.initialize(
+      fixture.updateAuth.publicKey,
+      epochPeriodDays,
+      new anchor.BN(minStakeAmount),
+      holdPeriodDays,
+      holdRoyalty,
+      royalty,
+      aprStart,
+      aprStep,
+      aprEnd,
+      { fixedApr: {} },
+      "",
+    )
+      .accounts({
+        admin: fixture.admin.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        ggwpToken: fixture.staking.ggwpToken,
+        accumulativeFund: fixture.staking.accumulativeFund,
+        stakingFund: fixture.staking.stakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        treasury: fixture.staking.treasury,
+        treasuryAuth: fixture.staking.treasuryAuth,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.admin, fixture.staking.info])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "InvalidPositionNFTUri");
+        assert.strictEqual(e.error.errorCode.number, 6034);
+        assert.strictEqual(e.error.errorMessage, "Invalid position NFT URI");
+        return true;
+      }
+    );
+  });
+
   it("Initialize", async () => {
     await program.methods.initialize(
       fixture.updateAuth.publicKey,
@@ -300,10 +372,14 @@ describe("Staking initialize tests", () => {
       aprStart,
       aprStep,
       aprEnd,
+      { fixedApr: {} },
+      positionNftUri,
     )
       .accounts({
         admin: fixture.admin.publicKey,
         stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
         ggwpToken: fixture.staking.ggwpToken,
         accumulativeFund: fixture.staking.accumulativeFund,
         stakingFund: fixture.staking.stakingFund,
@@ -330,5 +406,14 @@ describe("Staking initialize tests", () => {
     assert.equal(stakingInfoData.aprStep, aprStep);
     assert.equal(stakingInfoData.aprEnd, aprEnd);
     assert.equal(stakingInfoData.totalStaked, 0);
+    assert.deepEqual(stakingInfoData.poolMode, { fixedApr: {} });
+    assert.equal(stakingInfoData.totalBoostedStaked, 0);
+    assert.deepEqual(stakingInfoData.lockupOptions, [{ lockupDays: 0, aprBoost: 10000 }]);
+    assert.equal(stakingInfoData.positionNftUri, positionNftUri);
+
+    const aprScheduleData = await program.account.aprSchedule.fetch(fixture.staking.aprSchedule);
+    assert.equal(aprScheduleData.aprs.length, (aprStart - aprEnd) / aprStep + 1);
+    assert.equal(aprScheduleData.aprs[0], aprStart * 100);
+    assert.equal(aprScheduleData.aprs[aprScheduleData.aprs.length - 1], aprEnd * 100);
   });
 });
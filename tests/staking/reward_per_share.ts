@@ -0,0 +1,196 @@
+import * as anchor from "@project-serum/anchor";
+import { AnchorError, Program } from "@project-serum/anchor";
+import { SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import { Staking } from "../../target/types/staking";
+import * as assert from "assert";
+import * as utils from "../utils";
+import {
+  StakingTestFixture,
+  prepareStakingTestFixture,
+  findPositionPK,
+  findPositionNFTAccounts,
+  TOKEN_METADATA_PROGRAM_ID,
+} from "./fixture";
+import { ASSOCIATED_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token";
+
+describe("Staking reward per share tests", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const program = anchor.workspace.Staking as Program<Staking>;
+
+  const epochPeriodDays = 45;
+  const minStakeAmount = 3000_000_000_000;
+  const holdPeriodDays = 30;
+  const holdRoyalty = 15;
+  const royalty = 8;
+  const aprStart = 50;
+  const aprStep = 1;
+  const aprEnd = 5;
+  const positionNftUri = "https://example.com/positions";
+
+  let fixture: StakingTestFixture = null;
+  let position = null;
+  before(async () => {
+    fixture = await prepareStakingTestFixture(program);
+    position = findPositionPK(program, fixture.staking.info.publicKey, fixture.user.kp.publicKey, 0);
+    await program.methods.initialize(
+      fixture.updateAuth.publicKey,
+      epochPeriodDays,
+      new anchor.BN(minStakeAmount),
+      holdPeriodDays,
+      holdRoyalty,
+      royalty,
+      aprStart,
+      aprStep,
+      aprEnd,
+      { rewardPerShare: {} },
+      positionNftUri,
+    )
+      .accounts({
+        admin: fixture.admin.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        ggwpToken: fixture.staking.ggwpToken,
+        accumulativeFund: fixture.staking.accumulativeFund,
+        stakingFund: fixture.staking.stakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        treasury: fixture.staking.treasury,
+        treasuryAuth: fixture.staking.treasuryAuth,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([fixture.admin, fixture.staking.info])
+      .rpc();
+
+    const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    assert.deepEqual(stakingInfoData.poolMode, { rewardPerShare: {} });
+    assert.equal(stakingInfoData.rewardPerShare.toNumber(), 0);
+    assert.equal(stakingInfoData.rewardReserve.toNumber(), 0);
+  });
+
+  it("User stake amount of GGWP", async () => {
+    const stakeAmount = 5000_000_000_000;
+    await program.methods.stake(new anchor.BN(stakeAmount), 0, false)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: fixture.user.info,
+        position: position,
+        ...findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey),
+        userGgwpWallet: fixture.user.ggwpWallet,
+        accumulativeFund: fixture.staking.accumulativeFund,
+        treasury: fixture.staking.treasury,
+        stakingFund: fixture.staking.stakingFund,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    const positionData = await program.account.position.fetch(position);
+    assert.equal(positionData.rewardPerSharePaid.toNumber(), 0);
+  });
+
+  it("User claim staking fund deposits", async () => {
+    await utils.mintTokens(fixture.staking.ggwpToken, fixture.admin, fixture.staking.stakingFund, 1000_000_000_000);
+    const stakingFundBalance = await utils.getTokenBalance(fixture.staking.stakingFund);
+    const userTokenBalanceBefore = await utils.getTokenBalance(fixture.user.ggwpWallet);
+
+    await program.methods.claimRewards()
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: fixture.user.info,
+        position: position,
+        userPositionNftWallet: findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey).userPositionNftWallet,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        stakingFund: fixture.staking.stakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    // The only staker gets all the deposits except rounding dust
+    const reward = await utils.getTokenBalance(fixture.user.ggwpWallet) - userTokenBalanceBefore;
+    assert.ok(utils.assertWithPrecission(reward, stakingFundBalance, 1));
+    const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    assert.equal(stakingInfoData.rewardReserve.toNumber(), stakingFundBalance - reward);
+    assert.equal(stakingInfoData.rewardLiability.toNumber(), 0);
+  });
+
+  it("User can't claim without new deposits", async () => {
+    await assert.rejects(program.methods.claimRewards()
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        userInfo: fixture.user.info,
+        position: position,
+        userPositionNftWallet: findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey).userPositionNftWallet,
+        userGgwpWallet: fixture.user.ggwpWallet,
+        stakingFund: fixture.staking.stakingFund,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([fixture.user.kp])
+      .rpc(),
+      (e: AnchorError) => {
+        assert.ok(e.error !== undefined);
+        assert.strictEqual(e.error.errorCode.code, "NothingToClaim");
+        assert.strictEqual(e.error.errorCode.number, 6021);
+        return true;
+      }
+    );
+  });
+
+  it("Anyone compound staking fund deposits into the auto-compounding position", async () => {
+    await program.methods.setAutoCompound(true)
+      .accounts({
+        user: fixture.user.kp.publicKey,
+        stakingInfo: fixture.staking.info.publicKey,
+        position: position,
+        userPositionNftWallet: findPositionNFTAccounts(program, fixture.staking.info.publicKey, position, fixture.user.kp.publicKey).userPositionNftWallet,
+      })
+      .signers([fixture.user.kp])
+      .rpc();
+
+    await utils.mintTokens(fixture.staking.ggwpToken, fixture.admin, fixture.staking.stakingFund, 1000_000_000_000);
+    const stakingFundBalance = await utils.getTokenBalance(fixture.staking.stakingFund);
+    const treasuryBalanceBefore = await utils.getTokenBalance(fixture.staking.treasury);
+    const stakingInfoBefore = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    const positionBefore = await program.account.position.fetch(position);
+
+    await program.methods.compound()
+      .accounts({
+        stakingInfo: fixture.staking.info.publicKey,
+        epochHistory: fixture.staking.epochHistory,
+        aprSchedule: fixture.staking.aprSchedule,
+        stakerInfo: fixture.user.info,
+        position: position,
+        stakingFundAuth: fixture.staking.stakingFundAuth,
+        stakingFund: fixture.staking.stakingFund,
+        treasury: fixture.staking.treasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const reward = await utils.getTokenBalance(fixture.staking.treasury) - treasuryBalanceBefore;
+    assert.ok(utils.assertWithPrecission(reward, stakingFundBalance, 1));
+    const positionData = await program.account.position.fetch(position);
+    assert.equal(positionData.amount.toNumber(), positionBefore.amount.toNumber() + reward);
+    const stakingInfoData = await program.account.stakingInfo.fetch(fixture.staking.info.publicKey);
+    assert.equal(stakingInfoData.totalStaked.toNumber(), stakingInfoBefore.totalStaked.toNumber() + reward);
+    const userInfoData = await program.account.userInfo.fetch(fixture.user.info);
+    assert.equal(userInfoData.amount.toNumber(), positionData.amount.toNumber());
+  });
+});
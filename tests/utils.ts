@@ -8,13 +8,25 @@ const tokenProgram = anchor.Spl.token();
 export const GPASS_MINT_AUTH_SEED = "gpass_mint_auth";
 export const TREASURY_AUTH_SEED = "treasury_auth";
 export const STAKING_FUND_AUTH_SEED = "staking_fund_auth";
+export const EPOCH_HISTORY_SEED = "epoch_history";
+export const POSITION_SEED = "position";
+export const APR_SCHEDULE_SEED = "apr_schedule";
+export const POSITION_NFT_MINT_SEED = "position_nft_mint";
+export const POSITION_NFT_AUTH_SEED = "position_nft_auth";
 export const USER_INFO_SEED = "user_info";
 export const USER_WALLET_SEED = "user_gpass_wallet";
 export const ACCUMULATIVE_FUND_AUTH_SEED = "accumulative_fund_auth";
+export const DISTRIBUTION_HISTORY_SEED = "distribution_history";
+export const VESTING_ESCROW_SEED = "vesting_escrow";
+export const VESTING_ESCROW_AUTH_SEED = "vesting_escrow_auth";
+export const PENDING_RECIPIENTS_SEED = "pending_recipients";
 export const GPASS_BURN_AUTH_SEED = "gpass_burn_auth";
 export const GAME_INFO_SEED = "game_info";
 export const PLAY_TO_EARN_FUND_AUTH_SEED = "play_to_earn_fund_auth";
+export const TRANSFER_ALLOWANCE_SEED = "transfer_allowance";
+export const VESTING_ESCROW_WALLET_SEED = "vesting_escrow_wallet";
 export const REWARD_TRANSFER_AUTH_SEED = "reward_transfer_auth";
+export const REFERRER_INFO_SEED = "referrer_info";
 
 export async function airdropSol(conn: Connection, to: PublicKey, amount: number) {
     const airdropSignature = await conn.requestAirdrop(to, amount);
@@ -73,6 +85,17 @@ export async function createTokenWallet(mint: PublicKey, authority: PublicKey):
     return wallet.publicKey;
 }
 
+export async function transferTokens(from: PublicKey, authority: Keypair, to: PublicKey, amount: number): Promise<void> {
+    await tokenProgram.methods.transfer(new anchor.BN(amount))
+        .accounts({
+            source: from,
+            destination: to,
+            authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+}
+
 export async function mintTokens(mint: PublicKey, authority: Keypair, wallet: PublicKey, amount: number): Promise<void> {
     await tokenProgram.methods.mintTo(new anchor.BN(amount))
         .accounts({